@@ -22,5 +22,23 @@ sol!(
     ERC20, "../../out/ERC20.sol/ERC20.json"
 );
 
+sol!(
+    #![sol(all_derives = true)]
+    GenericPoolOrderBookV4ArbOrderTaker,
+    "../../out/GenericPoolOrderBookV4ArbOrderTaker.sol/GenericPoolOrderBookV4ArbOrderTaker.json"
+);
+
+sol!(
+    #![sol(all_derives = true)]
+    RouteProcessorOrderBookV4ArbOrderTaker,
+    "../../out/RouteProcessorOrderBookV4ArbOrderTaker.sol/RouteProcessorOrderBookV4ArbOrderTaker.json"
+);
+
+sol!(
+    #![sol(all_derives = true)]
+    GenericPoolOrderBookV4FlashBorrower,
+    "../../out/GenericPoolOrderBookV4FlashBorrower.sol/GenericPoolOrderBookV4FlashBorrower.json"
+);
+
 #[cfg(target_family = "wasm")]
 pub mod js_api;
@@ -1,7 +1,10 @@
 use crate::execute::Execute;
 use anyhow::{anyhow, Result};
 use clap::Args;
-use rain_orderbook_app_settings::{config_source::ConfigSource, Config};
+use rain_orderbook_app_settings::{
+    config_source::{ConfigSource, ParseContext},
+    Config,
+};
 use rain_orderbook_common::dotrain::RainDocument;
 use rain_orderbook_common::fuzz::FuzzRunner;
 use std::fs::read_to_string;
@@ -22,9 +25,13 @@ impl Execute for Chart {
     async fn execute(&self) -> Result<()> {
         let dotrain = read_to_string(self.dotrain_file.clone()).map_err(|e| anyhow!(e))?;
         let frontmatter = RainDocument::get_front_matter(&dotrain).unwrap();
-        let config_string = ConfigSource::try_from_string(frontmatter.to_string(), None)
-            .await?
-            .0;
+        let config_string = ConfigSource::try_from_string_with_context(
+            frontmatter.to_string(),
+            None,
+            &ParseContext::load_for_file(&self.dotrain_file)?,
+        )
+        .await?
+        .0;
         let config: Config = config_string.try_into()?;
         let fuzzer = FuzzRunner::new(&dotrain, config, None).await;
         let chart_data = fuzzer.make_chart_data().await?;
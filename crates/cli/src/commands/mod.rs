@@ -1,11 +1,13 @@
 mod chart;
 mod order;
 mod quote;
+mod settings;
 mod subgraph;
 mod trade;
 mod vault;
 mod words;
 
 pub use self::{
-    chart::Chart, order::Order, subgraph::Subgraph, trade::Trade, vault::Vault, words::Words,
+    chart::Chart, order::Order, settings::Settings, subgraph::Subgraph, trade::Trade, vault::Vault,
+    words::Words,
 };
@@ -1,11 +1,20 @@
 use crate::{
-    execute::Execute, status::display_write_transaction_status, transaction::CliTransactionArgs,
+    execute::Execute,
+    output::{output, SupportedOutputEncoding},
+    status::display_write_transaction_status,
+    transaction::CliTransactionArgs,
 };
+use alloy::primitives::{Address, B256};
 use anyhow::{anyhow, Result};
 use clap::{ArgAction, Args};
-use rain_orderbook_app_settings::Config;
+use rain_orderbook_app_settings::{
+    config_source::ParseContext,
+    deployment::{Deployment, VaultIdMode},
+    Config,
+};
 use rain_orderbook_common::add_order::AddOrderArgs;
-use rain_orderbook_common::frontmatter::parse_frontmatter;
+use rain_orderbook_common::add_order_and_deposit::AddOrderAndDepositArgs;
+use rain_orderbook_common::frontmatter::parse_frontmatter_with_context;
 use rain_orderbook_common::transaction::TransactionArgs;
 use rain_orderbook_common::GH_COMMIT_SHA;
 use std::fs::read_to_string;
@@ -32,15 +41,42 @@ pub struct CliOrderAddArgs {
     #[arg(long, action = ArgAction::SetTrue)]
     pub no_broadcast: bool,
 
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
+
     /// Skips checking the `raindex-version` against this cli app current version
     #[arg(long, action = ArgAction::SetTrue)]
     pub skip_version_check: bool,
+
+    /// Only output the approve and multicall calldata of adding the order and
+    /// depositing into its vaults, eg to submit through a multisig
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub calldata: bool,
+
+    #[arg(long, help = "Calldata output encoding", default_value = "hex")]
+    pub encoding: SupportedOutputEncoding,
+
+    #[arg(
+        long,
+        help = "Owner that deterministic vault ids are derived for, defaults to the signer"
+    )]
+    pub owner: Option<Address>,
+
+    #[arg(
+        long,
+        help = "Salt to derive the order nonce and secret from along with the deployment key, random if not set"
+    )]
+    pub salt: Option<B256>,
 }
 
 impl CliOrderAddArgs {
-    async fn to_add_order_args(&self) -> Result<AddOrderArgs> {
+    async fn to_deployment(&self) -> Result<(String, Deployment)> {
         let text = read_to_string(&self.dotrain_file).map_err(|e| anyhow!(e))?;
-        let config: Config = parse_frontmatter(text.clone()).await?.try_into()?;
+        let config: Config = parse_frontmatter_with_context(text.clone(), &ParseContext::load()?)
+            .await?
+            .try_into()?;
 
         if !self.skip_version_check {
             if let Some(ver) = config.raindex_version {
@@ -62,19 +98,131 @@ impl CliOrderAddArgs {
             .get(&self.deployment)
             .ok_or(anyhow!("specified deployment is undefined!"))?;
 
-        Ok(
-            AddOrderArgs::new_from_deployment(text.clone(), config_deployment.deref().clone())
-                .await?,
-        )
+        Ok((text, config_deployment.deref().clone()))
+    }
+
+    /// Owner to derive the deployment's vault ids for, only resolved from the
+    /// signer when the deployment uses deterministic vault ids
+    async fn to_owner(&self, deployment: &Deployment) -> Result<Option<Address>> {
+        if self.owner.is_some() {
+            return Ok(self.owner);
+        }
+        if deployment.vault_id_mode != Some(VaultIdMode::Deterministic) {
+            return Ok(None);
+        }
+        if self.calldata {
+            return Err(anyhow!(
+                "--owner is required for deterministic vault ids when only outputting calldata"
+            ));
+        }
+        let tx_args: TransactionArgs = self.transaction_args.clone().into();
+        Ok(Some(tx_args.try_into_signer().await?.address()))
+    }
+
+    async fn to_add_order_args(
+        &self,
+        text: String,
+        deployment: Deployment,
+    ) -> Result<AddOrderArgs> {
+        let owner = self.to_owner(&deployment).await?;
+        let args = AddOrderArgs::new_from_deployment_with_owner(text, deployment, owner).await?;
+        Ok(match self.salt {
+            Some(salt) => args.with_derived_nonce_and_secret(&self.deployment, salt),
+            None => args,
+        })
+    }
+
+    async fn to_add_order_and_deposit_args(
+        &self,
+        text: String,
+        deployment: Deployment,
+    ) -> Result<AddOrderAndDepositArgs> {
+        let owner = self.to_owner(&deployment).await?;
+        let mut args = AddOrderAndDepositArgs::new_from_deployment(text, deployment, owner).await?;
+        if let Some(salt) = self.salt {
+            args.add_order = args
+                .add_order
+                .with_derived_nonce_and_secret(&self.deployment, salt);
+        }
+        Ok(args)
+    }
+
+    async fn execute_add_order_and_deposit(
+        &self,
+        text: String,
+        deployment: Deployment,
+        mut tx_args: TransactionArgs,
+    ) -> Result<()> {
+        let args = self.to_add_order_and_deposit_args(text, deployment).await?;
+
+        if self.calldata {
+            for (token, approve_calldata) in args.get_approve_calldatas(tx_args.clone()) {
+                info!("----- Approve calldata for token {} -----", token);
+                output(&None, self.encoding.clone(), &approve_calldata)?;
+                println!();
+            }
+            info!("----- Add order and deposit multicall calldata -----");
+            output(
+                &None,
+                self.encoding.clone(),
+                &args.get_multicall_calldata(tx_args).await?,
+            )?;
+            println!();
+            return Ok(());
+        }
+
+        tx_args.try_fill_chain_id().await?;
+
+        if self.simulate {
+            info!("----- Simulating Add Order and Deposit -----");
+            let report = args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
+        info!("----- Simulating Transaction -----");
+        args.simulate_execute(tx_args.clone(), None).await?;
+        info!("----- Finished Simulation Successfully -----");
+
+        if !self.no_broadcast {
+            info!("----- Approve ERC20 token spends -----");
+            args.execute_approvals(tx_args.clone(), |token, status| {
+                info!("Token {}", token);
+                display_write_transaction_status(status);
+            })
+            .await?;
+
+            info!("----- Add Order and Deposit -----");
+            args.execute(tx_args, |status| {
+                display_write_transaction_status(status);
+            })
+            .await?;
+        }
+
+        Ok(())
     }
 }
 
 impl Execute for CliOrderAddArgs {
     async fn execute(&self) -> Result<()> {
-        let add_order_args: AddOrderArgs = self.clone().to_add_order_args().await?;
         let mut tx_args: TransactionArgs = self.transaction_args.clone().into();
+        let (text, deployment) = self.to_deployment().await?;
+        if self.calldata || deployment.deposits.is_some() {
+            return self
+                .execute_add_order_and_deposit(text, deployment, tx_args)
+                .await;
+        }
+
+        let add_order_args = self.to_add_order_args(text, deployment).await?;
         tx_args.try_fill_chain_id().await?;
 
+        if self.simulate {
+            info!("----- Simulating Add Order -----");
+            let report = add_order_args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
         info!("----- Simulating Transaction -----");
         add_order_args
             .simulate_execute(tx_args.clone(), None)
@@ -97,6 +245,7 @@ impl Execute for CliOrderAddArgs {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transaction::CliSignerKind;
     use alloy::primitives::{Address, U256};
     use rain_orderbook_bindings::IOrderBookV4::IO;
     use std::{collections::HashMap, str::FromStr};
@@ -110,21 +259,36 @@ mod tests {
 
         let cli_order_add_args = CliOrderAddArgs {
             no_broadcast: false,
+            simulate: false,
             dotrain_file: dotrain_path.into(),
             deployment: "some-deployment".to_string(),
             skip_version_check: false,
+            calldata: false,
+            encoding: SupportedOutputEncoding::Hex,
+            owner: None,
+            salt: None,
             transaction_args: CliTransactionArgs {
                 orderbook_address: Address::random(),
                 derivation_index: None,
                 chain_id: Some(123),
                 rpc_url: "https://some-rpc.com".to_string(),
+                fallback_rpc_urls: vec![],
                 max_fee_per_gas: None,
                 max_priority_fee_per_gas: None,
                 gas_fee_speed: None,
+                signer: CliSignerKind::Ledger,
+                private_key_env: "PRIVATE_KEY".to_string(),
+                keystore: None,
+                keystore_password_env: "KEYSTORE_PASSWORD".to_string(),
+                mnemonic_env: "MNEMONIC".to_string(),
             },
         };
 
-        let result = cli_order_add_args.to_add_order_args().await.unwrap();
+        let (text, deployment) = cli_order_add_args.to_deployment().await.unwrap();
+        let result = cli_order_add_args
+            .to_add_order_args(text, deployment)
+            .await
+            .unwrap();
         let expected = AddOrderArgs {
             dotrain: get_dotrain(GH_COMMIT_SHA),
             inputs: vec![IO {
@@ -139,6 +303,8 @@ mod tests {
             }],
             deployer: Address::from_str("0xF14E09601A47552De6aBd3A0B165607FaFd2B5Ba").unwrap(),
             bindings: HashMap::new(),
+            nonce: None,
+            secret: None,
         };
         assert_eq!(result, expected);
 
@@ -155,20 +321,31 @@ mod tests {
 
         let cli_order_add_args = CliOrderAddArgs {
             no_broadcast: false,
+            simulate: false,
             dotrain_file: dotrain_path.into(),
             deployment: "some-deployment".to_string(),
             skip_version_check: false,
+            calldata: false,
+            encoding: SupportedOutputEncoding::Hex,
+            owner: None,
+            salt: None,
             transaction_args: CliTransactionArgs {
                 orderbook_address: Address::random(),
                 derivation_index: None,
                 chain_id: Some(123),
                 rpc_url: "https://some-rpc.com".to_string(),
+                fallback_rpc_urls: vec![],
                 max_fee_per_gas: None,
                 max_priority_fee_per_gas: None,
                 gas_fee_speed: None,
+                signer: CliSignerKind::Ledger,
+                private_key_env: "PRIVATE_KEY".to_string(),
+                keystore: None,
+                keystore_password_env: "KEYSTORE_PASSWORD".to_string(),
+                mnemonic_env: "MNEMONIC".to_string(),
             },
         };
-        let result = cli_order_add_args.to_add_order_args().await;
+        let result = cli_order_add_args.to_deployment().await;
 
         // rm test file
         std::fs::remove_file(dotrain_path).unwrap();
@@ -0,0 +1,212 @@
+use crate::{
+    commands::order::take::CliTakeOrdersArgs, execute::Execute, output::output,
+    status::display_arb_transaction_status,
+};
+use alloy::primitives::{Address, Bytes};
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Args, ValueEnum};
+use rain_orderbook_common::arb::{ArbArgs, ArbExchange};
+use rain_orderbook_common::transaction::TransactionArgs;
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CliArbContractType {
+    GenericPoolOrderTaker,
+    GenericPoolFlashBorrower,
+    RouteProcessorOrderTaker,
+}
+
+#[derive(Args, Clone)]
+pub struct CliOrderArbArgs {
+    #[arg(long, help = "Address of the deployed arb contract")]
+    arb_contract: Address,
+
+    #[arg(long, help = "Type of the arb contract")]
+    arb_type: CliArbContractType,
+
+    #[arg(long, help = "Pool to swap against, for generic pool arb contracts")]
+    pool: Option<Address>,
+
+    #[arg(
+        long,
+        help = "Address to approve for spending the swapped token, defaults to the pool"
+    )]
+    spender: Option<Address>,
+
+    #[arg(long, help = "Hex encoded calldata of the swap on the pool")]
+    exchange_calldata: Option<Bytes>,
+
+    #[arg(long, help = "Hex encoded route, for route processor arb contracts")]
+    route: Option<Bytes>,
+
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
+
+    /// Only output the arb contract's arb3 calldata instead of executing
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub calldata: bool,
+
+    #[clap(flatten)]
+    pub take_args: CliTakeOrdersArgs,
+}
+
+impl CliOrderArbArgs {
+    fn to_exchange(&self) -> Result<ArbExchange> {
+        if self.arb_type == CliArbContractType::RouteProcessorOrderTaker {
+            let route = self
+                .route
+                .clone()
+                .ok_or(anyhow!("--route is required for route processor arbs"))?;
+            return Ok(ArbExchange::RouteProcessorOrderTaker { route });
+        }
+
+        let pool = self
+            .pool
+            .ok_or(anyhow!("--pool is required for generic pool arbs"))?;
+        let spender = self.spender.unwrap_or(pool);
+        let calldata = self.exchange_calldata.clone().ok_or(anyhow!(
+            "--exchange-calldata is required for generic pool arbs"
+        ))?;
+        Ok(match self.arb_type {
+            CliArbContractType::GenericPoolFlashBorrower => ArbExchange::GenericPoolFlashBorrower {
+                spender,
+                pool,
+                calldata,
+            },
+            _ => ArbExchange::GenericPoolOrderTaker {
+                spender,
+                pool,
+                calldata,
+            },
+        })
+    }
+}
+
+impl Execute for CliOrderArbArgs {
+    async fn execute(&self) -> Result<()> {
+        let arb_args = ArbArgs {
+            arb_contract: self.arb_contract,
+            exchange: self.to_exchange()?,
+            take_orders: self.take_args.to_take_orders_args().await?,
+            task: Default::default(),
+        };
+        let mut tx_args: TransactionArgs = self.take_args.transaction_args.clone().into();
+
+        let quoted = arb_args
+            .take_orders
+            .quote_candidates(&tx_args.rpc_url, tx_args.orderbook_address)
+            .await?;
+        info!("----- Quoted {} takeable Order(s) -----", quoted.len());
+
+        if self.calldata {
+            let arb_calldata = arb_args.get_arb_calldata(&quoted, tx_args.orderbook_address)?;
+            output(&None, self.take_args.encoding.clone(), &arb_calldata)?;
+            return Ok(());
+        }
+
+        tx_args.try_fill_chain_id().await?;
+
+        if self.simulate {
+            info!("----- Simulating Arb -----");
+            let report = arb_args.simulate(&quoted, tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
+        let profit = arb_args
+            .simulate_profit(&quoted, tx_args.clone(), None)
+            .await?;
+        info!(
+            "----- Simulated arb profit: {} input token, {} output token -----",
+            profit.input_token, profit.output_token
+        );
+
+        info!("----- Arb -----");
+        arb_args
+            .execute(&quoted, tx_args, display_arb_transaction_status)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        args: CliOrderArbArgs,
+    }
+
+    fn parse(extra: &[&str]) -> Result<TestCli, clap::Error> {
+        let mut args = vec![
+            "cmd",
+            "--arb-contract",
+            "0x0000000000000000000000000000000000000004",
+            "-i",
+            "0x01",
+            "--input-token",
+            "0x0000000000000000000000000000000000000001",
+            "--output-token",
+            "0x0000000000000000000000000000000000000002",
+            "--minimum-input",
+            "1",
+            "--maximum-input",
+            "10",
+            "--maximum-io-ratio",
+            "3",
+            "-s",
+            "https://some-sg.com",
+            "-o",
+            "0x0000000000000000000000000000000000000003",
+            "-r",
+            "https://some-rpc.com",
+        ];
+        args.extend_from_slice(extra);
+        TestCli::try_parse_from(args)
+    }
+
+    #[test]
+    fn test_to_exchange() {
+        let pool = Address::repeat_byte(5);
+        let cli = parse(&[
+            "--arb-type",
+            "generic-pool-order-taker",
+            "--pool",
+            "0x0505050505050505050505050505050505050505",
+            "--exchange-calldata",
+            "0x1234",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.args.to_exchange().unwrap(),
+            ArbExchange::GenericPoolOrderTaker {
+                spender: pool,
+                pool,
+                calldata: Bytes::from(vec![0x12, 0x34]),
+            }
+        );
+
+        let cli = parse(&[
+            "--arb-type",
+            "route-processor-order-taker",
+            "--route",
+            "0xab",
+        ])
+        .unwrap();
+        assert_eq!(
+            cli.args.to_exchange().unwrap(),
+            ArbExchange::RouteProcessorOrderTaker {
+                route: Bytes::from(vec![0xab]),
+            }
+        );
+
+        let cli = parse(&["--arb-type", "generic-pool-flash-borrower"]).unwrap();
+        assert!(cli.args.to_exchange().is_err());
+    }
+}
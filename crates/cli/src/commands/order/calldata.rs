@@ -1,13 +1,16 @@
 use crate::execute::Execute;
 use crate::output::{output, SupportedOutputEncoding};
-use alloy::sol_types::SolCall;
+use alloy::primitives::{Address, B256};
 use anyhow::{anyhow, Result};
-use clap::Parser;
+use clap::{ArgAction, Parser};
+use rain_orderbook_app_settings::config_source::ParseContext;
 use rain_orderbook_common::add_order::AddOrderArgs;
 use rain_orderbook_common::dotrain_order::DotrainOrder;
+use rain_orderbook_common::transaction::TransactionArgs;
 use std::fs::read_to_string;
 use std::ops::Deref;
 use std::path::PathBuf;
+use tracing::info;
 
 #[derive(Parser, Clone)]
 pub struct AddOrderCalldata {
@@ -26,6 +29,28 @@ pub struct AddOrderCalldata {
 
     #[arg(short = 'o', long, help = "Output encoding", default_value = "binary")]
     encoding: SupportedOutputEncoding,
+
+    #[arg(
+        long,
+        help = "Salt to derive the order nonce and secret from along with the deployment key"
+    )]
+    salt: Option<B256>,
+
+    #[arg(long, help = "Order nonce, overrides the one derived from --salt")]
+    nonce: Option<B256>,
+
+    #[arg(long, help = "Order secret, overrides the one derived from --salt")]
+    secret: Option<B256>,
+
+    #[arg(
+        long,
+        help = "Owner of the order, that deterministic vault ids and the order hash are derived for"
+    )]
+    owner: Option<Address>,
+
+    /// Also output the hash the order will have once added by --owner
+    #[arg(long, action = ArgAction::SetTrue, requires = "owner")]
+    order_hash: bool,
 }
 
 impl Execute for AddOrderCalldata {
@@ -37,7 +62,8 @@ impl Execute for AddOrderCalldata {
             }
             None => None,
         };
-        let order = DotrainOrder::new(dotrain, settings).await?;
+        let order =
+            DotrainOrder::new_with_context(dotrain, settings, &ParseContext::load()?).await?;
         let dotrain_string = order.dotrain().to_string();
 
         let config_deployment = order
@@ -46,16 +72,48 @@ impl Execute for AddOrderCalldata {
             .get(&self.deployment)
             .ok_or(anyhow!("specified deployment is undefined!"))?;
 
-        let add_order_args =
-            AddOrderArgs::new_from_deployment(dotrain_string, config_deployment.deref().clone())
-                .await;
-
-        let add_order_calldata = add_order_args?
-            .try_into_call(config_deployment.scenario.deployer.network.rpc.to_string())
-            .await?
-            .abi_encode();
+        let mut add_order_args = AddOrderArgs::new_from_deployment_with_owner(
+            dotrain_string,
+            config_deployment.deref().clone(),
+            self.owner,
+        )
+        .await?;
+        if let Some(salt) = self.salt {
+            add_order_args = add_order_args.with_derived_nonce_and_secret(&self.deployment, salt);
+        }
+        if self.nonce.is_some() {
+            add_order_args.nonce = self.nonce;
+        }
+        if self.secret.is_some() {
+            add_order_args.secret = self.secret;
+        }
 
-        output(&None, self.encoding.clone(), &add_order_calldata)?;
+        let network = &config_deployment.scenario.deployer.network;
+        let tx_args = TransactionArgs {
+            rpc_url: network.rpc.to_string(),
+            fallback_rpc_urls: network
+                .fallback_rpcs
+                .iter()
+                .map(|v| v.to_string())
+                .collect(),
+            ..Default::default()
+        };
+        if self.order_hash {
+            let owner = self
+                .owner
+                .ok_or(anyhow!("--owner is required to output the order hash"))?;
+            let (add_order_calldata, order_hash) = add_order_args
+                .get_add_order_calldata_with_order_hash(tx_args, owner)
+                .await?;
+            info!("----- Add order calldata -----");
+            output(&None, self.encoding.clone(), &add_order_calldata)?;
+            println!();
+            info!("----- Predicted order hash for owner {} -----", owner);
+            output(&None, self.encoding.clone(), order_hash.as_slice())?;
+        } else {
+            let add_order_calldata = add_order_args.get_add_order_calldata(tx_args).await?;
+            output(&None, self.encoding.clone(), &add_order_calldata)?;
+        }
 
         Ok(())
     }
@@ -64,7 +122,7 @@ impl Execute for AddOrderCalldata {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy::primitives::{hex::encode_prefixed, Address, Bytes, B256};
+    use alloy::primitives::{hex::encode_prefixed, Bytes};
     use alloy::sol_types::SolValue;
     use alloy_ethers_typecast::rpc::Response;
     use clap::CommandFactory;
@@ -115,6 +173,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_args_order_hash_requires_owner() {
+        let args = [
+            "cmd",
+            "-f",
+            "./order.rain",
+            "-e",
+            "some-deployment",
+            "--order-hash",
+        ];
+        assert!(AddOrderCalldata::command()
+            .try_get_matches_from(args)
+            .is_err());
+
+        let owner = Address::random().to_string();
+        let result = AddOrderCalldata::command()
+            .try_get_matches_from(args.iter().copied().chain(["--owner", owner.as_str()]))
+            .unwrap();
+        assert!(result.get_flag("order_hash"));
+    }
+
     #[tokio::test]
     async fn test_execute() {
         let rpc_server = MockServer::start_async().await;
@@ -242,6 +321,11 @@ _ _: 0 0;
             settings_file: None,
             deployment: "some-deployment".to_string(),
             encoding: SupportedOutputEncoding::Hex,
+            salt: Some(B256::repeat_byte(1)),
+            nonce: None,
+            secret: None,
+            owner: Some(Address::random()),
+            order_hash: true,
         };
         // should succeed without err
         add_order_calldata.execute().await.unwrap();
@@ -2,6 +2,7 @@ use crate::execute::Execute;
 use crate::output::{output, SupportedOutputEncoding};
 use anyhow::{anyhow, Result};
 use clap::Args;
+use rain_orderbook_app_settings::config_source::ParseContext;
 use rain_orderbook_common::dotrain_order::DotrainOrder;
 use std::fs::read_to_string;
 use std::path::PathBuf;
@@ -42,7 +43,8 @@ impl Execute for Compose {
             None => None,
         };
 
-        let order = DotrainOrder::new(dotrain, settings).await?;
+        let order =
+            DotrainOrder::new_with_context(dotrain, settings, &ParseContext::load()?).await?;
 
         let rainlang = if self.post {
             order
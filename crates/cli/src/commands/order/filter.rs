@@ -1,6 +1,7 @@
 use crate::execute::Execute;
 use anyhow::{anyhow, Result};
 use clap::{ArgAction, Parser};
+use rain_orderbook_app_settings::config_source::ParseContext;
 use rain_orderbook_common::dotrain_order::DotrainOrder;
 use std::fs::read_to_string;
 use std::path::PathBuf;
@@ -42,12 +43,10 @@ impl Execute for Filter {
         };
 
         // generate new dotrain order instance with cleaned up frontmatter
-        let order = DotrainOrder::new_with_frontmatter_filtered_by_deployment(
-            dotrain,
-            self.deployments.clone(),
-            settings,
-        )
-        .await?;
+        let order = DotrainOrder::new_with_context(dotrain, settings, &ParseContext::load()?)
+            .await?
+            .filter_by_deployment(self.deployments.clone())
+            .await?;
 
         // handle output
         if let Some(output) = &self.output {
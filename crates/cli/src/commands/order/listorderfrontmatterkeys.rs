@@ -2,6 +2,7 @@ use crate::execute::Execute;
 use crate::output::{output, SupportedOutputEncoding};
 use anyhow::{anyhow, Result};
 use clap::Parser;
+use rain_orderbook_app_settings::config_source::ParseContext;
 use rain_orderbook_common::dotrain_order::DotrainOrder;
 use std::fs::read_to_string;
 use std::path::PathBuf;
@@ -41,7 +42,8 @@ impl Execute for ListOrderFrontmatterKeys {
             None => None,
         };
 
-        let order = DotrainOrder::new(dotrain, settings).await?;
+        let order =
+            DotrainOrder::new_with_context(dotrain, settings, &ParseContext::load()?).await?;
 
         let keys_string = match self.key_type {
             KeyType::Deployment => {
@@ -1,4 +1,5 @@
 mod add;
+mod arb;
 mod calldata;
 mod compose;
 mod detail;
@@ -7,11 +8,14 @@ mod list;
 mod listorderfrontmatterkeys;
 mod orderbook_address;
 mod remove;
+mod take;
+mod update;
 
 use crate::commands::order::orderbook_address::OrderbookAddress;
 use crate::execute::Execute;
 use add::CliOrderAddArgs;
 use anyhow::Result;
+use arb::CliOrderArbArgs;
 use calldata::AddOrderCalldata;
 use clap::Parser;
 use compose::Compose;
@@ -21,6 +25,8 @@ use listorderfrontmatterkeys::ListOrderFrontmatterKeys;
 use detail::CliOrderDetailArgs;
 use list::CliOrderListArgs;
 use remove::CliOrderRemoveArgs;
+use take::CliOrderTakeArgs;
+use update::CliOrderUpdateArgs;
 
 #[derive(Parser)]
 pub enum Order {
@@ -36,6 +42,15 @@ pub enum Order {
     #[command(about = "Remove an Order", alias = "rm")]
     Remove(CliOrderRemoveArgs),
 
+    #[command(about = "Replace an Order with a new version of its deployment, keeping its vaults")]
+    Update(CliOrderUpdateArgs),
+
+    #[command(about = "Take Orders from the Orderbook")]
+    Take(CliOrderTakeArgs),
+
+    #[command(about = "Take Orders through an arb contract against external liquidity")]
+    Arb(CliOrderArbArgs),
+
     #[command(about = "Compose a .rain order file to Rainlang", alias = "comp")]
     Compose(Compose),
 
@@ -64,6 +79,9 @@ impl Execute for Order {
             Order::Detail(detail) => detail.execute().await,
             Order::Create(create) => create.execute().await,
             Order::Remove(remove) => remove.execute().await,
+            Order::Update(update) => update.execute().await,
+            Order::Take(take) => take.execute().await,
+            Order::Arb(arb) => arb.execute().await,
             Order::Compose(compose) => compose.execute().await,
             Order::Calldata(calldata) => calldata.execute().await,
             Order::OrderbookAddress(orderbook_address) => orderbook_address.execute().await,
@@ -3,7 +3,7 @@ use crate::{
     transaction::CliTransactionArgs,
 };
 use anyhow::Result;
-use clap::Args;
+use clap::{ArgAction, Args};
 use rain_orderbook_common::remove_order::RemoveOrderArgs;
 use rain_orderbook_common::subgraph::SubgraphArgs;
 use rain_orderbook_common::transaction::TransactionArgs;
@@ -19,6 +19,11 @@ pub struct CliOrderRemoveArgs {
 
     #[clap(flatten)]
     pub transaction_args: CliTransactionArgs,
+
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
 }
 
 impl Execute for CliOrderRemoveArgs {
@@ -34,6 +39,13 @@ impl Execute for CliOrderRemoveArgs {
         let mut tx_args: TransactionArgs = self.transaction_args.clone().into();
         tx_args.try_fill_chain_id().await?;
 
+        if self.simulate {
+            info!("----- Simulating Remove Order -----");
+            let report = remove_order_args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
         info!("----- Remove Order -----");
         remove_order_args
             .execute(tx_args, |status| {
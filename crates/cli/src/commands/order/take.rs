@@ -0,0 +1,195 @@
+use crate::{
+    execute::Execute,
+    output::{output, SupportedOutputEncoding},
+    status::display_write_transaction_status,
+    subgraph::CliSubgraphArgs,
+    transaction::CliTransactionArgs,
+};
+use alloy::primitives::{Address, U256};
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Args};
+use rain_orderbook_common::subgraph::SubgraphArgs;
+use rain_orderbook_common::take_orders::TakeOrdersArgs;
+use rain_orderbook_common::transaction::TransactionArgs;
+use rain_orderbook_subgraph_client::types::common::Bytes;
+use tracing::info;
+
+#[derive(Args, Clone)]
+pub struct CliOrderTakeArgs {
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
+
+    /// Only output the approve and takeOrders2 calldata instead of executing
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub calldata: bool,
+
+    #[clap(flatten)]
+    pub take_args: CliTakeOrdersArgs,
+}
+
+/// The orders to take from and the limits to take them with, shared by the
+/// commands that take orders
+#[derive(Args, Clone)]
+pub struct CliTakeOrdersArgs {
+    #[arg(
+        short = 'i',
+        long,
+        help = "IDs of the Orders to take from (comma-separated)",
+        value_delimiter = ',',
+        required = true
+    )]
+    order_ids: Vec<String>,
+
+    #[arg(long, help = "The token to receive, ie the orders' output token")]
+    input_token: Address,
+
+    #[arg(long, help = "The token to pay with, ie the orders' input token")]
+    output_token: Address,
+
+    #[arg(
+        long,
+        help = "Minimum amount of input token to receive (in its decimals)"
+    )]
+    minimum_input: U256,
+
+    #[arg(
+        long,
+        help = "Maximum amount of input token to receive (in its decimals)"
+    )]
+    maximum_input: U256,
+
+    #[arg(long, help = "Maximum IO ratio to accept (18 decimals)")]
+    maximum_io_ratio: U256,
+
+    #[arg(long, help = "Calldata output encoding", default_value = "hex")]
+    pub encoding: SupportedOutputEncoding,
+
+    #[clap(flatten)]
+    pub subgraph_args: CliSubgraphArgs,
+
+    #[clap(flatten)]
+    pub transaction_args: CliTransactionArgs,
+}
+
+impl CliTakeOrdersArgs {
+    pub async fn to_take_orders_args(&self) -> Result<TakeOrdersArgs> {
+        let subgraph_args: SubgraphArgs = self.subgraph_args.clone().into();
+        let orders = subgraph_args
+            .to_subgraph_client()
+            .await?
+            .batch_order_detail(self.order_ids.iter().cloned().map(Bytes).collect())
+            .await?;
+        if orders.is_empty() {
+            return Err(anyhow!("none of the specified orders were found"));
+        }
+
+        Ok(TakeOrdersArgs {
+            orders,
+            input_token: self.input_token,
+            output_token: self.output_token,
+            minimum_input: self.minimum_input,
+            maximum_input: self.maximum_input,
+            maximum_io_ratio: self.maximum_io_ratio,
+        })
+    }
+}
+
+impl Execute for CliOrderTakeArgs {
+    async fn execute(&self) -> Result<()> {
+        let take_orders_args = self.take_args.to_take_orders_args().await?;
+        let mut tx_args: TransactionArgs = self.take_args.transaction_args.clone().into();
+
+        let quoted = take_orders_args
+            .quote_candidates(&tx_args.rpc_url, tx_args.orderbook_address)
+            .await?;
+        info!("----- Quoted {} takeable Order(s) -----", quoted.len());
+
+        if self.calldata {
+            let approve_calldata = take_orders_args.get_approve_calldata(&quoted, tx_args);
+            let take_orders_calldata = take_orders_args.get_take_orders_calldata(&quoted)?;
+            info!("----- Approve calldata -----");
+            output(&None, self.take_args.encoding.clone(), &approve_calldata)?;
+            println!();
+            info!("----- Take Orders calldata -----");
+            output(
+                &None,
+                self.take_args.encoding.clone(),
+                &take_orders_calldata,
+            )?;
+            println!();
+            return Ok(());
+        }
+
+        tx_args.try_fill_chain_id().await?;
+
+        if self.simulate {
+            info!("----- Simulating Take Orders -----");
+            let report = take_orders_args.simulate(&quoted, tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
+        info!("----- Approve ERC20 token spend -----");
+        take_orders_args
+            .execute_approve(&quoted, tx_args.clone(), |status| {
+                display_write_transaction_status(status);
+            })
+            .await?;
+
+        info!("----- Take Orders -----");
+        take_orders_args
+            .execute(&quoted, tx_args, |status| {
+                display_write_transaction_status(status);
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        args: CliOrderTakeArgs,
+    }
+
+    #[test]
+    fn test_cli_args() {
+        let cli = TestCli::try_parse_from([
+            "cmd",
+            "-i",
+            "0x01,0x02",
+            "--input-token",
+            "0x0000000000000000000000000000000000000001",
+            "--output-token",
+            "0x0000000000000000000000000000000000000002",
+            "--minimum-input",
+            "1",
+            "--maximum-input",
+            "10",
+            "--maximum-io-ratio",
+            "3",
+            "--calldata",
+            "-s",
+            "https://some-sg.com",
+            "-o",
+            "0x0000000000000000000000000000000000000003",
+            "-r",
+            "https://some-rpc.com",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.args.take_args.order_ids, vec!["0x01", "0x02"]);
+        assert_eq!(cli.args.take_args.maximum_input, U256::from(10));
+        assert!(cli.args.calldata);
+        assert!(!cli.args.simulate);
+        assert_eq!(cli.args.take_args.encoding, SupportedOutputEncoding::Hex);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    execute::Execute,
+    output::{output, SupportedOutputEncoding},
+    status::display_write_transaction_status,
+    subgraph::CliSubgraphArgs,
+    transaction::CliTransactionArgs,
+};
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Args};
+use rain_orderbook_app_settings::{config_source::ParseContext, Config};
+use rain_orderbook_common::frontmatter::parse_frontmatter_with_context;
+use rain_orderbook_common::subgraph::SubgraphArgs;
+use rain_orderbook_common::transaction::TransactionArgs;
+use rain_orderbook_common::update_order::UpdateOrderArgs;
+use rain_orderbook_subgraph_client::types::common::Bytes;
+use std::fs::read_to_string;
+use std::ops::Deref;
+use std::path::PathBuf;
+use tracing::info;
+
+#[derive(Args, Clone)]
+pub struct CliOrderUpdateArgs {
+    #[arg(short = 'i', long, help = "Order hash of the Order to replace")]
+    order_hash: String,
+
+    #[arg(
+        short = 'f',
+        long,
+        help = "Path to the .rain file specifying the new Order"
+    )]
+    dotrain_file: PathBuf,
+
+    #[arg(short = 'e', long, help = "Deployment key to select from frontmatter")]
+    deployment: String,
+
+    #[clap(flatten)]
+    pub subgraph_args: CliSubgraphArgs,
+
+    #[clap(flatten)]
+    pub transaction_args: CliTransactionArgs,
+
+    /// Do NOT broadcast the transaction to the network, only simulate the transaction
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub no_broadcast: bool,
+
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
+
+    /// Only output the multicall calldata instead of executing
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub calldata: bool,
+
+    #[arg(long, help = "Calldata output encoding", default_value = "hex")]
+    pub encoding: SupportedOutputEncoding,
+}
+
+impl CliOrderUpdateArgs {
+    async fn to_update_order_args(&self) -> Result<UpdateOrderArgs> {
+        let text = read_to_string(&self.dotrain_file).map_err(|e| anyhow!(e))?;
+        let config: Config = parse_frontmatter_with_context(text.clone(), &ParseContext::load()?)
+            .await?
+            .try_into()?;
+        let deployment = config
+            .deployments
+            .get(&self.deployment)
+            .ok_or(anyhow!("specified deployment is undefined!"))?
+            .deref()
+            .clone();
+
+        let subgraph_args: SubgraphArgs = self.subgraph_args.clone().into();
+        let order = subgraph_args
+            .to_subgraph_client()
+            .await?
+            .order_detail_by_hash(Bytes(self.order_hash.clone()))
+            .await?;
+
+        Ok(UpdateOrderArgs::new_from_sg_order(order, text, deployment).await?)
+    }
+}
+
+impl Execute for CliOrderUpdateArgs {
+    async fn execute(&self) -> Result<()> {
+        let update_order_args = self.to_update_order_args().await?;
+        let mut tx_args: TransactionArgs = self.transaction_args.clone().into();
+
+        info!("----- Rainlang diff -----");
+        info!("\n{}", update_order_args.get_rainlang_diff()?);
+
+        if self.calldata {
+            let calldata = update_order_args.get_update_order_calldata(tx_args).await?;
+            output(&None, self.encoding.clone(), &calldata)?;
+            println!();
+            return Ok(());
+        }
+
+        tx_args.try_fill_chain_id().await?;
+
+        if self.simulate {
+            info!("----- Simulating Update Order -----");
+            let report = update_order_args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
+        info!("----- Simulating Transaction -----");
+        update_order_args
+            .simulate_execute(tx_args.clone(), None)
+            .await?;
+        info!("----- Finished Simulation Successfully -----");
+
+        if !self.no_broadcast {
+            info!("----- Update Order -----");
+            update_order_args
+                .execute(tx_args, |status| {
+                    display_write_transaction_status(status);
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        args: CliOrderUpdateArgs,
+    }
+
+    #[test]
+    fn test_cli_args() {
+        let cli = TestCli::try_parse_from([
+            "cmd",
+            "-i",
+            "0x01",
+            "-f",
+            "./order.rain",
+            "-e",
+            "some-deployment",
+            "-s",
+            "https://some-sg.com",
+            "-o",
+            "0x0000000000000000000000000000000000000001",
+            "-r",
+            "https://some-rpc.com",
+            "--calldata",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.args.order_hash, "0x01");
+        assert_eq!(cli.args.deployment, "some-deployment");
+        assert!(cli.args.calldata);
+        assert!(!cli.args.no_broadcast);
+        assert!(!cli.args.simulate);
+        assert_eq!(cli.args.encoding, SupportedOutputEncoding::Hex);
+
+        let cli = TestCli::try_parse_from([
+            "cmd",
+            "-i",
+            "0x01",
+            "-f",
+            "./order.rain",
+            "-e",
+            "some-deployment",
+            "-s",
+            "https://some-sg.com",
+            "-o",
+            "0x0000000000000000000000000000000000000001",
+            "-r",
+            "https://some-rpc.com",
+            "--simulate",
+        ])
+        .unwrap();
+        assert!(cli.args.simulate);
+    }
+}
@@ -0,0 +1,91 @@
+mod provenance;
+mod resolve_tokens;
+mod schema;
+mod validate;
+
+use crate::execute::Execute;
+use anyhow::{anyhow, Result};
+use clap::{Args, Parser};
+use provenance::CliSettingsProvenanceArgs;
+use rain_orderbook_app_settings::config_source::{ParseContext, PartialConfigSource};
+use rain_orderbook_common::dotrain::RainDocument;
+use resolve_tokens::CliSettingsResolveTokensArgs;
+use schema::CliSettingsSchemaArgs;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use validate::CliSettingsValidateArgs;
+
+#[derive(Parser)]
+pub enum Settings {
+    #[command(
+        about = "Merge a .rain file's frontmatter with settings files, later files overriding earlier ones, and print where each value came from"
+    )]
+    Provenance(CliSettingsProvenanceArgs),
+
+    #[command(
+        about = "Merge a .rain file's frontmatter with settings files, later files overriding earlier ones, and report every problem found in the resulting config"
+    )]
+    Validate(CliSettingsValidateArgs),
+
+    #[command(
+        about = "Print the JSON Schema of settings files and .rain frontmatter, for editors to validate and autocomplete them"
+    )]
+    Schema(CliSettingsSchemaArgs),
+
+    #[command(
+        about = "Read the decimals, symbols and names of tokens that do not set them from chain, with one multicall per network"
+    )]
+    ResolveTokens(CliSettingsResolveTokensArgs),
+}
+
+impl Execute for Settings {
+    async fn execute(&self) -> Result<()> {
+        match self {
+            Settings::Provenance(provenance) => provenance.execute().await,
+            Settings::Validate(validate) => validate.execute().await,
+            Settings::Schema(schema) => schema.execute().await,
+            Settings::ResolveTokens(resolve_tokens) => resolve_tokens.execute().await,
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct CliSettingsSourceArgs {
+    #[arg(short = 'f', long, help = "Path to the .rain file")]
+    dotrain_file: Option<PathBuf>,
+
+    #[arg(
+        short = 'c',
+        long,
+        help = "Path to a settings yaml file, can be given many times"
+    )]
+    settings_file: Vec<PathBuf>,
+}
+
+impl CliSettingsSourceArgs {
+    /// Named config sources in merge order, the .rain file first, parsed
+    /// as partial configs so they only set the values they define
+    async fn sources(&self) -> Result<Vec<(String, PartialConfigSource)>> {
+        let mut sources = vec![];
+        if let Some(dotrain_file) = &self.dotrain_file {
+            let dotrain = read_to_string(dotrain_file).map_err(|e| anyhow!(e))?;
+            let frontmatter = RainDocument::get_front_matter(&dotrain).unwrap_or("");
+            let config = PartialConfigSource::try_from_string(
+                frontmatter.to_string(),
+                &ParseContext::load_for_file(dotrain_file)?,
+            )
+            .await?;
+            sources.push((dotrain_file.display().to_string(), config));
+        }
+        for settings_file in &self.settings_file {
+            let settings = read_to_string(settings_file).map_err(|e| anyhow!(e))?;
+            let config = PartialConfigSource::try_from_string(
+                settings,
+                &ParseContext::load_for_file(settings_file)?,
+            )
+            .await?;
+            sources.push((settings_file.display().to_string(), config));
+        }
+        Ok(sources)
+    }
+}
@@ -0,0 +1,90 @@
+use super::CliSettingsSourceArgs;
+use crate::execute::Execute;
+use anyhow::Result;
+use clap::Args;
+use rain_orderbook_app_settings::{config_source::ConfigSource, merge::MergeStrategy};
+use tracing::info;
+
+#[derive(Args, Clone)]
+pub struct CliSettingsProvenanceArgs {
+    #[clap(flatten)]
+    sources: CliSettingsSourceArgs,
+}
+
+impl Execute for CliSettingsProvenanceArgs {
+    async fn execute(&self) -> Result<()> {
+        let (_, provenance) =
+            ConfigSource::merge_all(self.sources.sources().await?, MergeStrategy::Override)?;
+        for (path, source) in provenance.0 {
+            info!("{}: {}", path, source);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let dotrain_path = "./test_settings_provenance.rain";
+        let settings_path = "./test_settings_provenance.yml";
+        std::fs::write(
+            dotrain_path,
+            r#"
+networks:
+    some-network:
+        rpc: https://some-rpc.com
+        chain-id: 123
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;"#,
+        )
+        .unwrap();
+        std::fs::write(
+            settings_path,
+            r#"
+networks:
+    some-network:
+        rpc: https://other-rpc.com
+"#,
+        )
+        .unwrap();
+
+        let args = CliSettingsProvenanceArgs {
+            sources: CliSettingsSourceArgs {
+                dotrain_file: Some(dotrain_path.into()),
+                settings_file: vec![settings_path.into()],
+            },
+        };
+        let (merged, provenance) = ConfigSource::merge_all(
+            args.sources.sources().await.unwrap(),
+            MergeStrategy::Override,
+        )
+        .unwrap();
+        let result = args.execute().await;
+
+        std::fs::remove_file(dotrain_path).unwrap();
+        std::fs::remove_file(settings_path).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(
+            merged.networks.get("some-network").unwrap().rpc,
+            "https://other-rpc.com"
+                .parse::<reqwest::Url>()
+                .unwrap()
+                .into()
+        );
+        assert_eq!(
+            provenance.get("networks.some-network.rpc").unwrap(),
+            settings_path
+        );
+        assert_eq!(
+            provenance.get("networks.some-network.chain-id").unwrap(),
+            dotrain_path
+        );
+    }
+}
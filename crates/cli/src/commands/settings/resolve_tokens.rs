@@ -0,0 +1,169 @@
+use super::CliSettingsSourceArgs;
+use crate::execute::Execute;
+use anyhow::Result;
+use clap::Args;
+use rain_orderbook_app_settings::{
+    config_source::{ConfigSource, TokenConfigSource},
+    merge::MergeStrategy,
+    token_metadata::{write_token_metadata, TokenMetadataCache},
+};
+use rain_orderbook_common::{
+    frontmatter::write_frontmatter_token_metadata, token_metadata::TokenMetadataResolver,
+};
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+
+#[derive(Args, Clone)]
+pub struct CliSettingsResolveTokensArgs {
+    #[clap(flatten)]
+    sources: CliSettingsSourceArgs,
+
+    #[arg(
+        short = 'w',
+        long,
+        help = "Write the resolved metadata into the files the tokens are defined in"
+    )]
+    write: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Directory to cache token metadata in, defaults to one in the temp directory"
+    )]
+    cache_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "Seconds cached token metadata is used for before it is read from chain again, defaults to a week"
+    )]
+    cache_ttl: Option<u64>,
+
+    #[arg(long, help = "Read all token metadata from chain instead of the cache")]
+    no_cache: bool,
+}
+
+impl Execute for CliSettingsResolveTokensArgs {
+    async fn execute(&self) -> Result<()> {
+        let sources = self.sources.sources().await?;
+        // tokens are resolved as they are in the merged config, so a file
+        // can use the networks of another or override some token fields
+        let (merged, _) = ConfigSource::merge_all(sources.clone(), MergeStrategy::Override)?;
+        let resolver = TokenMetadataResolver::new((!self.no_cache).then(|| {
+            TokenMetadataCache::new(
+                self.cache_dir.clone(),
+                self.cache_ttl
+                    .map(Duration::from_secs)
+                    .unwrap_or(TokenMetadataCache::DEFAULT_TTL),
+            )
+        }));
+
+        let paths = self
+            .sources
+            .dotrain_file
+            .iter()
+            .chain(self.sources.settings_file.iter());
+        for (path, (name, source)) in paths.zip(sources) {
+            let mut tokens: HashMap<String, TokenConfigSource> = source
+                .value
+                .get("tokens")
+                .and_then(|v| v.as_object())
+                .into_iter()
+                .flat_map(|v| v.keys())
+                .filter_map(|k| merged.tokens.get(k).map(|v| (k.clone(), v.clone())))
+                .collect();
+            let changed = resolver
+                .resolve_token_sources(&mut tokens, &merged.networks)
+                .await?;
+            for token_name in &changed {
+                let token = &tokens[token_name];
+                info!(
+                    "{}: tokens.{}: decimals: {}, symbol: {}, label: {}",
+                    name,
+                    token_name,
+                    token.decimals.map_or("-".to_string(), |v| v.to_string()),
+                    token.symbol.as_deref().unwrap_or("-"),
+                    token.label.as_deref().unwrap_or("-"),
+                );
+            }
+
+            if self.write && !changed.is_empty() {
+                let text = read_to_string(path)?;
+                let text = if self.sources.dotrain_file.as_ref() == Some(path) {
+                    write_frontmatter_token_metadata(&text, &tokens)
+                } else {
+                    write_token_metadata(&text, &tokens)
+                };
+                std::fs::write(path, text)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(settings_path: &str) -> CliSettingsResolveTokensArgs {
+        CliSettingsResolveTokensArgs {
+            sources: CliSettingsSourceArgs {
+                dotrain_file: None,
+                settings_file: vec![settings_path.into()],
+            },
+            write: true,
+            cache_dir: None,
+            cache_ttl: None,
+            no_cache: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_complete_tokens() {
+        let settings_path = "./test_settings_resolve_tokens.yml";
+        let settings = r#"
+networks:
+    some-network:
+        rpc: https://some-rpc.com
+        chain-id: 123
+tokens:
+    token1:
+        network: some-network
+        address: 0xc2132d05d31c914a87c6611c10748aeb04b58e8f
+        decimals: 6
+        label: Tether USD
+        symbol: USDT
+"#;
+        std::fs::write(settings_path, settings).unwrap();
+
+        let result = args(settings_path).execute().await;
+        let written = read_to_string(settings_path).unwrap();
+        std::fs::remove_file(settings_path).unwrap();
+
+        assert!(result.is_ok());
+        assert_eq!(written, settings);
+    }
+
+    #[tokio::test]
+    async fn test_execute_unknown_network() {
+        let settings_path = "./test_settings_resolve_tokens_unknown_network.yml";
+        std::fs::write(
+            settings_path,
+            r#"
+tokens:
+    token1:
+        network: some-network
+        address: 0xc2132d05d31c914a87c6611c10748aeb04b58e8f
+"#,
+        )
+        .unwrap();
+
+        let result = args(settings_path).execute().await;
+        std::fs::remove_file(settings_path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::execute::Execute;
+use anyhow::Result;
+use clap::Args;
+use rain_orderbook_app_settings::schema::config_source_schema_json;
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+pub struct CliSettingsSchemaArgs {
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "PATH",
+        help = "Optional file to write the schema into instead of printing it"
+    )]
+    output: Option<PathBuf>,
+}
+
+impl Execute for CliSettingsSchemaArgs {
+    async fn execute(&self) -> Result<()> {
+        let schema = config_source_schema_json()?;
+        match &self.output {
+            Some(output) => std::fs::write(output, schema)?,
+            None => println!("{}", schema),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let output = "./test_settings_schema.json";
+        let args = CliSettingsSchemaArgs {
+            output: Some(output.into()),
+        };
+        let result = args.execute().await;
+        let schema = std::fs::read_to_string(output).unwrap();
+        std::fs::remove_file(output).unwrap();
+
+        assert!(result.is_ok());
+        let schema: serde_json::Value = serde_json::from_str(&schema).unwrap();
+        assert_eq!(schema["title"], "ConfigSource");
+    }
+}
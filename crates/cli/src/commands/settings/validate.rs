@@ -0,0 +1,93 @@
+use super::CliSettingsSourceArgs;
+use crate::execute::Execute;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use rain_orderbook_app_settings::{
+    config::Config,
+    config_source::ConfigSource,
+    merge::MergeStrategy,
+    validate::{Diagnostic, Severity},
+};
+use tracing::{error, warn};
+
+#[derive(Args, Clone)]
+pub struct CliSettingsValidateArgs {
+    #[clap(flatten)]
+    sources: CliSettingsSourceArgs,
+}
+
+impl CliSettingsValidateArgs {
+    async fn diagnostics(&self) -> Result<Vec<Diagnostic>> {
+        let (merged, _) =
+            ConfigSource::merge_all(self.sources.sources().await?, MergeStrategy::Override)?;
+        // unchecked so every mismatch is reported rather than the first
+        let config = Config::try_from_source_unchecked(merged)?;
+        Ok(config.validate())
+    }
+}
+
+impl Execute for CliSettingsValidateArgs {
+    async fn execute(&self) -> Result<()> {
+        let diagnostics = self.diagnostics().await?;
+        for diagnostic in &diagnostics {
+            match diagnostic.severity {
+                Severity::Error => error!("{}", diagnostic),
+                Severity::Warning => warn!("{}", diagnostic),
+            }
+        }
+        let errors = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+        if errors > 0 {
+            return Err(anyhow!("Config has {} error(s)", errors));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let settings_path = "./test_settings_validate.yml";
+        std::fs::write(
+            settings_path,
+            r#"
+networks:
+    some-network:
+        rpc: https://some-rpc.com
+        chain-id: 123
+tokens:
+    token1:
+        network: some-network
+        address: 0xc2132d05d31c914a87c6611c10748aeb04b58e8f
+orders:
+    some-order:
+        inputs:
+            - token: token1
+        outputs:
+            - token: token1
+"#,
+        )
+        .unwrap();
+
+        let args = CliSettingsValidateArgs {
+            sources: CliSettingsSourceArgs {
+                dotrain_file: None,
+                settings_file: vec![settings_path.into()],
+            },
+        };
+        let diagnostics = args.diagnostics().await.unwrap();
+        let result = args.execute().await;
+
+        std::fs::remove_file(settings_path).unwrap();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].path, "orders.some-order");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    execute::Execute, status::display_write_transaction_status, transaction::CliTransactionArgs,
+};
+use anyhow::{anyhow, Result};
+use clap::{ArgAction, Args, ValueEnum};
+use rain_orderbook_common::{
+    transaction::TransactionArgs,
+    vault_batch::{VaultBatchArgs, VaultBatchItem, VaultBatchItemResult},
+};
+use std::{fs::read_to_string, path::PathBuf};
+use tracing::info;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BatchFileFormat {
+    Yaml,
+    Csv,
+}
+
+#[derive(Args, Clone)]
+pub struct CliVaultBatchArgs {
+    #[arg(
+        short,
+        long,
+        help = "Path to a yaml or csv file of deposits and withdrawals, with action, token, vault-id and amount fields"
+    )]
+    file: PathBuf,
+
+    #[arg(
+        long,
+        help = "Format of the batch file, inferred from its extension if not set"
+    )]
+    format: Option<BatchFileFormat>,
+
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
+
+    #[clap(flatten)]
+    transaction_args: CliTransactionArgs,
+}
+
+impl CliVaultBatchArgs {
+    fn format(&self) -> Result<BatchFileFormat> {
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+        match self.file.extension().and_then(|v| v.to_str()) {
+            Some("yaml") | Some("yml") => Ok(BatchFileFormat::Yaml),
+            Some("csv") => Ok(BatchFileFormat::Csv),
+            _ => Err(anyhow!(
+                "cannot infer the batch file format, please specify it with --format"
+            )),
+        }
+    }
+
+    fn to_vault_batch_args(&self) -> Result<VaultBatchArgs> {
+        let text = read_to_string(&self.file)?;
+        let args = match self.format()? {
+            BatchFileFormat::Yaml => VaultBatchArgs::from_yaml(&text)?,
+            BatchFileFormat::Csv => VaultBatchArgs::from_csv(&text)?,
+        };
+        Ok(args)
+    }
+}
+
+impl Execute for CliVaultBatchArgs {
+    async fn execute(&self) -> Result<()> {
+        let mut tx_args: TransactionArgs = self.transaction_args.clone().into();
+        tx_args.try_fill_chain_id().await?;
+        let batch_args = self.to_vault_batch_args()?;
+
+        for (i, item) in batch_args.items.iter().enumerate() {
+            match item {
+                VaultBatchItem::Deposit(v) => info!(
+                    "#{} deposit {} of token {} into vault {}",
+                    i, v.amount, v.token, v.vault_id
+                ),
+                VaultBatchItem::Withdraw(v) => info!(
+                    "#{} withdraw {} of token {} from vault {}",
+                    i, v.target_amount, v.token, v.vault_id
+                ),
+            }
+        }
+
+        if self.simulate {
+            info!("----- Simulating Vault Batch -----");
+            let report = batch_args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
+        info!("----- Approve ERC20 token spends -----");
+        batch_args
+            .execute_approvals(tx_args.clone(), |token, status| {
+                info!("Token {}", token);
+                display_write_transaction_status(status);
+            })
+            .await?;
+
+        info!(
+            "----- Deposit and withdraw {} item(s) in one transaction -----",
+            batch_args.items.len()
+        );
+        let results = batch_args
+            .execute(tx_args, |status| {
+                display_write_transaction_status(status);
+            })
+            .await?;
+
+        let mut failed = 0;
+        for (i, result) in results.iter().enumerate() {
+            match result {
+                VaultBatchItemResult::Success => info!("#{} succeeded", i),
+                VaultBatchItemResult::Reverted(reason) => {
+                    failed += 1;
+                    info!("#{} reverted: {}", i, reason)
+                }
+                VaultBatchItemResult::Failed(reason) => {
+                    failed += 1;
+                    info!("#{} failed: {}", i, reason)
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow!("{} of {} item(s) failed", failed, results.len()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        args: CliVaultBatchArgs,
+    }
+
+    #[test]
+    fn test_format() {
+        let parse = |extra: &[&str]| {
+            let mut args = vec![
+                "cmd",
+                "-o",
+                "0x0000000000000000000000000000000000000001",
+                "-r",
+                "https://some-rpc.com",
+            ];
+            args.extend_from_slice(extra);
+            TestCli::try_parse_from(args).unwrap().args
+        };
+
+        assert_eq!(
+            parse(&["-f", "./batch.yml"]).format().unwrap(),
+            BatchFileFormat::Yaml
+        );
+        assert_eq!(
+            parse(&["-f", "./batch.csv"]).format().unwrap(),
+            BatchFileFormat::Csv
+        );
+        assert_eq!(
+            parse(&["-f", "./batch.txt", "--format", "csv"])
+                .format()
+                .unwrap(),
+            BatchFileFormat::Csv
+        );
+        assert!(parse(&["-f", "./batch"]).format().is_err());
+    }
+}
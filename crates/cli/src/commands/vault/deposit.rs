@@ -3,7 +3,7 @@ use crate::{
 };
 use alloy::primitives::{Address, U256};
 use anyhow::Result;
-use clap::Args;
+use clap::{ArgAction, Args};
 use rain_orderbook_common::{deposit::DepositArgs, transaction::TransactionArgs};
 use tracing::info;
 
@@ -20,6 +20,11 @@ pub struct CliVaultDepositArgs {
 
     #[clap(flatten)]
     pub transaction_args: CliTransactionArgs,
+
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
 }
 
 impl From<CliVaultDepositArgs> for DepositArgs {
@@ -38,6 +43,13 @@ impl Execute for CliVaultDepositArgs {
         tx_args.try_fill_chain_id().await?;
         let deposit_args: DepositArgs = self.clone().into();
 
+        if self.simulate {
+            info!("----- Simulating Deposit -----");
+            let report = deposit_args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
         info!("----- Approve ERC20 token spend -----");
         deposit_args
             .execute_approve(tx_args.clone(), |status| {
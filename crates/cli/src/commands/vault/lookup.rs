@@ -0,0 +1,158 @@
+use crate::{execute::Execute, subgraph::CliSubgraphArgs};
+use alloy::primitives::Address;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use rain_orderbook_common::{subgraph::SubgraphArgs, vault_id::lookup_deployment_vaults};
+use std::fs::read_to_string;
+use std::path::PathBuf;
+use tracing::info;
+
+#[derive(Args, Clone)]
+pub struct CliVaultLookupArgs {
+    #[arg(
+        short = 'f',
+        long,
+        help = "Path to the .rain file specifying the deployments"
+    )]
+    dotrain_file: PathBuf,
+
+    #[arg(
+        short = 'e',
+        long,
+        help = "Deployment key to look up, all deployments if not set"
+    )]
+    deployment: Option<String>,
+
+    #[arg(long, help = "Owner of the vaults")]
+    owner: Address,
+
+    #[clap(flatten)]
+    pub subgraph_args: CliSubgraphArgs,
+}
+
+impl Execute for CliVaultLookupArgs {
+    async fn execute(&self) -> Result<()> {
+        let dotrain = read_to_string(&self.dotrain_file).map_err(|e| anyhow!(e))?;
+        let subgraph_args: SubgraphArgs = self.subgraph_args.clone().into();
+        let client = subgraph_args.to_subgraph_client().await?;
+
+        let vaults =
+            lookup_deployment_vaults(dotrain, self.deployment.clone(), self.owner, &client).await?;
+        for vault in vaults {
+            match vault.balance {
+                Some(balance) => info!(
+                    "{}: token {} vault {} balance {}",
+                    vault.deployment, vault.token, vault.vault_id, balance
+                ),
+                None => info!(
+                    "{}: token {} vault {} not found",
+                    vault.deployment, vault.token, vault.vault_id
+                ),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{hex::encode_prefixed, primitives::B256};
+    use httpmock::MockServer;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_execute() {
+        let sg_server = MockServer::start();
+        sg_server.mock(|when, then| {
+            when.body_contains("\"skip\":0");
+            then.json_body_obj(&json!({
+                "data": {
+                    "vaults": [{
+                        "id": encode_prefixed(B256::random()),
+                        "vaultId": "1",
+                        "owner": encode_prefixed(Address::repeat_byte(1)),
+                        "balance": "100",
+                        "token": {
+                            "id": "0xc2132d05d31c914a87c6611c10748aeb04b58e8f",
+                            "address": "0xc2132d05d31c914a87c6611c10748aeb04b58e8f",
+                            "name": "T1",
+                            "symbol": "T1",
+                            "decimals": "6"
+                        },
+                        "orderbook": {
+                            "id": encode_prefixed(B256::random()),
+                        },
+                        "ordersAsOutput": [],
+                        "ordersAsInput": [],
+                        "balanceChanges": []
+                    }]
+                }
+            }));
+        });
+        sg_server.mock(|_when, then| {
+            then.json_body_obj(&json!({"data": {"vaults": []}}));
+        });
+
+        let dotrain_path = "./test_dotrain_vault_lookup.rain";
+        std::fs::write(
+            dotrain_path,
+            r#"
+networks:
+    some-network:
+        rpc: https://some-rpc.com
+        chain-id: 123
+deployers:
+    some-deployer:
+        network: some-network
+        address: 0xF14E09601A47552De6aBd3A0B165607FaFd2B5Ba
+tokens:
+    token1:
+        network: some-network
+        address: 0xc2132d05d31c914a87c6611c10748aeb04b58e8f
+        decimals: 6
+    token2:
+        network: some-network
+        address: 0x8f3cf7ad23cd3cadbd9735aff958023239c6a063
+        decimals: 18
+scenarios:
+    some-scenario:
+        network: some-network
+        deployer: some-deployer
+orders:
+    some-order:
+        inputs:
+            - token: token1
+              vault-id: 1
+        outputs:
+            - token: token2
+deployments:
+    some-deployment:
+        scenario: some-scenario
+        order: some-order
+        vault-id-mode: deterministic
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;
+#handle-add-order
+:;"#,
+        )
+        .unwrap();
+
+        let cli_vault_lookup_args = CliVaultLookupArgs {
+            dotrain_file: dotrain_path.into(),
+            deployment: None,
+            owner: Address::repeat_byte(1),
+            subgraph_args: CliSubgraphArgs {
+                subgraph_url: sg_server.url("/sg"),
+            },
+        };
+        let result = cli_vault_lookup_args.execute().await;
+
+        std::fs::remove_file(dotrain_path).unwrap();
+        assert!(result.is_ok());
+    }
+}
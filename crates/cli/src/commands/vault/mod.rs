@@ -1,16 +1,20 @@
+mod batch;
 mod deposit;
 mod detail;
 mod list;
 mod list_balance_changes;
+mod lookup;
 mod withdraw;
 
 use crate::execute::Execute;
 use anyhow::Result;
+use batch::CliVaultBatchArgs;
 use clap::Parser;
 use deposit::CliVaultDepositArgs;
 use detail::CliVaultDetailArgs;
 use list::CliVaultListArgs;
 use list_balance_changes::CliVaultBalanceChangesList;
+use lookup::CliVaultLookupArgs;
 use withdraw::CliVaultWithdrawArgs;
 
 #[derive(Parser)]
@@ -21,6 +25,9 @@ pub enum Vault {
     #[command(about = "Withdraw tokens from a Vault")]
     Withdraw(CliVaultWithdrawArgs),
 
+    #[command(about = "Deposit and withdraw tokens for many Vaults in a single transaction")]
+    Batch(CliVaultBatchArgs),
+
     #[command(about = "List all Vaults", alias = "ls")]
     List(CliVaultListArgs),
 
@@ -29,6 +36,9 @@ pub enum Vault {
 
     #[command(about = "List balance changes for a Vault (i.e. deposits + withdraws)")]
     ListBalanceChanges(CliVaultBalanceChangesList),
+
+    #[command(about = "Look up the Vaults of a .rain file's deployments for an owner")]
+    Lookup(CliVaultLookupArgs),
 }
 
 impl Execute for Vault {
@@ -36,9 +46,11 @@ impl Execute for Vault {
         match self {
             Vault::Deposit(deposit) => deposit.execute().await,
             Vault::Withdraw(withdraw) => withdraw.execute().await,
+            Vault::Batch(batch) => batch.execute().await,
             Vault::List(list) => list.execute().await,
             Vault::Detail(detail) => detail.execute().await,
             Vault::ListBalanceChanges(list_balance_changes) => list_balance_changes.execute().await,
+            Vault::Lookup(lookup) => lookup.execute().await,
         }
     }
 }
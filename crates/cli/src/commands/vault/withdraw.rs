@@ -2,7 +2,7 @@ use crate::status::display_write_transaction_status;
 use crate::{execute::Execute, transaction::CliTransactionArgs};
 use alloy::primitives::{Address, U256};
 use anyhow::Result;
-use clap::Args;
+use clap::{ArgAction, Args};
 use rain_orderbook_common::transaction::TransactionArgs;
 use rain_orderbook_common::withdraw::WithdrawArgs;
 use tracing::info;
@@ -20,6 +20,11 @@ pub struct CliVaultWithdrawArgs {
 
     #[clap(flatten)]
     transaction_args: CliTransactionArgs,
+
+    /// Only simulate the transaction on a fork of the network and print a
+    /// report of the outcome, without broadcasting
+    #[arg(long, action = ArgAction::SetTrue)]
+    pub simulate: bool,
 }
 
 impl From<CliVaultWithdrawArgs> for WithdrawArgs {
@@ -38,6 +43,13 @@ impl Execute for CliVaultWithdrawArgs {
         tx_args.try_fill_chain_id().await?;
         let withdraw_args: WithdrawArgs = self.clone().into();
 
+        if self.simulate {
+            info!("----- Simulating Withdraw -----");
+            let report = withdraw_args.simulate(tx_args, None).await?;
+            info!("\n{}", report);
+            return Ok(());
+        }
+
         info!("----- Withdraw tokens from Vault -----");
         withdraw_args
             .execute(tx_args, |status| {
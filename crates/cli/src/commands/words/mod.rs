@@ -2,6 +2,7 @@ use crate::execute::Execute;
 use anyhow::{anyhow, Result};
 use clap::{ArgAction, Args, Parser};
 use csv::Writer;
+use rain_orderbook_app_settings::config_source::ParseContext;
 use rain_orderbook_common::dotrain_order::{AuthoringMetaV2, DotrainOrder, WordsResult};
 use reqwest::Url;
 use std::{fs::read_to_string, path::PathBuf, str::FromStr, sync::Arc};
@@ -92,7 +93,8 @@ impl Execute for Words {
             }
             None => None,
         };
-        let mut order = DotrainOrder::new(dotrain, settings).await?;
+        let mut order =
+            DotrainOrder::new_with_context(dotrain, settings, &ParseContext::load()?).await?;
 
         let results = if let Some(deployer_key) = &self.source.deployer {
             // get deployer from order config
@@ -1,5 +1,6 @@
 use alloy::sol_types::SolCall;
 use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+use rain_orderbook_common::arb::ArbTransactionStatus;
 use std::fmt::Debug;
 use tracing::info;
 
@@ -9,7 +10,7 @@ pub fn display_write_transaction_status<T: SolCall + Debug>(status: WriteTransac
             info!("⏳  Preparing transaction. Please wait.");
         }
         WriteTransactionStatus::PendingSign(_) => {
-            info!("🖋   Please sign the transaction on your Ledger device.");
+            info!("🖋   Signing transaction. Please confirm on your Ledger device if prompted.");
         }
         WriteTransactionStatus::PendingSend(_) => {
             info!("⏳  Awaiting transaction confirmation. Please wait.");
@@ -19,3 +20,20 @@ pub fn display_write_transaction_status<T: SolCall + Debug>(status: WriteTransac
         }
     }
 }
+
+pub fn display_arb_transaction_status(status: ArbTransactionStatus) {
+    match status {
+        ArbTransactionStatus::PendingPrepare => {
+            info!("⏳  Preparing transaction. Please wait.");
+        }
+        ArbTransactionStatus::PendingSign => {
+            info!("🖋   Signing transaction. Please confirm on your Ledger device if prompted.");
+        }
+        ArbTransactionStatus::PendingSend => {
+            info!("⏳  Awaiting transaction confirmation. Please wait.");
+        }
+        ArbTransactionStatus::Confirmed(receipt) => {
+            info!("✅  Transaction confirmed: {:?}", receipt.transaction_hash);
+        }
+    }
+}
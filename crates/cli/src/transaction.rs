@@ -1,7 +1,8 @@
 use alloy::primitives::{Address, U256};
 use alloy_ethers_typecast::gas_fee_middleware::GasFeeSpeed;
 use clap::Args;
-use rain_orderbook_common::transaction::TransactionArgs;
+use rain_orderbook_common::transaction::{SignerSource, TransactionArgs};
+use std::path::PathBuf;
 
 #[derive(Args, Clone)]
 pub struct CliTransactionArgs {
@@ -11,7 +12,7 @@ pub struct CliTransactionArgs {
     #[arg(
         short,
         long,
-        help = "Derivation index of the Ledger wallet address to use",
+        help = "Derivation index of the Ledger wallet address or mnemonic account to use",
         default_value = "0"
     )]
     pub derivation_index: Option<usize>,
@@ -22,6 +23,12 @@ pub struct CliTransactionArgs {
     #[arg(short, long, help = "RPC URL")]
     pub rpc_url: String,
 
+    #[arg(
+        long = "fallback-rpc-url",
+        help = "RPC URL to fall back on when reads from the RPC URL fail, can be given multiple times"
+    )]
+    pub fallback_rpc_urls: Vec<String>,
+
     #[arg(
         short = 'p',
         long,
@@ -45,22 +52,91 @@ pub struct CliTransactionArgs {
         default_value = "medium"
     )]
     pub gas_fee_speed: Option<CliGasFeeSpeed>,
+
+    #[arg(
+        long,
+        help = "Where the transaction signing key comes from",
+        default_value = "ledger"
+    )]
+    pub signer: CliSignerKind,
+
+    #[arg(
+        long,
+        help = "Name of the environment variable holding the hex encoded private key",
+        default_value = "PRIVATE_KEY"
+    )]
+    pub private_key_env: String,
+
+    #[arg(
+        long,
+        help = "Path to the encrypted JSON keystore file",
+        required_if_eq("signer", "keystore")
+    )]
+    pub keystore: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Name of the environment variable holding the keystore password",
+        default_value = "KEYSTORE_PASSWORD"
+    )]
+    pub keystore_password_env: String,
+
+    #[arg(
+        long,
+        help = "Name of the environment variable holding the mnemonic phrase",
+        default_value = "MNEMONIC"
+    )]
+    pub mnemonic_env: String,
+}
+
+impl CliTransactionArgs {
+    fn signer_source(&self) -> SignerSource {
+        match self.signer {
+            CliSignerKind::Ledger => SignerSource::Ledger,
+            CliSignerKind::PrivateKey => SignerSource::PrivateKey {
+                env_var: self.private_key_env.clone(),
+            },
+            CliSignerKind::Keystore => SignerSource::Keystore {
+                path: self
+                    .keystore
+                    .clone()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string(),
+                password_env_var: self.keystore_password_env.clone(),
+            },
+            CliSignerKind::Mnemonic => SignerSource::Mnemonic {
+                env_var: self.mnemonic_env.clone(),
+            },
+        }
+    }
 }
 
 impl From<CliTransactionArgs> for TransactionArgs {
     fn from(val: CliTransactionArgs) -> Self {
+        let signer = val.signer_source();
         TransactionArgs {
             orderbook_address: val.orderbook_address,
             derivation_index: val.derivation_index,
             chain_id: val.chain_id,
             rpc_url: val.rpc_url,
+            fallback_rpc_urls: val.fallback_rpc_urls,
             max_priority_fee_per_gas: val.max_priority_fee_per_gas,
             max_fee_per_gas: val.max_fee_per_gas,
             gas_fee_speed: val.gas_fee_speed.map(|g| g.into()),
+            signer,
         }
     }
 }
 
+#[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
+pub enum CliSignerKind {
+    Ledger,
+    PrivateKey,
+    Keystore,
+    Mnemonic,
+}
+
 #[derive(clap::ValueEnum, Clone)]
 pub enum CliGasFeeSpeed {
     Slow,
@@ -79,3 +155,66 @@ impl From<CliGasFeeSpeed> for GasFeeSpeed {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        transaction_args: CliTransactionArgs,
+    }
+
+    #[test]
+    fn test_signer_args() {
+        let base = [
+            "cmd",
+            "-o",
+            "0x0000000000000000000000000000000000000001",
+            "-r",
+            "url",
+        ];
+
+        let cli = TestCli::try_parse_from(base).unwrap();
+        let tx_args: TransactionArgs = cli.transaction_args.into();
+        assert_eq!(tx_args.signer, SignerSource::Ledger);
+
+        let cli = TestCli::try_parse_from(
+            [
+                &base[..],
+                &["--signer", "private-key", "--private-key-env", "BOT_KEY"],
+            ]
+            .concat(),
+        )
+        .unwrap();
+        let tx_args: TransactionArgs = cli.transaction_args.into();
+        assert_eq!(
+            tx_args.signer,
+            SignerSource::PrivateKey {
+                env_var: "BOT_KEY".to_string()
+            }
+        );
+
+        let cli = TestCli::try_parse_from(
+            [
+                &base[..],
+                &["--signer", "keystore", "--keystore", "./key.json"],
+            ]
+            .concat(),
+        )
+        .unwrap();
+        let tx_args: TransactionArgs = cli.transaction_args.into();
+        assert_eq!(
+            tx_args.signer,
+            SignerSource::Keystore {
+                path: "./key.json".to_string(),
+                password_env_var: "KEYSTORE_PASSWORD".to_string(),
+            }
+        );
+
+        // keystore signer needs a keystore path
+        assert!(TestCli::try_parse_from([&base[..], &["--signer", "keystore"]].concat()).is_err());
+    }
+}
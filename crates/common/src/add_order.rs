@@ -1,31 +1,37 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::simulation::{
+    new_fork, resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport,
+};
 use crate::{
     dotrain_order::DotrainOrderError,
     rainlang::compose_to_rainlang,
+    token_metadata::{TokenMetadataError, TokenMetadataResolver},
     transaction::{TransactionArgs, TransactionArgsError},
+    vault_id::derive_vault_id,
 };
-use alloy::primitives::{hex::FromHexError, private::rand, Address, U256};
-use alloy::sol_types::SolCall;
+use alloy::primitives::{hex::FromHexError, keccak256, private::rand, Address, B256, U256};
+use alloy::sol_types::{SolCall, SolValue};
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
 use alloy_ethers_typecast::transaction::{
-    ReadContractParameters, ReadableClientError, ReadableClientHttp, WritableClientError,
+    ReadableClientError, ReadableClientHttp, WritableClientError,
 };
-#[cfg(not(target_family = "wasm"))]
-use alloy_ethers_typecast::transaction::{WriteTransaction, WriteTransactionStatus};
 use dotrain::error::ComposeError;
 use rain_interpreter_dispair::{DISPair, DISPairError};
 #[cfg(not(target_family = "wasm"))]
-use rain_interpreter_eval::{
-    error::ForkCallError,
-    fork::{Forker, NewForkedEvm},
-};
+use rain_interpreter_eval::error::ForkCallError;
 use rain_interpreter_parser::{Parser2, ParserError, ParserV2};
 use rain_metadata::{
     ContentEncoding, ContentLanguage, ContentType, Error as RainMetaError, KnownMagic,
     RainMetaDocumentV1Item,
 };
-use rain_orderbook_app_settings::deployment::Deployment;
-use rain_orderbook_bindings::{
-    IOrderBookV4::{addOrder2Call, EvaluableV3, OrderConfigV3, TaskV1, IO},
-    ERC20::decimalsCall,
+use rain_orderbook_app_settings::{
+    deployment::{Deployment, VaultIdMode},
+    order::OrderIO,
+    token::Token,
+};
+use rain_orderbook_bindings::IOrderBookV4::{
+    addOrder2Call, EvaluableV3, OrderConfigV3, OrderV3, TaskV1, IO,
 };
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
@@ -60,6 +66,33 @@ pub enum AddOrderArgsError {
     #[cfg(not(target_family = "wasm"))]
     #[error(transparent)]
     ForkCallError(#[from] ForkCallError),
+    #[error("An owner is required to derive deterministic vault ids")]
+    MissingVaultIdOwner,
+    #[error(transparent)]
+    TokenMetadataError(#[from] TokenMetadataError),
+    #[error("Failed to read the decimals of token {0}")]
+    MissingTokenDecimals(Address),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+}
+
+impl RpcRequestError for AddOrderArgsError {
+    fn is_rpc_failure(&self) -> bool {
+        match self {
+            AddOrderArgsError::ReadableClientError(e)
+            | AddOrderArgsError::DISPairError(DISPairError::ReadableClientError(e))
+            | AddOrderArgsError::ParserError(ParserError::ReadableClientError(e)) => {
+                e.is_rpc_failure()
+            }
+            AddOrderArgsError::TokenMetadataError(e) => e.is_rpc_failure(),
+            _ => false,
+        }
+    }
+
+    fn no_rpc_urls() -> Self {
+        ReadableClientError::no_rpc_urls().into()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -70,6 +103,35 @@ pub struct AddOrderArgs {
     pub outputs: Vec<IO>,
     pub deployer: Address,
     pub bindings: HashMap<String, String>,
+    /// Order nonce, random if not set
+    #[serde(default)]
+    pub nonce: Option<B256>,
+    /// Order secret, random if not set
+    #[serde(default)]
+    pub secret: Option<B256>,
+}
+
+/// Derive an order nonce and secret from a deployment key and a salt, so
+/// adding the same deployment with the same salt yields the same order hash
+pub fn derive_nonce_and_secret(deployment_name: &str, salt: B256) -> (B256, B256) {
+    let nonce =
+        keccak256(("nonce".to_string(), deployment_name.to_string(), salt).abi_encode_params());
+    let secret =
+        keccak256(("secret".to_string(), deployment_name.to_string(), salt).abi_encode_params());
+    (nonce, secret)
+}
+
+/// Predict the hash of the order an addOrder2 call adds for the given owner,
+/// same as `LibOrder.hash` on chain
+pub fn predict_order_hash(call: &addOrder2Call, owner: Address) -> B256 {
+    let order = OrderV3 {
+        owner,
+        evaluable: call.config.evaluable.clone(),
+        validInputs: call.config.validInputs.clone(),
+        validOutputs: call.config.validOutputs.clone(),
+        nonce: call.config.nonce,
+    };
+    keccak256(order.abi_encode())
 }
 
 impl AddOrderArgs {
@@ -77,56 +139,77 @@ impl AddOrderArgs {
     pub async fn new_from_deployment(
         dotrain: String,
         deployment: Deployment,
+    ) -> Result<AddOrderArgs, AddOrderArgsError> {
+        Self::new_from_deployment_with_owner(dotrain, deployment, None).await
+    }
+
+    /// create a new instance from Deployment, with the owner that deterministic
+    /// vault ids are derived for
+    pub async fn new_from_deployment_with_owner(
+        dotrain: String,
+        deployment: Deployment,
+        owner: Option<Address>,
     ) -> Result<AddOrderArgs, AddOrderArgsError> {
         let random_vault_id: U256 = rand::random();
-        let mut inputs = vec![];
-        for input in &deployment.order.inputs {
-            if let Some(decimals) = input.token.decimals {
-                inputs.push(IO {
-                    token: input.token.address,
-                    vaultId: input.vault_id.unwrap_or(random_vault_id),
-                    decimals,
-                });
-            } else {
-                let client = ReadableClientHttp::new_from_url(input.token.network.rpc.to_string())?;
-                let parameters = ReadContractParameters {
-                    address: input.token.address,
-                    call: decimalsCall {},
-                    block_number: None,
-                };
-                let decimals = client.read(parameters).await?._0;
-                inputs.push(IO {
-                    token: input.token.address,
-                    vaultId: input.vault_id.unwrap_or(random_vault_id),
-                    decimals,
-                });
+        let vault_id = |io: &OrderIO| -> Result<U256, AddOrderArgsError> {
+            if let Some(vault_id) = io.vault_id {
+                return Ok(vault_id);
             }
-        }
-
-        let mut outputs = vec![];
-        for output in &deployment.order.outputs {
-            if let Some(decimals) = output.token.decimals {
-                outputs.push(IO {
-                    token: output.token.address,
-                    vaultId: output.vault_id.unwrap_or(random_vault_id),
-                    decimals,
-                });
-            } else {
-                let client =
-                    ReadableClientHttp::new_from_url(output.token.network.rpc.to_string())?;
-                let parameters = ReadContractParameters {
-                    address: output.token.address,
-                    call: decimalsCall {},
-                    block_number: None,
-                };
-                let decimals = client.read(parameters).await?._0;
-                outputs.push(IO {
-                    token: output.token.address,
-                    vaultId: output.vault_id.unwrap_or(random_vault_id),
-                    decimals,
-                });
+            match deployment.vault_id_mode.unwrap_or_default() {
+                VaultIdMode::Random => Ok(random_vault_id),
+                VaultIdMode::Deterministic => Ok(derive_vault_id(
+                    owner.ok_or(AddOrderArgsError::MissingVaultIdOwner)?,
+                    &deployment.name,
+                    io.token.address,
+                )),
+            }
+        };
+        // decimals of tokens that do not set them are read with one multicall,
+        // or one call per token where the multicall fails
+        let missing_decimals: Vec<&Token> = deployment
+            .order
+            .inputs
+            .iter()
+            .chain(deployment.order.outputs.iter())
+            .map(|io| io.token.as_ref())
+            .filter(|token| token.decimals.is_none())
+            .collect();
+        let mut read_decimals = HashMap::new();
+        if !missing_decimals.is_empty() {
+            let metadata = TokenMetadataResolver::default()
+                .resolve_tokens(&missing_decimals)
+                .await?;
+            for (token, metadata) in missing_decimals.iter().zip(metadata) {
+                read_decimals.insert(token.address, metadata.decimals);
             }
         }
+        let to_io = |io: &OrderIO| -> Result<IO, AddOrderArgsError> {
+            let decimals = match io.token.decimals {
+                Some(decimals) => decimals,
+                None => read_decimals
+                    .get(&io.token.address)
+                    .copied()
+                    .flatten()
+                    .ok_or(AddOrderArgsError::MissingTokenDecimals(io.token.address))?,
+            };
+            Ok(IO {
+                token: io.token.address,
+                vaultId: vault_id(io)?,
+                decimals,
+            })
+        };
+        let inputs = deployment
+            .order
+            .inputs
+            .iter()
+            .map(to_io)
+            .collect::<Result<Vec<_>, _>>()?;
+        let outputs = deployment
+            .order
+            .outputs
+            .iter()
+            .map(to_io)
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok(AddOrderArgs {
             dotrain: dotrain.to_string(),
@@ -134,9 +217,20 @@ impl AddOrderArgs {
             outputs,
             deployer: deployment.scenario.deployer.address,
             bindings: deployment.scenario.bindings.to_owned(),
+            nonce: None,
+            secret: None,
         })
     }
 
+    /// Use a nonce and secret derived from the deployment key and salt
+    /// instead of random ones
+    pub fn with_derived_nonce_and_secret(mut self, deployment_name: &str, salt: B256) -> Self {
+        let (nonce, secret) = derive_nonce_and_secret(deployment_name, salt);
+        self.nonce = Some(nonce);
+        self.secret = Some(secret);
+        self
+    }
+
     /// Read parser address from deployer contract, then call parser to parse rainlang into bytecode and constants
     async fn try_parse_rainlang(
         &self,
@@ -196,8 +290,16 @@ impl AddOrderArgs {
         Ok(res)
     }
 
-    /// Generate an addOrder call from given dotrain
-    pub async fn try_into_call(&self, rpc_url: String) -> Result<addOrder2Call, AddOrderArgsError> {
+    /// Generate an addOrder call from given dotrain, reading the deployer and
+    /// parser from the first of the rpc urls that responds
+    pub async fn try_into_call(&self, rpcs: &[String]) -> Result<addOrder2Call, AddOrderArgsError> {
+        with_rpc_failover(rpcs, |rpc_url| self.try_into_call_with_rpc(rpc_url)).await
+    }
+
+    async fn try_into_call_with_rpc(
+        &self,
+        rpc_url: String,
+    ) -> Result<addOrder2Call, AddOrderArgsError> {
         let rainlang = self.compose_to_rainlang()?;
         let bytecode = self
             .try_parse_rainlang(rpc_url.clone(), rainlang.clone())
@@ -237,8 +339,8 @@ impl AddOrderArgs {
                     bytecode: bytecode.into(),
                 },
                 meta: meta.into(),
-                nonce: alloy::primitives::private::rand::random::<U256>().into(),
-                secret: alloy::primitives::private::rand::random::<U256>().into(),
+                nonce: self.nonce.unwrap_or_else(|| rand::random::<U256>().into()),
+                secret: self.secret.unwrap_or_else(|| rand::random::<U256>().into()),
             },
             tasks: vec![post_task],
         })
@@ -250,16 +352,14 @@ impl AddOrderArgs {
         transaction_args: TransactionArgs,
         transaction_status_changed: S,
     ) -> Result<(), AddOrderArgsError> {
-        let ledger_client = transaction_args.clone().try_into_ledger_client().await?;
+        let signer = transaction_args.clone().try_into_signer().await?;
 
-        let add_order_call = self.try_into_call(transaction_args.clone().rpc_url).await?;
+        let add_order_call = self.try_into_call(&transaction_args.rpc_urls()).await?;
         let params = transaction_args
             .try_into_write_contract_parameters(add_order_call, transaction_args.orderbook_address)
             .await?;
 
-        WriteTransaction::new(ledger_client.client, params, 4, transaction_status_changed)
-            .execute()
-            .await?;
+        signer.execute(params, transaction_status_changed).await?;
 
         Ok(())
     }
@@ -269,41 +369,53 @@ impl AddOrderArgs {
         transaction_args: TransactionArgs,
     ) -> Result<Vec<u8>, AddOrderArgsError> {
         Ok(self
-            .try_into_call(transaction_args.clone().rpc_url)
+            .try_into_call(&transaction_args.rpc_urls())
             .await?
             .abi_encode())
     }
 
+    /// Generate addOrder2 calldata along with the hash the order will have
+    /// once added by the given owner
+    pub async fn get_add_order_calldata_with_order_hash(
+        &self,
+        transaction_args: TransactionArgs,
+        owner: Address,
+    ) -> Result<(Vec<u8>, B256), AddOrderArgsError> {
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        let order_hash = predict_order_hash(&call, owner);
+        Ok((call.abi_encode(), order_hash))
+    }
+
+    /// Simulate adding the order on a fork of the network and report the
+    /// outcome, including the traces of the post tasks
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, AddOrderArgsError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        let calls = vec![SimulationCall::new(
+            from,
+            transaction_args.orderbook_address,
+            call,
+        )];
+        Ok(simulate(&transaction_args, calls, &[], &[]).await?)
+    }
+
     #[cfg(not(target_family = "wasm"))]
     pub async fn simulate_execute(
         &self,
         transaction_args: TransactionArgs,
         from: Option<Address>,
     ) -> Result<(), AddOrderArgsError> {
-        let from_address = if let Some(v) = from {
-            v.0 .0
-        } else {
-            transaction_args
-                .clone()
-                .try_into_ledger_client()
-                .await?
-                .client
-                .address()
-                .0
-        };
-        let mut forker = Forker::new_with_fork(
-            NewForkedEvm {
-                fork_url: transaction_args.rpc_url.clone(),
-                fork_block_number: None,
-            },
-            None,
-            None,
-        )
-        .await?;
-        let call = self.try_into_call(transaction_args.rpc_url.clone()).await?;
+        let from_address = resolve_sender(&transaction_args, from).await?;
+        let mut forker = new_fork(&transaction_args).await?;
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
         forker
             .alloy_call_committing(
-                Address::from(from_address),
+                from_address,
                 transaction_args.orderbook_address,
                 call,
                 U256::ZERO,
@@ -349,6 +461,8 @@ price: 2e18;
             outputs: vec![],
             bindings: HashMap::new(),
             deployer: Address::default(),
+            nonce: None,
+            secret: None,
         };
 
         let meta_bytes = args.try_generate_meta(dotrain_body).unwrap();
@@ -428,6 +542,7 @@ price: 2e18;
         let network = Network {
             name: "test-network".to_string(),
             rpc: Url::parse("https://some-rpc.com").unwrap(),
+            fallback_rpcs: vec![],
             chain_id: 137,
             label: None,
             network_id: None,
@@ -492,8 +607,11 @@ price: 2e18;
             orderbook: None,
         };
         let deployment = Deployment {
+            name: "some-deployment".to_string(),
             scenario: Arc::new(scenario),
             order: Arc::new(order),
+            deposits: None,
+            vault_id_mode: None,
         };
 
         let dotrain = format!(
@@ -509,7 +627,7 @@ _ _: 0 0;
 "#,
             raindex_version = "1234"
         );
-        let result = AddOrderArgs::new_from_deployment(dotrain.to_string(), deployment)
+        let result = AddOrderArgs::new_from_deployment(dotrain.to_string(), deployment.clone())
             .await
             .unwrap();
 
@@ -518,6 +636,36 @@ _ _: 0 0;
 
         // input0 and output0 vaults should be the same random value
         assert_eq!(result.inputs[0].vaultId, result.outputs[0].vaultId);
+
+        // deterministic vault ids need an owner
+        let deployment = Deployment {
+            vault_id_mode: Some(VaultIdMode::Deterministic),
+            ..deployment
+        };
+        let result =
+            AddOrderArgs::new_from_deployment(dotrain.to_string(), deployment.clone()).await;
+        assert!(matches!(
+            result,
+            Err(AddOrderArgsError::MissingVaultIdOwner)
+        ));
+
+        let owner = Address::random();
+        let result = AddOrderArgs::new_from_deployment_with_owner(
+            dotrain.to_string(),
+            deployment,
+            Some(owner),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            result.inputs[0].vaultId,
+            derive_vault_id(owner, "some-deployment", token1_arc.address)
+        );
+        assert_eq!(result.inputs[1].vaultId, known_vault_id);
+        assert_eq!(
+            result.outputs[0].vaultId,
+            derive_vault_id(owner, "some-deployment", token3_arc.address)
+        );
     }
 
     #[tokio::test]
@@ -526,6 +674,7 @@ _ _: 0 0;
         let network = Network {
             name: "test-network".to_string(),
             rpc: Url::parse(&local_evm.url()).unwrap(),
+            fallback_rpcs: vec![],
             chain_id: 137,
             label: None,
             network_id: None,
@@ -589,8 +738,11 @@ _ _: 0 0;
             orderbook: None,
         };
         let deployment = Deployment {
+            name: "some-deployment".to_string(),
             scenario: Arc::new(scenario),
             order: Arc::new(order),
+            deposits: None,
+            vault_id_mode: None,
         };
 
         let dotrain = format!(
@@ -610,7 +762,7 @@ _ _: 0 0;
             .await
             .unwrap();
 
-        let add_order_call = result.try_into_call(local_evm.url()).await.unwrap();
+        let add_order_call = result.try_into_call(&[local_evm.url()]).await.unwrap();
 
         assert_eq!(add_order_call.config.validInputs.len(), 2);
         assert_eq!(add_order_call.config.validOutputs.len(), 1);
@@ -658,6 +810,7 @@ _ _: 0 0;
         let network = Network {
             name: "test-network".to_string(),
             rpc: Url::parse("https://some-rpc.com").unwrap(),
+            fallback_rpcs: vec![],
             chain_id: 137,
             label: None,
             network_id: None,
@@ -722,8 +875,11 @@ _ _: 0 0;
             orderbook: None,
         };
         let deployment = Deployment {
+            name: "some-deployment".to_string(),
             scenario: Arc::new(scenario),
             order: Arc::new(order),
+            deposits: None,
+            vault_id_mode: None,
         };
 
         let dotrain = format!(
@@ -829,6 +985,92 @@ _ _: 16 52;
             .unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_predict_order_hash() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let owner = local_evm.anvil.addresses()[0];
+
+        let dotrain = format!(
+            r#"
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+        network-id: 123
+        currency: ETH
+deployers:
+    some-key:
+        address: {deployer}
+tokens:
+    t1:
+        network: some-key
+        address: {token1}
+        decimals: 18
+    t2:
+        network: some-key
+        address: {token2}
+        decimals: 18
+orders:
+    some-key:
+        inputs:
+            - token: t1
+              vault-id: 0x01
+        outputs:
+            - token: t2
+              vault-id: 0x01
+scenarios:
+    some-key:
+deployments:
+    some-key:
+        scenario: some-key
+        order: some-key
+---
+#calculate-io
+_ _: 16 52;
+#handle-add-order
+:;
+#handle-io
+:;
+"#,
+            rpc_url = local_evm.url(),
+            deployer = local_evm.deployer.address(),
+            token1 = local_evm.tokens[0].address(),
+            token2 = local_evm.tokens[1].address(),
+        );
+
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let salt = B256::repeat_byte(7);
+        let args = AddOrderArgs::new_from_deployment(dotrain, deployment)
+            .await
+            .unwrap()
+            .with_derived_nonce_and_secret("some-key", salt);
+        assert_eq!(
+            (args.nonce.unwrap(), args.secret.unwrap()),
+            derive_nonce_and_secret("some-key", salt)
+        );
+
+        let tx_args = TransactionArgs {
+            orderbook_address: *local_evm.orderbook.address(),
+            rpc_url: local_evm.url(),
+            ..Default::default()
+        };
+        let (calldata, order_hash) = args
+            .get_add_order_calldata_with_order_hash(tx_args.clone(), owner)
+            .await
+            .unwrap();
+
+        // same nonce gives the same order hash
+        let (_, other_order_hash) = args
+            .get_add_order_calldata_with_order_hash(tx_args, owner)
+            .await
+            .unwrap();
+        assert_eq!(order_hash, other_order_hash);
+
+        let event = local_evm.add_order(&calldata, owner).await.0;
+        assert_eq!(order_hash, event.orderHash);
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_simulate_execute_err() {
         let local_evm = LocalEvm::new_with_tokens(2).await;
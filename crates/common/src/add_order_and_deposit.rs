@@ -0,0 +1,383 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::simulation::{
+    new_fork, resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport,
+};
+use crate::{
+    add_order::{AddOrderArgs, AddOrderArgsError},
+    deposit::DepositArgs,
+    transaction::{TransactionArgs, TransactionArgsError},
+    vault_batch::{VaultBatchArgs, VaultBatchError, VaultBatchItem},
+};
+use alloy::primitives::{
+    utils::{parse_units, UnitsError},
+    Address, Bytes, U256,
+};
+use alloy::sol_types::SolCall;
+use alloy_ethers_typecast::transaction::WritableClientError;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+#[cfg(not(target_family = "wasm"))]
+use rain_interpreter_eval::error::ForkCallError;
+use rain_orderbook_app_settings::deployment::Deployment;
+use rain_orderbook_bindings::{
+    IOrderBookV4::deposit2Call, OrderBook::multicallCall, IERC20::approveCall,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum AddOrderAndDepositArgsError {
+    #[error(transparent)]
+    AddOrderArgsError(#[from] AddOrderArgsError),
+    #[error(transparent)]
+    VaultBatchError(#[from] VaultBatchError),
+    #[error(transparent)]
+    TransactionArgs(#[from] TransactionArgsError),
+    #[error(transparent)]
+    WritableClientError(#[from] WritableClientError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    ForkCallError(#[from] ForkCallError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+    #[error("Invalid deposit amount '{0}': {1}")]
+    InvalidDepositAmount(String, UnitsError),
+    #[error("Deposit token {0} is not an output of the order")]
+    DepositTokenNotOutput(Address),
+}
+
+/// Adds an order and deposits into its output vaults in a single OrderBook
+/// multicall transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddOrderAndDepositArgs {
+    pub add_order: AddOrderArgs,
+    pub deposits: Vec<DepositArgs>,
+}
+
+impl AddOrderAndDepositArgs {
+    /// Create a new instance from Deployment, deposit amounts are read from
+    /// the deployment's deposits and go into the matching output vaults.
+    /// The owner is needed for deployments with deterministic vault ids.
+    pub async fn new_from_deployment(
+        dotrain: String,
+        deployment: Deployment,
+        owner: Option<Address>,
+    ) -> Result<Self, AddOrderAndDepositArgsError> {
+        let add_order =
+            AddOrderArgs::new_from_deployment_with_owner(dotrain, deployment.clone(), owner)
+                .await?;
+
+        let mut deposits = vec![];
+        for deposit in deployment.deposits.unwrap_or_default() {
+            let output = add_order
+                .outputs
+                .iter()
+                .find(|v| v.token == deposit.token.address)
+                .ok_or(AddOrderAndDepositArgsError::DepositTokenNotOutput(
+                    deposit.token.address,
+                ))?;
+            let amount: U256 = parse_units(&deposit.amount, output.decimals)
+                .map_err(|e| {
+                    AddOrderAndDepositArgsError::InvalidDepositAmount(deposit.amount.clone(), e)
+                })?
+                .into();
+            deposits.push(DepositArgs {
+                token: output.token,
+                vault_id: output.vaultId,
+                amount,
+            });
+        }
+
+        Ok(Self {
+            add_order,
+            deposits,
+        })
+    }
+
+    fn vault_batch(&self) -> VaultBatchArgs {
+        VaultBatchArgs {
+            items: self
+                .deposits
+                .iter()
+                .cloned()
+                .map(VaultBatchItem::Deposit)
+                .collect(),
+        }
+    }
+
+    /// Generate the multicall of addOrder2 followed by the deposit2 calls
+    pub async fn try_into_call(
+        &self,
+        rpcs: &[String],
+    ) -> Result<multicallCall, AddOrderAndDepositArgsError> {
+        let add_order_call = self.add_order.try_into_call(rpcs).await?;
+        let mut data: Vec<Bytes> = vec![add_order_call.abi_encode().into()];
+        for deposit in &self.deposits {
+            data.push(deposit2Call::from(deposit.clone()).abi_encode().into());
+        }
+        Ok(multicallCall { data })
+    }
+
+    /// Approve calldata for the full amount of each deposited token, meant for
+    /// signers whose current allowance can not be read, eg multisigs
+    pub fn get_approve_calldatas(
+        &self,
+        transaction_args: TransactionArgs,
+    ) -> Vec<(Address, Vec<u8>)> {
+        self.vault_batch()
+            .deposit_totals()
+            .into_iter()
+            .map(|total| {
+                (
+                    total.token,
+                    approveCall {
+                        spender: transaction_args.orderbook_address,
+                        amount: total.amount,
+                    }
+                    .abi_encode(),
+                )
+            })
+            .collect()
+    }
+
+    pub async fn get_multicall_calldata(
+        &self,
+        transaction_args: TransactionArgs,
+    ) -> Result<Vec<u8>, AddOrderAndDepositArgsError> {
+        Ok(self
+            .try_into_call(&transaction_args.rpc_urls())
+            .await?
+            .abi_encode())
+    }
+
+    /// Execute IERC20 approve calls for the deposited tokens that do not have
+    /// enough allowance yet
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute_approvals<S: Fn(Address, WriteTransactionStatus<approveCall>)>(
+        &self,
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), AddOrderAndDepositArgsError> {
+        self.vault_batch()
+            .execute_approvals(transaction_args, transaction_status_changed)
+            .await?;
+        Ok(())
+    }
+
+    /// Execute the OrderBook multicall adding the order and depositing
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute<S: Fn(WriteTransactionStatus<multicallCall>)>(
+        &self,
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), AddOrderAndDepositArgsError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+
+        let multicall = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        let params = transaction_args
+            .try_into_write_contract_parameters(multicall, transaction_args.orderbook_address)
+            .await?;
+
+        signer.execute(params, transaction_status_changed).await?;
+
+        Ok(())
+    }
+
+    /// Simulate the approvals and the multicall on a fork of the network
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate_execute(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<(), AddOrderAndDepositArgsError> {
+        let from_address = resolve_sender(&transaction_args, from).await?;
+        let mut forker = new_fork(&transaction_args).await?;
+
+        for total in self.vault_batch().deposit_totals() {
+            forker
+                .alloy_call_committing(
+                    from_address,
+                    total.token,
+                    approveCall {
+                        spender: transaction_args.orderbook_address,
+                        amount: total.amount,
+                    },
+                    U256::ZERO,
+                    true,
+                )
+                .await?;
+        }
+
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        forker
+            .alloy_call_committing(
+                from_address,
+                transaction_args.orderbook_address,
+                call,
+                U256::ZERO,
+                true,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Simulate the approvals and the multicall on a fork of the network and
+    /// report the outcome
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, AddOrderAndDepositArgsError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let orderbook = transaction_args.orderbook_address;
+        let vault_batch = self.vault_batch();
+
+        let mut calls = vault_batch.approve_simulation_calls(from, orderbook);
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        calls.push(SimulationCall::new(from, orderbook, call));
+
+        let tokens: Vec<Address> = vault_batch
+            .deposit_totals()
+            .iter()
+            .map(|total| total.token)
+            .collect();
+        Ok(simulate(&transaction_args, calls, &tokens, &[from, orderbook]).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dotrain_order::DotrainOrder, transaction::SignerSource};
+    use alloy::primitives::{hex::encode_prefixed, utils::parse_ether};
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    fn get_dotrain(local_evm: &LocalEvm, deposit: &str) -> String {
+        format!(
+            r#"
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+        network-id: 123
+        currency: ETH
+deployers:
+    some-key:
+        address: {deployer}
+tokens:
+    t1:
+        network: some-key
+        address: {token1}
+        decimals: 18
+    t2:
+        network: some-key
+        address: {token2}
+        decimals: 18
+orderbook:
+    some-key:
+        address: {orderbook}
+orders:
+    some-key:
+        inputs:
+            - token: t2
+        outputs:
+            - token: t1
+              vault-id: 0x07
+scenarios:
+    some-key:
+deployments:
+    some-key:
+        scenario: some-key
+        order: some-key
+        deposits:
+            t1: {deposit}
+---
+#calculate-io
+_ _: 100e18 2e18;
+#handle-add-order
+:;
+#handle-io
+:;
+"#,
+            rpc_url = local_evm.url(),
+            orderbook = local_evm.orderbook.address(),
+            deployer = local_evm.deployer.address(),
+            token1 = local_evm.tokens[0].address(),
+            token2 = local_evm.tokens[1].address(),
+        )
+    }
+
+    async fn get_args(local_evm: &LocalEvm, deposit: &str) -> AddOrderAndDepositArgs {
+        let dotrain = get_dotrain(local_evm, deposit);
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        AddOrderAndDepositArgs::new_from_deployment(dotrain, deployment, None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_new_from_deployment() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let args = get_args(&local_evm, "12.5").await;
+
+        assert_eq!(args.deposits.len(), 1);
+        assert_eq!(args.deposits[0].token, *local_evm.tokens[0].address());
+        assert_eq!(args.deposits[0].vault_id, U256::from(7));
+        assert_eq!(args.deposits[0].amount, parse_ether("12.5").unwrap());
+
+        let call = args.try_into_call(&[local_evm.url()]).await.unwrap();
+        assert_eq!(call.data.len(), 2);
+        let deposit = deposit2Call::abi_decode(&call.data[1], true).unwrap();
+        assert_eq!(deposit.vaultId, U256::from(7));
+
+        let dotrain = get_dotrain(&local_evm, "abc");
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let result = AddOrderAndDepositArgs::new_from_deployment(dotrain, deployment, None).await;
+        assert!(matches!(
+            result,
+            Err(AddOrderAndDepositArgsError::InvalidDepositAmount(amount, _)) if amount == "abc"
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_simulate_and_execute() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let owner = local_evm.anvil.addresses()[0];
+        let args = get_args(&local_evm, "100").await;
+
+        std::env::set_var(
+            "TEST_ADD_ORDER_AND_DEPOSIT_PRIVATE_KEY",
+            encode_prefixed(local_evm.anvil.keys()[0].to_bytes()),
+        );
+        let tx_args = TransactionArgs {
+            orderbook_address: *local_evm.orderbook.address(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            rpc_url: local_evm.url(),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_ADD_ORDER_AND_DEPOSIT_PRIVATE_KEY".to_string(),
+            },
+            ..Default::default()
+        };
+
+        args.simulate_execute(tx_args.clone(), Some(owner))
+            .await
+            .unwrap();
+        args.execute_approvals(tx_args.clone(), |_, _| {})
+            .await
+            .unwrap();
+        args.execute(tx_args, |_| {}).await.unwrap();
+
+        let balance = local_evm
+            .orderbook
+            .vaultBalance(owner, *local_evm.tokens[0].address(), U256::from(7))
+            .call()
+            .await
+            .unwrap()
+            ._0;
+        assert_eq!(balance, parse_ether("100").unwrap());
+    }
+}
@@ -0,0 +1,639 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::{
+    simulation::{
+        new_fork, resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport,
+    },
+    transaction::TransactionArgs,
+};
+use crate::{
+    take_orders::{QuotedTakeOrder, TakeOrdersArgs, TakeOrdersArgsError},
+    transaction::TransactionArgsError,
+};
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::sol_types::{SolCall, SolValue};
+use alloy_ethers_typecast::transaction::WritableClientError;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+#[cfg(not(target_family = "wasm"))]
+use ethers::types::TransactionReceipt;
+#[cfg(not(target_family = "wasm"))]
+use rain_interpreter_eval::{error::ForkCallError, fork::Forker};
+#[cfg(not(target_family = "wasm"))]
+use rain_orderbook_bindings::IERC20::balanceOfCall;
+use rain_orderbook_bindings::{
+    GenericPoolOrderBookV4ArbOrderTaker, GenericPoolOrderBookV4FlashBorrower,
+    IOrderBookV4::{TakeOrdersConfigV3, TaskV1},
+    RouteProcessorOrderBookV4ArbOrderTaker,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ArbArgsError {
+    #[error(transparent)]
+    TakeOrdersArgs(#[from] TakeOrdersArgsError),
+    #[error(transparent)]
+    WritableClientError(#[from] WritableClientError),
+    #[error(transparent)]
+    TransactionArgs(#[from] TransactionArgsError),
+    #[error(transparent)]
+    AbiDecode(#[from] alloy::sol_types::Error),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    ForkCallError(#[from] ForkCallError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+    #[error("Arb is not profitable")]
+    NotProfitable,
+}
+
+/// The external liquidity an arb contract trades against, one variant per
+/// arb contract implementation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ArbExchange {
+    /// GenericPoolOrderBookV4ArbOrderTaker, calls the pool with the given
+    /// calldata after approving the spender for the orders' output token
+    GenericPoolOrderTaker {
+        spender: Address,
+        pool: Address,
+        calldata: Bytes,
+    },
+    /// GenericPoolOrderBookV4FlashBorrower, same as the order taker but the
+    /// swap is funded by a flash loan from the orderbook
+    GenericPoolFlashBorrower {
+        spender: Address,
+        pool: Address,
+        calldata: Bytes,
+    },
+    /// RouteProcessorOrderBookV4ArbOrderTaker, processes the given route with
+    /// the contract's route processor
+    RouteProcessorOrderTaker { route: Bytes },
+}
+
+impl ArbExchange {
+    /// Encode the exchange data the way the arb contract decodes it
+    pub fn encode(&self) -> Bytes {
+        match self {
+            ArbExchange::GenericPoolOrderTaker {
+                spender,
+                pool,
+                calldata,
+            }
+            | ArbExchange::GenericPoolFlashBorrower {
+                spender,
+                pool,
+                calldata,
+            } => (*spender, *pool, calldata.clone())
+                .abi_encode_params()
+                .into(),
+            ArbExchange::RouteProcessorOrderTaker { route } => {
+                (route.clone(),).abi_encode_params().into()
+            }
+        }
+    }
+}
+
+/// Arguments to take orders through an arb contract against external liquidity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbArgs {
+    pub arb_contract: Address,
+    pub exchange: ArbExchange,
+    pub take_orders: TakeOrdersArgs,
+    /// The post arb task, must be the same task the arb contract was deployed
+    /// with, if any
+    #[serde(default)]
+    pub task: TaskV1,
+}
+
+/// Token amounts the arb sends back to the sender, named from the taker's
+/// point of view same as [TakeOrdersArgs]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ArbProfit {
+    pub input_token: U256,
+    pub output_token: U256,
+}
+
+impl ArbProfit {
+    pub fn is_profitable(&self) -> bool {
+        !self.input_token.is_zero() || !self.output_token.is_zero()
+    }
+}
+
+/// arb3 call of any of the supported arb contracts
+#[derive(Debug, Clone)]
+pub enum ArbCall {
+    GenericPoolOrderTaker(GenericPoolOrderBookV4ArbOrderTaker::arb3Call),
+    GenericPoolFlashBorrower(GenericPoolOrderBookV4FlashBorrower::arb3Call),
+    RouteProcessorOrderTaker(RouteProcessorOrderBookV4ArbOrderTaker::arb3Call),
+}
+
+impl ArbCall {
+    pub fn abi_encode(&self) -> Vec<u8> {
+        match self {
+            ArbCall::GenericPoolOrderTaker(call) => call.abi_encode(),
+            ArbCall::GenericPoolFlashBorrower(call) => call.abi_encode(),
+            ArbCall::RouteProcessorOrderTaker(call) => call.abi_encode(),
+        }
+    }
+}
+
+/// Write transaction status of an arb3 call, independent of the arb contract
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, Clone)]
+pub enum ArbTransactionStatus {
+    PendingPrepare,
+    PendingSign,
+    PendingSend,
+    Confirmed(TransactionReceipt),
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl<T: SolCall> From<WriteTransactionStatus<T>> for ArbTransactionStatus {
+    fn from(value: WriteTransactionStatus<T>) -> Self {
+        match value {
+            WriteTransactionStatus::PendingPrepare(_) => ArbTransactionStatus::PendingPrepare,
+            WriteTransactionStatus::PendingSign(_) => ArbTransactionStatus::PendingSign,
+            WriteTransactionStatus::PendingSend(_) => ArbTransactionStatus::PendingSend,
+            WriteTransactionStatus::Confirmed(receipt) => ArbTransactionStatus::Confirmed(receipt),
+        }
+    }
+}
+
+impl ArbArgs {
+    /// Build the takeOrders2 config, order takers get the exchange data as
+    /// the takeOrders2 data so it is passed to their onTakeOrders callback
+    pub fn try_into_take_orders_config(
+        &self,
+        quoted: &[QuotedTakeOrder],
+    ) -> Result<TakeOrdersConfigV3, ArbArgsError> {
+        let mut config = self.take_orders.try_into_call(quoted)?.config;
+        if !matches!(self.exchange, ArbExchange::GenericPoolFlashBorrower { .. }) {
+            config.data = self.exchange.encode();
+        }
+        Ok(config)
+    }
+
+    /// Build the arb3 call of the arb contract for the given orderbook,
+    /// the orderbook and arb bindings share the same abi types so they are
+    /// converted by abi encoding
+    pub fn try_into_call(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        orderbook: Address,
+    ) -> Result<ArbCall, ArbArgsError> {
+        let config = self.try_into_take_orders_config(quoted)?.abi_encode();
+        let task = self.task.abi_encode();
+
+        let call = match &self.exchange {
+            ArbExchange::GenericPoolOrderTaker { .. } => {
+                use GenericPoolOrderBookV4ArbOrderTaker as Arb;
+                ArbCall::GenericPoolOrderTaker(Arb::arb3Call {
+                    orderBook: orderbook,
+                    takeOrders: Arb::TakeOrdersConfigV3::abi_decode(&config, true)?,
+                    task: Arb::TaskV1::abi_decode(&task, true)?,
+                })
+            }
+            ArbExchange::GenericPoolFlashBorrower { .. } => {
+                use GenericPoolOrderBookV4FlashBorrower as Arb;
+                ArbCall::GenericPoolFlashBorrower(Arb::arb3Call {
+                    orderBook: orderbook,
+                    takeOrders: Arb::TakeOrdersConfigV3::abi_decode(&config, true)?,
+                    exchangeData: self.exchange.encode(),
+                    task: Arb::TaskV1::abi_decode(&task, true)?,
+                })
+            }
+            ArbExchange::RouteProcessorOrderTaker { .. } => {
+                use RouteProcessorOrderBookV4ArbOrderTaker as Arb;
+                ArbCall::RouteProcessorOrderTaker(Arb::arb3Call {
+                    orderBook: orderbook,
+                    takeOrders: Arb::TakeOrdersConfigV3::abi_decode(&config, true)?,
+                    task: Arb::TaskV1::abi_decode(&task, true)?,
+                })
+            }
+        };
+        Ok(call)
+    }
+
+    pub fn get_arb_calldata(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        orderbook: Address,
+    ) -> Result<Vec<u8>, ArbArgsError> {
+        Ok(self.try_into_call(quoted, orderbook)?.abi_encode())
+    }
+
+    /// Simulate the arb on a fork of the network and return what the sender
+    /// would receive from it, errors if the sender would receive nothing
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate_profit(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<ArbProfit, ArbArgsError> {
+        let from_address = resolve_sender(&transaction_args, from).await?;
+        let mut forker = new_fork(&transaction_args).await?;
+
+        let input_before =
+            Self::fork_balance(&forker, self.take_orders.input_token, from_address).await?;
+        let output_before =
+            Self::fork_balance(&forker, self.take_orders.output_token, from_address).await?;
+
+        let call = self.try_into_call(quoted, transaction_args.orderbook_address)?;
+        match call {
+            ArbCall::GenericPoolOrderTaker(call) => {
+                forker
+                    .alloy_call_committing(from_address, self.arb_contract, call, U256::ZERO, true)
+                    .await?;
+            }
+            ArbCall::GenericPoolFlashBorrower(call) => {
+                forker
+                    .alloy_call_committing(from_address, self.arb_contract, call, U256::ZERO, true)
+                    .await?;
+            }
+            ArbCall::RouteProcessorOrderTaker(call) => {
+                forker
+                    .alloy_call_committing(from_address, self.arb_contract, call, U256::ZERO, true)
+                    .await?;
+            }
+        }
+
+        let input_after =
+            Self::fork_balance(&forker, self.take_orders.input_token, from_address).await?;
+        let output_after =
+            Self::fork_balance(&forker, self.take_orders.output_token, from_address).await?;
+
+        let profit = ArbProfit {
+            input_token: input_after.saturating_sub(input_before),
+            output_token: output_after.saturating_sub(output_before),
+        };
+        if !profit.is_profitable() {
+            return Err(ArbArgsError::NotProfitable);
+        }
+        Ok(profit)
+    }
+
+    /// Simulate the arb on a fork of the network and report the outcome,
+    /// including the sender's balances of the pair's tokens and the traces of
+    /// the taken orders
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, ArbArgsError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let calls = vec![SimulationCall {
+            from,
+            to: self.arb_contract,
+            calldata: self.get_arb_calldata(quoted, transaction_args.orderbook_address)?,
+        }];
+        let tokens = [self.take_orders.input_token, self.take_orders.output_token];
+        Ok(simulate(&transaction_args, calls, &tokens, &[from]).await?)
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    async fn fork_balance(
+        forker: &Forker,
+        token: Address,
+        account: Address,
+    ) -> Result<U256, ArbArgsError> {
+        Ok(forker
+            .alloy_call(account, token, balanceOfCall { account }, true)
+            .await?
+            .typed_return
+            ._0)
+    }
+
+    /// Execute the arb contract's arb3 call
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute<S: Fn(ArbTransactionStatus)>(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), ArbArgsError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+
+        match self.try_into_call(quoted, transaction_args.orderbook_address)? {
+            ArbCall::GenericPoolOrderTaker(call) => {
+                let params = transaction_args
+                    .try_into_write_contract_parameters(call, self.arb_contract)
+                    .await?;
+                signer
+                    .execute(params, |status| transaction_status_changed(status.into()))
+                    .await?;
+            }
+            ArbCall::GenericPoolFlashBorrower(call) => {
+                let params = transaction_args
+                    .try_into_write_contract_parameters(call, self.arb_contract)
+                    .await?;
+                signer
+                    .execute(params, |status| transaction_status_changed(status.into()))
+                    .await?;
+            }
+            ArbCall::RouteProcessorOrderTaker(call) => {
+                let params = transaction_args
+                    .try_into_write_contract_parameters(call, self.arb_contract)
+                    .await?;
+                signer
+                    .execute(params, |status| transaction_status_changed(status.into()))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_order::AddOrderArgs, dotrain_order::DotrainOrder, transaction::SignerSource};
+    use alloy::primitives::{hex::encode_prefixed, utils::parse_ether, B256};
+    use rain_orderbook_bindings::IOrderBookV4::{OrderV3, TakeOrderConfigV3, IO};
+    use rain_orderbook_quote::OrderQuoteValue;
+    use rain_orderbook_subgraph_client::types::common::{
+        BigInt, Bytes as SgBytes, Order, Orderbook,
+    };
+    use rain_orderbook_test_fixtures::{
+        ContractTxHandler, GenericPoolOrderBookV4ArbOrderTaker as ArbOrderTaker, LocalEvm, MockPool,
+    };
+
+    fn get_order(order: &OrderV3, orderbook: Address) -> Order {
+        Order {
+            id: SgBytes(B256::random().to_string()),
+            order_bytes: SgBytes(encode_prefixed(order.abi_encode())),
+            order_hash: SgBytes(B256::random().to_string()),
+            owner: SgBytes(order.owner.to_string()),
+            outputs: vec![],
+            inputs: vec![],
+            orderbook: Orderbook {
+                id: SgBytes(orderbook.to_string()),
+            },
+            active: true,
+            timestamp_added: BigInt("0".to_string()),
+            meta: None,
+            add_events: vec![],
+            trades: vec![],
+        }
+    }
+
+    fn get_args(exchange: ArbExchange) -> (ArbArgs, Vec<QuotedTakeOrder>) {
+        let token1 = Address::random();
+        let token2 = Address::random();
+        let order = OrderV3 {
+            owner: Address::random(),
+            validInputs: vec![IO {
+                token: token2,
+                decimals: 18,
+                vaultId: U256::from(1),
+            }],
+            validOutputs: vec![IO {
+                token: token1,
+                decimals: 18,
+                vaultId: U256::from(1),
+            }],
+            ..Default::default()
+        };
+        let args = ArbArgs {
+            arb_contract: Address::random(),
+            exchange,
+            take_orders: TakeOrdersArgs {
+                orders: vec![get_order(&order, Address::random())],
+                input_token: token1,
+                output_token: token2,
+                minimum_input: U256::from(1),
+                maximum_input: U256::from(10),
+                maximum_io_ratio: U256::MAX,
+            },
+            task: TaskV1::default(),
+        };
+        let quoted = vec![QuotedTakeOrder {
+            config: TakeOrderConfigV3 {
+                order,
+                inputIOIndex: U256::ZERO,
+                outputIOIndex: U256::ZERO,
+                signedContext: vec![],
+            },
+            quote: OrderQuoteValue::default(),
+        }];
+        (args, quoted)
+    }
+
+    #[test]
+    fn test_exchange_encode() {
+        let spender = Address::random();
+        let pool = Address::random();
+        let calldata = Bytes::from(vec![1, 2, 3]);
+        let exchange = ArbExchange::GenericPoolOrderTaker {
+            spender,
+            pool,
+            calldata: calldata.clone(),
+        };
+        let decoded =
+            <(Address, Address, Bytes)>::abi_decode_params(&exchange.encode(), true).unwrap();
+        assert_eq!(decoded, (spender, pool, calldata.clone()));
+
+        let exchange = ArbExchange::RouteProcessorOrderTaker {
+            route: calldata.clone(),
+        };
+        let decoded = <(Bytes,)>::abi_decode_params(&exchange.encode(), true).unwrap();
+        assert_eq!(decoded, (calldata,));
+    }
+
+    #[test]
+    fn test_try_into_call() {
+        let orderbook = Address::random();
+        let exchange = ArbExchange::GenericPoolOrderTaker {
+            spender: Address::random(),
+            pool: Address::random(),
+            calldata: Bytes::from(vec![1, 2, 3]),
+        };
+        let (args, quoted) = get_args(exchange.clone());
+        let ArbCall::GenericPoolOrderTaker(call) = args.try_into_call(&quoted, orderbook).unwrap()
+        else {
+            panic!("expected order taker arb call");
+        };
+        assert_eq!(call.orderBook, orderbook);
+        assert_eq!(call.takeOrders.data, exchange.encode());
+        assert_eq!(call.takeOrders.maximumInput, U256::from(10));
+
+        let exchange = ArbExchange::GenericPoolFlashBorrower {
+            spender: Address::random(),
+            pool: Address::random(),
+            calldata: Bytes::from(vec![1, 2, 3]),
+        };
+        let (args, quoted) = get_args(exchange.clone());
+        let ArbCall::GenericPoolFlashBorrower(call) =
+            args.try_into_call(&quoted, orderbook).unwrap()
+        else {
+            panic!("expected flash borrower arb call");
+        };
+        assert!(call.takeOrders.data.is_empty());
+        assert_eq!(call.exchangeData, exchange.encode());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_arb_simulate_and_execute() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let orderbook = &local_evm.orderbook;
+        let owner = local_evm.anvil.addresses()[0];
+        let arber = local_evm.anvil.addresses()[1];
+        let token1 = local_evm.tokens[0].clone();
+        let token2 = local_evm.tokens[1].clone();
+
+        // pool with enough token2 liquidity to buy the order's token1 at a better price
+        let pool = MockPool::deploy(local_evm.provider.clone()).await.unwrap();
+        token2
+            .transfer(*pool.address(), parse_ether("100").unwrap())
+            .do_send(&local_evm)
+            .await
+            .unwrap();
+        let arb = ArbOrderTaker::deploy(
+            local_evm.provider.clone(),
+            ArbOrderTaker::OrderBookV4ArbConfigV2 {
+                orderBook: *orderbook.address(),
+                task: Default::default(),
+                implementationData: Default::default(),
+            },
+        )
+        .await
+        .unwrap();
+
+        let dotrain = format!(
+            r#"
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+        network-id: 123
+        currency: ETH
+deployers:
+    some-key:
+        address: {deployer}
+tokens:
+    t1:
+        network: some-key
+        address: {token1}
+        decimals: 18
+    t2:
+        network: some-key
+        address: {token2}
+        decimals: 18
+orderbook:
+    some-key:
+        address: {orderbook}
+orders:
+    some-key:
+        inputs:
+            - token: t2
+        outputs:
+            - token: t1
+              vault-id: 0x01
+scenarios:
+    some-key:
+deployments:
+    some-key:
+        scenario: some-key
+        order: some-key
+---
+#calculate-io
+_ _: 100e18 2e18;
+#handle-add-order
+:;
+#handle-io
+:;
+"#,
+            rpc_url = local_evm.url(),
+            orderbook = orderbook.address(),
+            deployer = local_evm.deployer.address(),
+            token1 = token1.address(),
+            token2 = token2.address(),
+        );
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
+            .await
+            .unwrap()
+            .try_into_call(&[local_evm.url()])
+            .await
+            .unwrap()
+            .abi_encode();
+        let order = local_evm
+            .add_order_and_deposit(
+                &calldata,
+                owner,
+                *token1.address(),
+                parse_ether("1000").unwrap(),
+                U256::from(1),
+            )
+            .await
+            .0
+            .order;
+        let order = OrderV3::abi_decode(&order.abi_encode(), true).unwrap();
+
+        // take 10 token1 for 20 token2 from the order, sell them to the pool for 25 token2
+        let swap = MockPool::swapCall {
+            tokenIn: *token1.address(),
+            tokenOut: *token2.address(),
+            amountIn: parse_ether("10").unwrap(),
+            amountOut: parse_ether("25").unwrap(),
+        };
+        let args = ArbArgs {
+            arb_contract: *arb.address(),
+            exchange: ArbExchange::GenericPoolOrderTaker {
+                spender: *pool.address(),
+                pool: *pool.address(),
+                calldata: swap.abi_encode().into(),
+            },
+            take_orders: TakeOrdersArgs {
+                orders: vec![get_order(&order, *orderbook.address())],
+                input_token: *token1.address(),
+                output_token: *token2.address(),
+                minimum_input: parse_ether("10").unwrap(),
+                maximum_input: parse_ether("10").unwrap(),
+                maximum_io_ratio: parse_ether("3").unwrap(),
+            },
+            task: TaskV1::default(),
+        };
+
+        std::env::set_var(
+            "TEST_ARB_PRIVATE_KEY",
+            encode_prefixed(local_evm.anvil.keys()[1].to_bytes()),
+        );
+        let tx_args = TransactionArgs {
+            orderbook_address: *orderbook.address(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            rpc_url: local_evm.url(),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_ARB_PRIVATE_KEY".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let quoted = args
+            .take_orders
+            .quote_candidates(&local_evm.url(), *orderbook.address())
+            .await
+            .unwrap();
+        let profit = args
+            .simulate_profit(&quoted, tx_args.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            profit,
+            ArbProfit {
+                input_token: U256::ZERO,
+                output_token: parse_ether("5").unwrap(),
+            }
+        );
+
+        args.execute(&quoted, tx_args, |_| {}).await.unwrap();
+        let balance = token2.balanceOf(arber).call().await.unwrap()._0;
+        assert_eq!(balance, parse_ether("5").unwrap());
+    }
+}
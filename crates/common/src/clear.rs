@@ -0,0 +1,672 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::{
+    simulation::{resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport},
+    transaction::TransactionArgs,
+};
+use crate::{
+    transaction::TransactionArgsError,
+    utils::fixed_point::{fixed_point_mul_up, scale_n},
+};
+use alloy::primitives::{private::rand, Address, U256};
+use alloy::sol_types::SolCall;
+use alloy_ethers_typecast::transaction::WritableClientError;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+use rain_orderbook_bindings::IOrderBookV4::{clear2Call, ClearConfig, OrderV3, Quote};
+use rain_orderbook_quote::{
+    error::Error as QuoteError, BatchQuoteTarget, OrderQuoteValue, QuoteTarget,
+};
+use rain_orderbook_subgraph_client::types::{common::Order, order_detail_traits::OrderDetailError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClearArgsError {
+    #[error(transparent)]
+    WritableClientError(#[from] WritableClientError),
+    #[error(transparent)]
+    TransactionArgs(#[from] TransactionArgsError),
+    #[error(transparent)]
+    OrderDetailError(#[from] OrderDetailError),
+    #[error(transparent)]
+    QuoteError(#[from] QuoteError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+    #[error("Orders have the same owner and cannot be cleared against each other")]
+    SameOwner,
+    #[error("Orders do not have opposing input and output tokens")]
+    NoOpposingIO,
+    #[error("No profitable clear exists between the orders")]
+    NotProfitable,
+}
+
+/// Arguments to clear two orders with opposing IO tokens against each other
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClearArgs {
+    pub alice: Order,
+    pub bob: Order,
+    /// The clearer's vault that receives alice's bounty in alice's output
+    /// token, a random vault id is used if not specified
+    pub alice_bounty_vault_id: Option<U256>,
+    /// The clearer's vault that receives bob's bounty in bob's output token,
+    /// a random vault id is used if not specified
+    pub bob_bounty_vault_id: Option<U256>,
+}
+
+/// Orderbook's clear state change for a pair of quotes, all values are in
+/// their token's decimals, bounties are in the respective order's output token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ClearStateChange {
+    pub alice_input: U256,
+    pub alice_output: U256,
+    pub bob_input: U256,
+    pub bob_output: U256,
+    pub alice_bounty: U256,
+    pub bob_bounty: U256,
+}
+
+impl ClearStateChange {
+    /// Calculates the clear state change the same way the orderbook does, ie
+    /// from the 18 decimals quotes with outputs rounded down and inputs rounded
+    /// up to their token's decimals, alice's input token being bob's output
+    /// token and vice versa. Returns None if the orders' io ratios overlap and
+    /// so clearing would revert.
+    pub fn calculate(
+        alice: &OrderQuoteValue,
+        bob: &OrderQuoteValue,
+        alice_output_decimals: u8,
+        bob_output_decimals: u8,
+    ) -> Option<Self> {
+        let (alice_input, alice_output) =
+            Self::calculate_alice(alice, bob, alice_output_decimals, bob_output_decimals);
+        let (bob_input, bob_output) =
+            Self::calculate_alice(bob, alice, bob_output_decimals, alice_output_decimals);
+        Some(Self {
+            alice_input,
+            alice_output,
+            bob_input,
+            bob_output,
+            alice_bounty: alice_output.checked_sub(bob_input)?,
+            bob_bounty: bob_output.checked_sub(alice_input)?,
+        })
+    }
+
+    fn calculate_alice(
+        alice: &OrderQuoteValue,
+        bob: &OrderQuoteValue,
+        alice_output_decimals: u8,
+        bob_output_decimals: u8,
+    ) -> (U256, U256) {
+        let bob_input_max_18 = fixed_point_mul_up(bob.max_output, bob.ratio);
+        let alice_output_18 = alice.max_output.min(bob_input_max_18);
+        let alice_input_18 = fixed_point_mul_up(alice_output_18, alice.ratio);
+        (
+            scale_n(alice_input_18, bob_output_decimals, true),
+            scale_n(alice_output_18, alice_output_decimals, false),
+        )
+    }
+
+    /// If clearing pays any bounty at all
+    pub fn is_profitable(&self) -> bool {
+        !self.alice_bounty.is_zero() || !self.bob_bounty.is_zero()
+    }
+}
+
+/// A possible clear of the orders' opposing IOs with its quotes
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClearCandidate {
+    pub config: ClearConfig,
+    pub alice_quote: OrderQuoteValue,
+    pub bob_quote: OrderQuoteValue,
+    pub state_change: ClearStateChange,
+}
+
+impl ClearArgs {
+    pub fn try_into_orders(&self) -> Result<(OrderV3, OrderV3), ClearArgsError> {
+        let alice: OrderV3 = self.alice.clone().try_into()?;
+        let bob: OrderV3 = self.bob.clone().try_into()?;
+        if alice.owner == bob.owner {
+            return Err(ClearArgsError::SameOwner);
+        }
+        Ok((alice, bob))
+    }
+
+    /// Build the clear configs of all the opposing IO pairs of the orders,
+    /// ie alice's output token is bob's input token and vice versa, with
+    /// matching decimals
+    pub fn get_clear_configs(&self) -> Result<Vec<ClearConfig>, ClearArgsError> {
+        let (alice, bob) = self.try_into_orders()?;
+        let alice_bounty_vault_id = self.alice_bounty_vault_id.unwrap_or_else(rand::random);
+        let bob_bounty_vault_id = self.bob_bounty_vault_id.unwrap_or_else(rand::random);
+
+        let mut configs = vec![];
+        for (alice_input_index, alice_input) in alice.validInputs.iter().enumerate() {
+            for (alice_output_index, alice_output) in alice.validOutputs.iter().enumerate() {
+                if alice_input.token == alice_output.token {
+                    continue;
+                }
+                let bob_input_index = bob.validInputs.iter().position(|v| {
+                    v.token == alice_output.token && v.decimals == alice_output.decimals
+                });
+                let bob_output_index = bob.validOutputs.iter().position(|v| {
+                    v.token == alice_input.token && v.decimals == alice_input.decimals
+                });
+                if let (Some(bob_input_index), Some(bob_output_index)) =
+                    (bob_input_index, bob_output_index)
+                {
+                    configs.push(ClearConfig {
+                        aliceInputIOIndex: U256::from(alice_input_index),
+                        aliceOutputIOIndex: U256::from(alice_output_index),
+                        bobInputIOIndex: U256::from(bob_input_index),
+                        bobOutputIOIndex: U256::from(bob_output_index),
+                        aliceBountyVaultId: alice_bounty_vault_id,
+                        bobBountyVaultId: bob_bounty_vault_id,
+                    });
+                }
+            }
+        }
+        if configs.is_empty() {
+            return Err(ClearArgsError::NoOpposingIO);
+        }
+        Ok(configs)
+    }
+
+    /// Quote both orders for every opposing IO pair and return the profitable
+    /// clears, sorted by alice's bounty and then bob's bounty, best first
+    pub async fn quote_candidates(
+        &self,
+        rpc_url: &str,
+        orderbook: Address,
+        block_number: Option<u64>,
+    ) -> Result<Vec<ClearCandidate>, ClearArgsError> {
+        let (alice, bob) = self.try_into_orders()?;
+        let configs = self.get_clear_configs()?;
+
+        let quote_target = |order: &OrderV3, input_index: U256, output_index: U256| QuoteTarget {
+            orderbook,
+            quote_config: Quote {
+                order: order.clone(),
+                inputIOIndex: input_index,
+                outputIOIndex: output_index,
+                signedContext: vec![],
+            },
+        };
+        let quote_targets = configs
+            .iter()
+            .flat_map(|config| {
+                [
+                    quote_target(&alice, config.aliceInputIOIndex, config.aliceOutputIOIndex),
+                    quote_target(&bob, config.bobInputIOIndex, config.bobOutputIOIndex),
+                ]
+            })
+            .collect();
+        let mut quotes = BatchQuoteTarget(quote_targets)
+            .do_quote(rpc_url, block_number, None)
+            .await?
+            .into_iter();
+
+        let mut candidates = vec![];
+        for config in configs {
+            let (Some(alice_quote), Some(bob_quote)) = (quotes.next(), quotes.next()) else {
+                break;
+            };
+            let (Ok(alice_quote), Ok(bob_quote)) = (alice_quote, bob_quote) else {
+                continue;
+            };
+            let alice_output_decimals =
+                alice.validOutputs[config.aliceOutputIOIndex.to::<usize>()].decimals;
+            let bob_output_decimals =
+                bob.validOutputs[config.bobOutputIOIndex.to::<usize>()].decimals;
+            if let Some(state_change) = ClearStateChange::calculate(
+                &alice_quote,
+                &bob_quote,
+                alice_output_decimals,
+                bob_output_decimals,
+            ) {
+                if state_change.is_profitable() {
+                    candidates.push(ClearCandidate {
+                        config,
+                        alice_quote,
+                        bob_quote,
+                        state_change,
+                    });
+                }
+            }
+        }
+        candidates.sort_by(|a, b| {
+            (b.state_change.alice_bounty, b.state_change.bob_bounty)
+                .cmp(&(a.state_change.alice_bounty, a.state_change.bob_bounty))
+        });
+
+        Ok(candidates)
+    }
+
+    /// Build the clear2 call for the given candidate
+    pub fn try_into_call(&self, candidate: &ClearCandidate) -> Result<clear2Call, ClearArgsError> {
+        let (alice, bob) = self.try_into_orders()?;
+        Ok(clear2Call::new((
+            alice,
+            bob,
+            candidate.config.clone(),
+            vec![],
+            vec![],
+        )))
+    }
+
+    /// Quote the orders and build the clear2 call of the most profitable clear
+    pub async fn try_into_best_call(
+        &self,
+        rpc_url: &str,
+        orderbook: Address,
+    ) -> Result<(ClearCandidate, clear2Call), ClearArgsError> {
+        let candidate = self
+            .quote_candidates(rpc_url, orderbook, None)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or(ClearArgsError::NotProfitable)?;
+        let call = self.try_into_call(&candidate)?;
+        Ok((candidate, call))
+    }
+
+    pub fn get_clear_calldata(
+        &self,
+        candidate: &ClearCandidate,
+    ) -> Result<Vec<u8>, ClearArgsError> {
+        Ok(self.try_into_call(candidate)?.abi_encode())
+    }
+
+    /// Execute OrderbookV4 clear2 call
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute<S: Fn(WriteTransactionStatus<clear2Call>)>(
+        &self,
+        candidate: &ClearCandidate,
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), ClearArgsError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+
+        let clear_call = self.try_into_call(candidate)?;
+        let params = transaction_args
+            .try_into_write_contract_parameters(clear_call, transaction_args.orderbook_address)
+            .await?;
+
+        signer.execute(params, transaction_status_changed).await?;
+
+        Ok(())
+    }
+
+    /// Simulate the clear2 call on a fork of the network and report the
+    /// outcome, including the traces of both orders and the balances of
+    /// their IO tokens, from the given address or the signer's address
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        candidate: &ClearCandidate,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, ClearArgsError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let orderbook = transaction_args.orderbook_address;
+        let call = self.try_into_call(candidate)?;
+
+        let mut tokens: Vec<Address> = vec![];
+        for io in [&call.alice, &call.bob]
+            .into_iter()
+            .flat_map(|order| order.validInputs.iter().chain(order.validOutputs.iter()))
+        {
+            if !tokens.contains(&io.token) {
+                tokens.push(io.token);
+            }
+        }
+        let calls = vec![SimulationCall::new(from, orderbook, call)];
+        Ok(simulate(&transaction_args, calls, &tokens, &[from, orderbook]).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_order::AddOrderArgs, dotrain_order::DotrainOrder};
+    use alloy::primitives::{hex::encode_prefixed, utils::parse_ether, B256};
+    use alloy::sol_types::SolValue;
+    use rain_orderbook_bindings::IOrderBookV4::IO;
+    use rain_orderbook_subgraph_client::types::common::{BigInt, Bytes, Orderbook};
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    fn get_order(order: &OrderV3, orderbook: Address) -> Order {
+        Order {
+            id: Bytes(B256::random().to_string()),
+            order_bytes: Bytes(encode_prefixed(order.abi_encode())),
+            order_hash: Bytes(B256::random().to_string()),
+            owner: Bytes(order.owner.to_string()),
+            outputs: vec![],
+            inputs: vec![],
+            orderbook: Orderbook {
+                id: Bytes(orderbook.to_string()),
+            },
+            active: true,
+            timestamp_added: BigInt("0".to_string()),
+            meta: None,
+            add_events: vec![],
+            trades: vec![],
+        }
+    }
+
+    fn get_io(token: Address) -> IO {
+        IO {
+            token,
+            decimals: 18,
+            vaultId: U256::from(1),
+        }
+    }
+
+    fn quote(max_output: &str, ratio: &str) -> OrderQuoteValue {
+        OrderQuoteValue {
+            max_output: parse_ether(max_output).unwrap(),
+            ratio: parse_ether(ratio).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_clear_state_change() {
+        // alice sells 10 at 0.5, bob sells 100 at 1.5, so bob's output is capped
+        // at alice's input max of 5 and alice gets 2.5 bounty
+        let result =
+            ClearStateChange::calculate(&quote("10", "0.5"), &quote("100", "1.5"), 18, 18).unwrap();
+        assert_eq!(
+            result,
+            ClearStateChange {
+                alice_input: parse_ether("5").unwrap(),
+                alice_output: parse_ether("10").unwrap(),
+                bob_input: parse_ether("7.5").unwrap(),
+                bob_output: parse_ether("5").unwrap(),
+                alice_bounty: parse_ether("2.5").unwrap(),
+                bob_bounty: U256::ZERO,
+            }
+        );
+        assert!(result.is_profitable());
+
+        // overlapping ratios would revert
+        assert!(
+            ClearStateChange::calculate(&quote("10", "2"), &quote("10", "1"), 18, 18).is_none()
+        );
+
+        // exact match clears without any bounty
+        let result =
+            ClearStateChange::calculate(&quote("10", "1"), &quote("10", "1"), 18, 18).unwrap();
+        assert!(!result.is_profitable());
+    }
+
+    #[test]
+    fn test_clear_state_change_decimals() {
+        // alice's output token has 6 decimals, so alice's output is rounded
+        // down and bob's input rounded up to 6 decimals before the bounty is
+        // taken, which leaves alice's bounty a unit short of the 18 decimals
+        // difference of 2.500000000000024999
+        let result = ClearStateChange::calculate(
+            &quote("10.0000000000005", "0.5"),
+            &quote("100", "1.50000000000002"),
+            6,
+            18,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            ClearStateChange {
+                alice_input: parse_ether("5.00000000000025").unwrap(),
+                alice_output: U256::from(10_000_000),
+                bob_input: U256::from(7_500_001),
+                bob_output: parse_ether("5.00000000000025").unwrap(),
+                alice_bounty: U256::from(2_499_999),
+                bob_bounty: U256::ZERO,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_clear_configs() {
+        let orderbook = Address::random();
+        let token1 = Address::random();
+        let token2 = Address::random();
+        let token3 = Address::random();
+        let alice = OrderV3 {
+            owner: Address::random(),
+            validInputs: vec![get_io(token1), get_io(token3)],
+            validOutputs: vec![get_io(token2)],
+            ..Default::default()
+        };
+        let bob = OrderV3 {
+            owner: Address::random(),
+            validInputs: vec![get_io(token3), get_io(token2)],
+            validOutputs: vec![get_io(token1)],
+            ..Default::default()
+        };
+        let args = ClearArgs {
+            alice: get_order(&alice, orderbook),
+            bob: get_order(&bob, orderbook),
+            alice_bounty_vault_id: Some(U256::from(7)),
+            bob_bounty_vault_id: Some(U256::from(8)),
+        };
+
+        let result = args.get_clear_configs().unwrap();
+        let expected = vec![ClearConfig {
+            aliceInputIOIndex: U256::from(0),
+            aliceOutputIOIndex: U256::from(0),
+            bobInputIOIndex: U256::from(1),
+            bobOutputIOIndex: U256::from(0),
+            aliceBountyVaultId: U256::from(7),
+            bobBountyVaultId: U256::from(8),
+        }];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_get_clear_configs_unhappy() {
+        let orderbook = Address::random();
+        let token1 = Address::random();
+        let token2 = Address::random();
+        let alice = OrderV3 {
+            owner: Address::random(),
+            validInputs: vec![get_io(token1)],
+            validOutputs: vec![get_io(token2)],
+            ..Default::default()
+        };
+
+        // same owner
+        let args = ClearArgs {
+            alice: get_order(&alice, orderbook),
+            bob: get_order(&alice, orderbook),
+            alice_bounty_vault_id: None,
+            bob_bounty_vault_id: None,
+        };
+        assert!(matches!(
+            args.get_clear_configs(),
+            Err(ClearArgsError::SameOwner)
+        ));
+
+        // same direction
+        let bob = OrderV3 {
+            owner: Address::random(),
+            ..alice.clone()
+        };
+        let args = ClearArgs {
+            alice: get_order(&alice, orderbook),
+            bob: get_order(&bob, orderbook),
+            alice_bounty_vault_id: None,
+            bob_bounty_vault_id: None,
+        };
+        assert!(matches!(
+            args.get_clear_configs(),
+            Err(ClearArgsError::NoOpposingIO)
+        ));
+    }
+
+    fn get_dotrain(
+        local_evm: &LocalEvm,
+        input: Address,
+        output: Address,
+        calculate_io: &str,
+    ) -> String {
+        format!(
+            r#"
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+        network-id: 123
+        currency: ETH
+deployers:
+    some-key:
+        address: {deployer}
+tokens:
+    t1:
+        network: some-key
+        address: {input}
+        decimals: 18
+    t2:
+        network: some-key
+        address: {output}
+        decimals: 18
+orderbook:
+    some-key:
+        address: {orderbook}
+orders:
+    some-key:
+        inputs:
+            - token: t1
+              vault-id: 0x01
+        outputs:
+            - token: t2
+              vault-id: 0x01
+scenarios:
+    some-key:
+deployments:
+    some-key:
+        scenario: some-key
+        order: some-key
+---
+#calculate-io
+{calculate_io}
+#handle-add-order
+:;
+#handle-io
+:;
+"#,
+            rpc_url = local_evm.url(),
+            orderbook = local_evm.orderbook.address(),
+            deployer = local_evm.deployer.address(),
+        )
+    }
+
+    async fn add_test_order(local_evm: &LocalEvm, dotrain: String, owner: Address) -> OrderV3 {
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
+            .await
+            .unwrap()
+            .try_into_call(&[local_evm.url()])
+            .await
+            .unwrap()
+            .abi_encode();
+        let order = local_evm.add_order(&calldata, owner).await.0.order;
+        OrderV3::abi_decode(&order.abi_encode(), true).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_clear_simulate() {
+        let mut local_evm = LocalEvm::new().await;
+        let alice_owner = local_evm.anvil.addresses()[0];
+        let bob_owner = local_evm.anvil.addresses()[1];
+        let clearer = local_evm.anvil.addresses()[2];
+        let token1 = local_evm
+            .deploy_new_token(
+                "Token1",
+                "Token1",
+                18,
+                parse_ether("1000").unwrap(),
+                alice_owner,
+            )
+            .await;
+        let token2 = local_evm
+            .deploy_new_token(
+                "Token2",
+                "Token2",
+                18,
+                parse_ether("1000").unwrap(),
+                bob_owner,
+            )
+            .await;
+
+        // alice sells token1 for token2 at 0.5, bob sells token2 for token1 at 1.5
+        let alice_dotrain = get_dotrain(
+            &local_evm,
+            *token2.address(),
+            *token1.address(),
+            "_ _: 10e18 5e17;",
+        );
+        let bob_dotrain = get_dotrain(
+            &local_evm,
+            *token1.address(),
+            *token2.address(),
+            "_ _: 100e18 15e17;",
+        );
+        let alice = add_test_order(&local_evm, alice_dotrain, alice_owner).await;
+        let bob = add_test_order(&local_evm, bob_dotrain, bob_owner).await;
+        local_evm
+            .deposit(
+                alice_owner,
+                *token1.address(),
+                parse_ether("100").unwrap(),
+                U256::from(1),
+            )
+            .await;
+        local_evm
+            .deposit(
+                bob_owner,
+                *token2.address(),
+                parse_ether("100").unwrap(),
+                U256::from(1),
+            )
+            .await;
+
+        let orderbook = *local_evm.orderbook.address();
+        let args = ClearArgs {
+            alice: get_order(&alice, orderbook),
+            bob: get_order(&bob, orderbook),
+            alice_bounty_vault_id: Some(U256::from(3)),
+            bob_bounty_vault_id: Some(U256::from(4)),
+        };
+        let (candidate, _) = args
+            .try_into_best_call(&local_evm.url(), orderbook)
+            .await
+            .unwrap();
+        assert_eq!(
+            candidate.state_change.alice_bounty,
+            parse_ether("2.5").unwrap()
+        );
+        assert_eq!(candidate.state_change.bob_bounty, U256::ZERO);
+
+        let tx_args = TransactionArgs {
+            orderbook_address: orderbook,
+            rpc_url: local_evm.url(),
+            ..Default::default()
+        };
+        let report = args
+            .simulate(&candidate, tx_args, Some(clearer))
+            .await
+            .unwrap();
+        assert!(!report.reverted);
+        // both orders' tokens for the clearer and the orderbook
+        assert_eq!(report.balance_deltas.len(), 4);
+        assert!(report
+            .balance_deltas
+            .iter()
+            .any(|v| v.token == *token1.address() && v.account == clearer));
+        assert!(report
+            .balance_deltas
+            .iter()
+            .any(|v| v.token == *token2.address() && v.account == orderbook));
+    }
+}
@@ -1,15 +1,16 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::simulation::{
+    resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport,
+};
 use crate::transaction::{TransactionArgs, TransactionArgsError, WritableTransactionExecuteError};
 use alloy::primitives::{Address, U256};
 use alloy::sol_types::SolCall;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
 use alloy_ethers_typecast::transaction::{
     ReadContractParametersBuilder, ReadContractParametersBuilderError, ReadableClient,
     ReadableClientError, WritableClientError,
 };
-#[cfg(not(target_family = "wasm"))]
-use alloy_ethers_typecast::{
-    ethers_address_to_alloy,
-    transaction::{WriteTransaction, WriteTransactionStatus},
-};
 use rain_orderbook_bindings::{
     IOrderBookV4::deposit2Call,
     IERC20::{allowanceCall, approveCall},
@@ -35,7 +36,7 @@ pub enum DepositError {
     TransactionArgsError(#[from] TransactionArgsError),
 }
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct DepositArgs {
     pub token: Address,
     pub vault_id: U256,
@@ -80,29 +81,25 @@ impl DepositArgs {
         transaction_args: TransactionArgs,
         transaction_status_changed: S,
     ) -> Result<(), DepositError> {
-        let ledger_client = transaction_args.clone().try_into_ledger_client().await?;
+        let signer = transaction_args.clone().try_into_signer().await?;
 
         // Check allowance already granted for this token and contract
         let current_allowance = self
-            .read_allowance(
-                ethers_address_to_alloy(ledger_client.client.address()),
-                transaction_args.clone(),
-            )
+            .read_allowance(signer.address(), transaction_args.clone())
             .await?;
 
-        // If more allowance is required, then call approve for the difference
+        // If more allowance is required, then approve the full amount, since
+        // approve sets the allowance rather than adding to it
         if current_allowance < self.amount {
             let approve_call = approveCall {
                 spender: transaction_args.orderbook_address,
-                amount: self.amount - current_allowance,
+                amount: self.amount,
             };
             let params = transaction_args
                 .try_into_write_contract_parameters(approve_call, self.token)
                 .await?;
 
-            WriteTransaction::new(ledger_client.client, params, 4, transaction_status_changed)
-                .execute()
-                .await?;
+            signer.execute(params, transaction_status_changed).await?;
         }
 
         Ok(())
@@ -111,11 +108,10 @@ impl DepositArgs {
     pub async fn get_approve_calldata(
         &self,
         transaction_args: TransactionArgs,
-        current_allowance: U256,
     ) -> Result<Vec<u8>, WritableTransactionExecuteError> {
         let approve_call = approveCall {
             spender: transaction_args.orderbook_address,
-            amount: self.amount - current_allowance,
+            amount: self.amount,
         };
         Ok(approve_call.abi_encode())
     }
@@ -127,20 +123,41 @@ impl DepositArgs {
         transaction_args: TransactionArgs,
         transaction_status_changed: S,
     ) -> Result<(), DepositError> {
-        let ledger_client = transaction_args.clone().try_into_ledger_client().await?;
+        let signer = transaction_args.clone().try_into_signer().await?;
 
         let deposit_call: deposit2Call = self.clone().into();
         let params = transaction_args
             .try_into_write_contract_parameters(deposit_call, transaction_args.orderbook_address)
             .await?;
 
-        WriteTransaction::new(ledger_client.client, params, 4, transaction_status_changed)
-            .execute()
-            .await?;
+        signer.execute(params, transaction_status_changed).await?;
 
         Ok(())
     }
 
+    /// Simulate approving and depositing on a fork of the network
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, SimulationError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let orderbook = transaction_args.orderbook_address;
+        let calls = vec![
+            SimulationCall::new(
+                from,
+                self.token,
+                approveCall {
+                    spender: orderbook,
+                    amount: self.amount,
+                },
+            ),
+            SimulationCall::new(from, orderbook, deposit2Call::from(self.clone())),
+        ];
+        simulate(&transaction_args, calls, &[self.token], &[from, orderbook]).await
+    }
+
     pub async fn get_deposit_calldata(&self) -> Result<Vec<u8>, WritableTransactionExecuteError> {
         let deposit_call: deposit2Call = self.clone().into();
         Ok(deposit_call.abi_encode())
@@ -150,7 +167,9 @@ impl DepositArgs {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy::primitives::Address;
+    use crate::transaction::SignerSource;
+    use alloy::primitives::{hex::encode_prefixed, Address};
+    use rain_orderbook_test_fixtures::LocalEvm;
 
     #[test]
     fn test_deposit_args_into() {
@@ -173,4 +192,42 @@ mod tests {
         assert_eq!(deposit_call.vaultId, U256::from(42));
         assert_eq!(deposit_call.amount, U256::from(100));
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_execute_deposit_with_private_key_signer() {
+        let local_evm = LocalEvm::new_with_tokens(1).await;
+        let token = local_evm.tokens[0].clone();
+        let owner = local_evm.anvil.addresses()[0];
+        std::env::set_var(
+            "TEST_DEPOSIT_PRIVATE_KEY",
+            encode_prefixed(local_evm.anvil.keys()[0].to_bytes()),
+        );
+
+        let tx_args = TransactionArgs {
+            orderbook_address: *local_evm.orderbook.address(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            rpc_url: local_evm.url(),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_DEPOSIT_PRIVATE_KEY".to_string(),
+            },
+            ..Default::default()
+        };
+        let args = DepositArgs {
+            token: *token.address(),
+            vault_id: U256::from(1),
+            amount: U256::from(1000),
+        };
+
+        args.execute_approve(tx_args.clone(), |_| {}).await.unwrap();
+        args.execute_deposit(tx_args, |_| {}).await.unwrap();
+
+        let balance = local_evm
+            .orderbook
+            .vaultBalance(owner, *token.address(), U256::from(1))
+            .call()
+            .await
+            .unwrap()
+            ._0;
+        assert_eq!(balance, U256::from(1000));
+    }
 }
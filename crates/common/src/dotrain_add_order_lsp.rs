@@ -1,6 +1,8 @@
 #[cfg(not(target_family = "wasm"))]
 use crate::add_order::ORDERBOOK_ORDER_ENTRYPOINTS;
 #[cfg(not(target_family = "wasm"))]
+use crate::frontmatter::{parse_frontmatter_config, SETTINGS_DOCUMENT};
+#[cfg(not(target_family = "wasm"))]
 use crate::rainlang::parse_rainlang_on_fork;
 #[cfg(not(target_family = "wasm"))]
 use alloy::primitives::Address;
@@ -56,6 +58,27 @@ impl DotrainAddOrderLsp {
         LANG_SERVICES.do_complete(&self.text_document, position, None, self.rebinds.clone())
     }
 
+    /// get problems of the frontmatter of a given text document item merged
+    /// with the given settings, located at the yaml key they are about, or
+    /// at the start of the document with the settings named if they are
+    /// about the settings
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn frontmatter_problems(&self, settings: Option<String>) -> Vec<Problem> {
+        match parse_frontmatter_config(&self.text_document.text, settings).await {
+            Ok(_) => vec![],
+            Err(e) if e.document.as_deref() == Some(SETTINGS_DOCUMENT) => vec![Problem {
+                msg: format!("{}: {}", SETTINGS_DOCUMENT, e),
+                position: [0, 0],
+                code: ErrorCode::NativeParserError,
+            }],
+            Err(e) => vec![Problem {
+                msg: e.to_string(),
+                position: e.span.map(|span| [span.start, span.end]).unwrap_or([0, 0]),
+                code: ErrorCode::NativeParserError,
+            }],
+        }
+    }
+
     /// get problems for a given text document item
     #[cfg(not(target_family = "wasm"))]
     pub async fn problems(
@@ -63,14 +86,12 @@ impl DotrainAddOrderLsp {
         rpc_url: &str,
         block_number: Option<u64>,
         deployer: Option<Address>,
+        settings: Option<String>,
     ) -> Vec<Problem> {
         let rain_document =
             LANG_SERVICES.new_rain_document(&self.text_document, self.rebinds.clone());
-        let mut bindings_problems = rain_document
-            .bindings_problems()
-            .iter()
-            .map(|&v| v.clone())
-            .collect::<Vec<_>>();
+        let mut bindings_problems = self.frontmatter_problems(settings).await;
+        bindings_problems.extend(rain_document.bindings_problems().iter().map(|&v| v.clone()));
         let top_problems = rain_document.problems();
         if !top_problems.is_empty() {
             bindings_problems.extend(top_problems.to_vec());
@@ -30,9 +30,13 @@ impl DotrainOrder {
         &self,
         deployments: Vec<String>,
     ) -> Result<DotrainOrder, DotrainOrderError> {
-        // new empty config to copy used fields into
-        let mut new_config_source = ConfigSource::default();
+        // new empty config to copy used fields into, keeping the env
+        // interpolations so placeholders are written back
         let config_source = &self.config_source;
+        let mut new_config_source = ConfigSource {
+            env_interpolations: config_source.env_interpolations.clone(),
+            ..Default::default()
+        };
 
         for deployment in deployments {
             // find and insert the specified deployment
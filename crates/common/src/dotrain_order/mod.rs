@@ -10,7 +10,7 @@ use futures::future::join_all;
 use rain_interpreter_parser::{ParserError, ParserV2};
 pub use rain_metadata::types::authoring::v2::*;
 use rain_orderbook_app_settings::{
-    config_source::{ConfigSource, ConfigSourceError},
+    config_source::{ConfigSource, ConfigSourceError, ParseContext},
     merge::MergeError,
     Config, ParseConfigSourceError,
 };
@@ -121,17 +121,7 @@ impl DotrainOrder {
         dotrain: String,
         config: Option<String>,
     ) -> Result<DotrainOrder, DotrainOrderError> {
-        let frontmatter = RainDocument::get_front_matter(&dotrain)
-            .unwrap_or("")
-            .to_string();
-        let (mut frontmatter_config, config_string) =
-            ConfigSource::try_from_string(frontmatter, config).await?;
-        frontmatter_config.merge(config_string)?;
-        Ok(Self {
-            dotrain,
-            config_source: frontmatter_config.clone(),
-            config: frontmatter_config.try_into()?,
-        })
+        Self::new_with_context(dotrain, config, &ParseContext::from_process()).await
     }
 
     // get this instance's dotrain string
@@ -207,6 +197,27 @@ impl DotrainOrder {
 }
 
 impl DotrainOrder {
+    /// Same as [DotrainOrder::new] but parsing the frontmatter and settings
+    /// in the given context, eg to also resolve placeholders from a `.env`
+    /// file
+    pub async fn new_with_context(
+        dotrain: String,
+        config: Option<String>,
+        context: &ParseContext,
+    ) -> Result<DotrainOrder, DotrainOrderError> {
+        let frontmatter = RainDocument::get_front_matter(&dotrain)
+            .unwrap_or("")
+            .to_string();
+        // the settings override the values of the frontmatter
+        let frontmatter_config =
+            ConfigSource::try_from_string_with_override(frontmatter, config, context).await?;
+        Ok(Self {
+            dotrain,
+            config_source: frontmatter_config.clone(),
+            config: frontmatter_config.try_into()?,
+        })
+    }
+
     /// get this instance's config
     pub fn config(&self) -> &Config {
         &self.config
@@ -576,6 +587,46 @@ networks:
         );
     }
 
+    #[tokio::test]
+    async fn test_config_merge_override() {
+        let server = mock_server(vec![]);
+        let dotrain = format!(
+            r#"
+networks:
+  polygon:
+    rpc: {rpc_url}
+    chain-id: 137
+    network-id: 137
+    currency: MATIC
+---
+#calculate-io
+_ _: 00;
+
+#handle-io
+:;"#,
+            rpc_url = server.url("/rpc-polygon"),
+        );
+
+        // only overrides the rpc of the frontmatter's network
+        let settings = format!(
+            r#"
+networks:
+    polygon:
+        rpc: {rpc_url}"#,
+            rpc_url = server.url("/rpc-private"),
+        );
+
+        let merged_dotrain_order =
+            DotrainOrder::new(dotrain.to_string(), Some(settings.to_string()))
+                .await
+                .unwrap();
+
+        let polygon = merged_dotrain_order.config.networks.get("polygon").unwrap();
+        assert_eq!(polygon.rpc.to_string(), server.url("/rpc-private"));
+        assert_eq!(polygon.chain_id, 137);
+        assert_eq!(polygon.currency, Some("MATIC".to_string()));
+    }
+
     #[tokio::test]
     async fn test_get_pragmas_for_scenario() {
         let pragma_addresses = vec![Address::random()];
@@ -1,11 +1,269 @@
 use dotrain::RainDocument;
 pub use rain_metadata::types::authoring::v2::*;
-use rain_orderbook_app_settings::{config::ParseConfigSourceError, config_source::ConfigSource};
+use rain_orderbook_app_settings::{
+    config::{Config, ParseConfigSourceError},
+    config_source::{
+        ConfigSource, ConfigSourceError, ParseContext, PartialConfigSource, TokenConfigSource,
+    },
+    merge::MergeStrategy,
+    span::SpannedConfigError,
+    token_metadata::write_token_metadata,
+};
+use std::collections::HashMap;
 
 /// Parse dotrain frontmatter and merges it with top Config if given
 pub async fn parse_frontmatter(dotrain: String) -> Result<ConfigSource, ParseConfigSourceError> {
+    parse_frontmatter_with_context(dotrain, &ParseContext::from_process()).await
+}
+
+/// Parse dotrain frontmatter in the given context
+pub async fn parse_frontmatter_with_context(
+    dotrain: String,
+    context: &ParseContext,
+) -> Result<ConfigSource, ParseConfigSourceError> {
     let frontmatter = RainDocument::get_front_matter(dotrain.as_str()).unwrap_or("");
-    Ok(ConfigSource::try_from_string(frontmatter.to_string(), None)
-        .await?
-        .0)
+    Ok(
+        ConfigSource::try_from_string_with_context(frontmatter.to_string(), None, context)
+            .await?
+            .0,
+    )
+}
+
+/// Name of the frontmatter document in [SpannedConfigError::document]
+pub const FRONTMATTER_DOCUMENT: &str = "frontmatter";
+
+/// Name of the settings document in [SpannedConfigError::document]
+pub const SETTINGS_DOCUMENT: &str = "settings";
+
+/// Parse dotrain frontmatter into a Config, with the given settings if any
+/// overriding its values, locating errors in the document the values they
+/// are about came from, spans in the frontmatter are relative to the dotrain
+/// document. Parses offline against the process environment for
+/// diagnostics, a config that needs imports or remote networks fetched from
+/// urls or variables that are not set cannot be checked and gives `None`.
+pub async fn parse_frontmatter_config(
+    dotrain: &str,
+    settings: Option<String>,
+) -> Result<Option<Config>, SpannedConfigError> {
+    let frontmatter = RainDocument::get_front_matter(dotrain).unwrap_or("");
+    let offset = frontmatter_offset(dotrain, frontmatter);
+    let settings = settings.unwrap_or_default();
+    let documents = [
+        (FRONTMATTER_DOCUMENT, frontmatter),
+        (SETTINGS_DOCUMENT, settings.as_str()),
+    ];
+    let locate = |document: usize, error: ParseConfigSourceError| {
+        let (name, source) = documents[document];
+        SpannedConfigError {
+            document: Some(name.to_string()),
+            ..SpannedConfigError::new(source, error)
+        }
+    };
+
+    let context = ParseContext {
+        offline: true,
+        ..ParseContext::from_process()
+    };
+    let mut sources = vec![];
+    for (i, (name, source)) in documents.iter().enumerate() {
+        match PartialConfigSource::try_from_string(source.to_string(), &context).await {
+            Ok(partial) => sources.push((name.to_string(), partial)),
+            Err(e) if e.needs_network() || e.needs_env() => return Ok(None),
+            Err(e) => return Err(in_dotrain(dotrain, offset, locate(i, e.into()))),
+        }
+    }
+    let (config, provenance) =
+        ConfigSource::merge_all(sources, MergeStrategy::Override).map_err(|e| {
+            in_dotrain(
+                dotrain,
+                offset,
+                locate(0, ConfigSourceError::from(e).into()),
+            )
+        })?;
+
+    Config::try_from(config).map(Some).map_err(|error| {
+        in_dotrain(
+            dotrain,
+            offset,
+            SpannedConfigError::in_documents(&documents, &provenance, error),
+        )
+    })
+}
+
+/// Make the span of an error located in the frontmatter relative to the
+/// dotrain document
+fn in_dotrain(dotrain: &str, offset: usize, mut error: SpannedConfigError) -> SpannedConfigError {
+    if error.document.as_deref() == Some(FRONTMATTER_DOCUMENT) {
+        error.span = error.span.map(|span| span.shift(dotrain, offset));
+    }
+    error
+}
+
+/// Write the decimals, symbols and labels of tokens into the frontmatter of
+/// a dotrain document, see [write_token_metadata]
+pub fn write_frontmatter_token_metadata(
+    dotrain: &str,
+    tokens: &HashMap<String, TokenConfigSource>,
+) -> String {
+    let Some(frontmatter) = RainDocument::get_front_matter(dotrain) else {
+        return dotrain.to_string();
+    };
+    let offset = frontmatter_offset(dotrain, frontmatter);
+    format!(
+        "{}{}{}",
+        &dotrain[..offset],
+        write_token_metadata(frontmatter, tokens),
+        &dotrain[offset + frontmatter.len()..]
+    )
+}
+
+/// Byte offset of the frontmatter in the dotrain document
+fn frontmatter_offset(dotrain: &str, frontmatter: &str) -> usize {
+    let start = dotrain.as_ptr() as usize;
+    let frontmatter_start = frontmatter.as_ptr() as usize;
+    if frontmatter_start >= start && frontmatter_start + frontmatter.len() <= start + dotrain.len()
+    {
+        frontmatter_start - start
+    } else {
+        dotrain.find(frontmatter).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_frontmatter_config_error_span() {
+        let dotrain = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+tokens:
+    eth:
+        network: polygon
+        address: 0xabc0000000000000000000000000000000000003
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;"#;
+        let error = parse_frontmatter_config(dotrain, None).await.unwrap_err();
+        let span = error.span.unwrap();
+        assert_eq!(error.document.as_deref(), Some(FRONTMATTER_DOCUMENT));
+        assert_eq!(&dotrain[span.start..span.end], "eth");
+        assert_eq!(span.line, 6);
+        assert_eq!(span.column, 4);
+    }
+
+    #[tokio::test]
+    async fn test_parse_frontmatter_config_error_in_settings() {
+        let dotrain = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;"#;
+        let settings = r#"
+tokens:
+    eth:
+        network: polygon
+        address: 0xabc0000000000000000000000000000000000003
+"#;
+        let error = parse_frontmatter_config(dotrain, Some(settings.to_string()))
+            .await
+            .unwrap_err();
+        let span = error.span.unwrap();
+        assert_eq!(error.document.as_deref(), Some(SETTINGS_DOCUMENT));
+        assert_eq!(&settings[span.start..span.end], "eth");
+        assert_eq!(span.line, 2);
+    }
+
+    #[tokio::test]
+    async fn test_parse_frontmatter_config_offline() {
+        let server = httpmock::MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.any_request();
+            then.status(200).body("[]");
+        });
+        let dotrain = format!(
+            r#"
+imports:
+    - {}
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;"#,
+            server.url("/networks.yml")
+        );
+
+        assert!(parse_frontmatter_config(&dotrain, None)
+            .await
+            .unwrap()
+            .is_none());
+        mock.assert_hits(0);
+    }
+
+    #[tokio::test]
+    async fn test_parse_frontmatter_config_unset_variable() {
+        let dotrain = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node/${FRONTMATTER_CONFIG_UNSET_API_KEY}
+        chain-id: 1
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;"#;
+
+        assert!(parse_frontmatter_config(dotrain, None)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_write_frontmatter_token_metadata() {
+        let dotrain = r#"
+tokens:
+    eth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000003
+---
+#calculate-io
+_ _: 0 0;"#;
+        let tokens = HashMap::from([(
+            "eth".to_string(),
+            TokenConfigSource {
+                network: "mainnet".to_string(),
+                address: "0xabc0000000000000000000000000000000000003"
+                    .parse()
+                    .unwrap(),
+                decimals: Some(18),
+                label: None,
+                symbol: Some("ETH".to_string()),
+            },
+        )]);
+
+        assert_eq!(
+            write_frontmatter_token_metadata(dotrain, &tokens),
+            r#"
+tokens:
+    eth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000003
+        decimals: 18
+        symbol: ETH
+---
+#calculate-io
+_ _: 0 0;"#
+        );
+    }
 }
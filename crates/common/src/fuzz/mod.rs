@@ -17,6 +17,7 @@ use rain_interpreter_eval::{
 use rain_orderbook_app_settings::blocks::BlockError;
 use rain_orderbook_app_settings::chart::Chart;
 use rain_orderbook_app_settings::config::*;
+use rain_orderbook_app_settings::rpc::with_rpc_failover;
 use rain_orderbook_app_settings::scenario::Scenario;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -125,23 +126,30 @@ impl FuzzRunner {
 
         let deployer = scenario.deployer.clone();
 
-        // Fetch the latest block number
-        let block_number = ReadableClientHttp::new_from_url(deployer.network.rpc.to_string())?
-            .get_block_number()
+        // Fetch the latest block number from the first rpc that responds
+        let (rpc, block_number) =
+            with_rpc_failover(&deployer.network.rpc_urls(), |rpc| async move {
+                let block_number = ReadableClientHttp::new_from_url(rpc.clone())?
+                    .get_block_number()
+                    .await?;
+                Ok::<_, ReadableClientError>((rpc, block_number))
+            })
             .await?;
 
-        let blocks = scenario
-            .blocks
-            .as_ref()
-            .map_or(Ok(vec![block_number]), |b| {
-                b.expand_to_block_numbers(block_number)
-            })?;
+        let blocks = match &scenario.blocks {
+            Some(blocks) => {
+                blocks
+                    .expand_to_block_numbers_with_rpc(block_number, &rpc)
+                    .await?
+            }
+            None => vec![block_number],
+        };
 
         // Create a fork with the first block number
         self.forker
             .add_or_select(
                 NewForkedEvm {
-                    fork_url: deployer.network.rpc.clone().into(),
+                    fork_url: rpc,
                     fork_block_number: Some(blocks[0]),
                 },
                 None,
@@ -391,6 +399,66 @@ _: block-number();
         });
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_time_based_block_range() {
+        let local_evm = LocalEvm::new().await;
+
+        // ten blocks a minute apart
+        local_evm
+            .provider
+            .anvil_mine(Some(U256::from(10)), Some(U256::from(60)))
+            .await
+            .unwrap();
+        let last_block_number = local_evm.provider.get_block_number().await.unwrap();
+
+        let dotrain = format!(
+            r#"
+deployers:
+    some-key:
+        address: {deployer}
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+scenarios:
+    some-key:
+        blocks:
+            range: [-5m..]
+            interval: 2m
+---
+#calculate-io
+_: block-number();
+#handle-io
+:;
+    "#,
+            rpc_url = local_evm.url(),
+            deployer = local_evm.deployer.address(),
+        );
+        let frontmatter = RainDocument::get_front_matter(&dotrain).unwrap();
+        let settings = serde_yaml::from_str::<ConfigSource>(frontmatter).unwrap();
+        let config = settings
+            .try_into()
+            .map_err(|e| println!("{:?}", e))
+            .unwrap();
+
+        let mut runner = FuzzRunner::new(&dotrain, config, None).await;
+
+        let res = runner
+            .run_scenario_by_name("some-key")
+            .await
+            .map_err(|e| println!("{:#?}", e))
+            .unwrap();
+
+        // the blocks at 5, 3 and 1 minutes before the last block
+        assert_eq!(res.runs.len(), 3);
+        res.runs.iter().enumerate().for_each(|(i, run)| {
+            assert_eq!(
+                run.traces[0].stack[0],
+                parse_ether(&(last_block_number - 5 + (i as u64 * 2)).to_string()).unwrap()
+            );
+        });
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
     async fn test_nested_flattened_fuzz() {
         let local_evm = LocalEvm::new().await;
@@ -1,4 +1,7 @@
 pub mod add_order;
+pub mod add_order_and_deposit;
+pub mod arb;
+pub mod clear;
 pub mod csv;
 pub mod deposit;
 pub mod dotrain_add_order_lsp;
@@ -11,12 +14,19 @@ pub mod rainlang;
 pub mod remove_order;
 #[cfg(not(target_family = "wasm"))]
 pub mod replays;
+#[cfg(not(target_family = "wasm"))]
+pub mod simulation;
 pub mod subgraph;
+pub mod take_orders;
+pub mod token_metadata;
 pub mod transaction;
 pub mod types;
 #[cfg(not(target_family = "wasm"))]
 pub mod unit_tests;
+pub mod update_order;
 pub mod utils;
+pub mod vault_batch;
+pub mod vault_id;
 pub mod withdraw;
 
 #[cfg(target_family = "wasm")]
@@ -1,11 +1,15 @@
 #[cfg(not(target_family = "wasm"))]
+use crate::simulation::{simulate, SimulationCall, SimulationError, SimulationReport};
+#[cfg(not(target_family = "wasm"))]
 use crate::transaction::TransactionArgs;
 use crate::transaction::TransactionArgsError;
 use alloy::primitives::hex::FromHexError;
+#[cfg(not(target_family = "wasm"))]
+use alloy::primitives::Address;
 use alloy::sol_types::SolCall;
 use alloy_ethers_typecast::transaction::WritableClientError;
 #[cfg(not(target_family = "wasm"))]
-use alloy_ethers_typecast::transaction::{WriteTransaction, WriteTransactionStatus};
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
 use rain_orderbook_bindings::IOrderBookV4::removeOrder2Call;
 use rain_orderbook_subgraph_client::types::{common::Order, order_detail_traits::OrderDetailError};
 use serde::{Deserialize, Serialize};
@@ -21,6 +25,9 @@ pub enum RemoveOrderArgsError {
     FromHexError(#[from] FromHexError),
     #[error(transparent)]
     OrderDetailError(#[from] OrderDetailError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -52,7 +59,7 @@ impl RemoveOrderArgs {
         transaction_args: TransactionArgs,
         transaction_status_changed: S,
     ) -> Result<(), RemoveOrderArgsError> {
-        let ledger_client = transaction_args.clone().try_into_ledger_client().await?;
+        let signer = transaction_args.clone().try_into_signer().await?;
 
         let remove_order_call: removeOrder2Call = self.try_into()?;
         let params = transaction_args
@@ -62,13 +69,40 @@ impl RemoveOrderArgs {
             )
             .await?;
 
-        WriteTransaction::new(ledger_client.client, params, 4, transaction_status_changed)
-            .execute()
-            .await?;
+        signer.execute(params, transaction_status_changed).await?;
 
         Ok(())
     }
 
+    /// Simulate removing the order on a fork of the network, sent from the
+    /// order owner if no sender is given
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, RemoveOrderArgsError> {
+        let remove_order_call: removeOrder2Call = self.try_into()?;
+        let from = from.unwrap_or(remove_order_call.order.owner);
+        let mut tokens: Vec<Address> = vec![];
+        for io in remove_order_call
+            .order
+            .validInputs
+            .iter()
+            .chain(remove_order_call.order.validOutputs.iter())
+        {
+            if !tokens.contains(&io.token) {
+                tokens.push(io.token);
+            }
+        }
+        let calls = vec![SimulationCall::new(
+            from,
+            transaction_args.orderbook_address,
+            remove_order_call,
+        )];
+        Ok(simulate(&transaction_args, calls, &tokens, &[from]).await?)
+    }
+
     pub async fn get_rm_order_calldata(self) -> Result<Vec<u8>, RemoveOrderArgsError> {
         let remove_order_call: removeOrder2Call = self.try_into()?;
         Ok(remove_order_call.abi_encode())
@@ -142,7 +142,7 @@ amount price: 2 1;
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
@@ -0,0 +1,342 @@
+use crate::transaction::{TransactionArgs, TransactionArgsError};
+use alloy::primitives::{hex::encode_prefixed, Address, I256, U256};
+use alloy::sol_types::SolCall;
+use alloy_ethers_typecast::transaction::ReadableClientError;
+use rain_error_decoding::AbiDecodedErrorType;
+use rain_interpreter_eval::{
+    error::ForkCallError,
+    fork::{Forker, NewForkedEvm},
+    trace::{RainEvalResult, RainSourceTrace},
+};
+use rain_orderbook_app_settings::rpc::{with_rpc_failover, RpcRequestError};
+use rain_orderbook_bindings::IERC20::balanceOfCall;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SimulationError {
+    #[error(transparent)]
+    ForkCallError(#[from] ForkCallError),
+    #[error(transparent)]
+    TransactionArgs(#[from] TransactionArgsError),
+}
+
+impl RpcRequestError for SimulationError {
+    fn is_rpc_failure(&self) -> bool {
+        // fork calls fail on the rpc when forking or reading state
+        matches!(self, SimulationError::ForkCallError(_))
+    }
+
+    fn no_rpc_urls() -> Self {
+        TransactionArgsError::ReadableClient(ReadableClientError::no_rpc_urls()).into()
+    }
+}
+
+/// A call to run on the fork
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationCall {
+    pub from: Address,
+    pub to: Address,
+    pub calldata: Vec<u8>,
+}
+
+impl SimulationCall {
+    pub fn new<C: SolCall>(from: Address, to: Address, call: C) -> Self {
+        Self {
+            from,
+            to,
+            calldata: call.abi_encode(),
+        }
+    }
+}
+
+/// Token balance of an account before and after a simulation
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceDelta {
+    pub token: Address,
+    pub account: Address,
+    pub before: U256,
+    pub after: U256,
+}
+
+impl BalanceDelta {
+    pub fn change(&self) -> I256 {
+        I256::from_raw(self.after).wrapping_sub(I256::from_raw(self.before))
+    }
+}
+
+/// Outcome of simulating a transaction on a fork
+#[derive(Debug)]
+pub struct SimulationReport {
+    pub reverted: bool,
+    /// Revert reason decoded with the selector registry, or the raw revert
+    /// data if it could not be decoded
+    pub revert_reason: Option<String>,
+    pub gas_used: u64,
+    pub balance_deltas: Vec<BalanceDelta>,
+    /// Traces of the rainlang sources evaluated during the calls
+    pub traces: Vec<RainSourceTrace>,
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.revert_reason {
+            Some(reason) if self.reverted => writeln!(f, "Reverted: {}", reason)?,
+            _ if self.reverted => writeln!(f, "Reverted")?,
+            _ => writeln!(f, "Succeeded")?,
+        }
+        writeln!(f, "Gas used: {}", self.gas_used)?;
+        for delta in &self.balance_deltas {
+            writeln!(
+                f,
+                "Balance of {} in token {}: {} -> {} ({})",
+                delta.account,
+                delta.token,
+                delta.before,
+                delta.after,
+                delta.change()
+            )?;
+        }
+        for trace in &self.traces {
+            writeln!(
+                f,
+                "Trace {}.{}: {:?}",
+                trace.parent_source_index, trace.source_index, trace.stack
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Sender of a simulation, the given address or the signer's one
+pub async fn resolve_sender(
+    transaction_args: &TransactionArgs,
+    from: Option<Address>,
+) -> Result<Address, SimulationError> {
+    match from {
+        Some(from) => Ok(from),
+        None => Ok(transaction_args.clone().try_into_signer().await?.address()),
+    }
+}
+
+/// Fork the transaction args' network at its latest block from the first of
+/// its rpc urls that responds
+pub async fn new_fork(transaction_args: &TransactionArgs) -> Result<Forker, SimulationError> {
+    with_rpc_failover(&transaction_args.rpc_urls(), |fork_url| async move {
+        Ok(Forker::new_with_fork(
+            NewForkedEvm {
+                fork_url,
+                fork_block_number: None,
+            },
+            None,
+            None,
+        )
+        .await?)
+    })
+    .await
+}
+
+/// Run calls in order on a fork of the transaction args' network, stopping at
+/// the first revert, and report the balances of the given tokens for the
+/// given accounts before and after
+pub async fn simulate(
+    transaction_args: &TransactionArgs,
+    calls: Vec<SimulationCall>,
+    tokens: &[Address],
+    accounts: &[Address],
+) -> Result<SimulationReport, SimulationError> {
+    let mut forker = new_fork(transaction_args).await?;
+
+    let before = fork_balances(&forker, tokens, accounts).await?;
+
+    let mut report = SimulationReport {
+        reverted: false,
+        revert_reason: None,
+        gas_used: 0,
+        balance_deltas: vec![],
+        traces: vec![],
+    };
+    for call in calls {
+        let res = forker.call_committing(
+            call.from.as_slice(),
+            call.to.as_slice(),
+            &call.calldata,
+            U256::ZERO,
+        )?;
+        report.gas_used += res.gas_used;
+        if res.exit_reason.is_revert() {
+            report.reverted = true;
+            report.revert_reason = Some(revert_reason(&res.result).await);
+            break;
+        }
+        let eval_result: RainEvalResult = res.into();
+        report.traces.extend(eval_result.traces);
+    }
+
+    let after = fork_balances(&forker, tokens, accounts).await?;
+    report.balance_deltas = before
+        .into_iter()
+        .zip(after)
+        .map(|((token, account, before), (_, _, after))| BalanceDelta {
+            token,
+            account,
+            before,
+            after,
+        })
+        .collect();
+
+    Ok(report)
+}
+
+/// Run calls in order on a fork of the transaction args' network, carrying
+/// on past reverts, and return the revert reason of each call, None for the
+/// calls that went through
+pub async fn simulate_each(
+    transaction_args: &TransactionArgs,
+    calls: Vec<SimulationCall>,
+) -> Result<Vec<Option<String>>, SimulationError> {
+    let mut forker = new_fork(transaction_args).await?;
+
+    let mut reasons = vec![];
+    for call in calls {
+        let res = forker.call_committing(
+            call.from.as_slice(),
+            call.to.as_slice(),
+            &call.calldata,
+            U256::ZERO,
+        )?;
+        reasons.push(if res.exit_reason.is_revert() {
+            Some(revert_reason(&res.result).await)
+        } else {
+            None
+        });
+    }
+    Ok(reasons)
+}
+
+/// Revert data decoded with the selector registry, or the raw revert data if
+/// it could not be decoded
+async fn revert_reason(data: &[u8]) -> String {
+    match AbiDecodedErrorType::selector_registry_abi_decode(data).await {
+        Ok(decoded) => decoded.to_string(),
+        Err(_) => encode_prefixed(data),
+    }
+}
+
+async fn fork_balances(
+    forker: &Forker,
+    tokens: &[Address],
+    accounts: &[Address],
+) -> Result<Vec<(Address, Address, U256)>, SimulationError> {
+    let mut balances = vec![];
+    for token in tokens {
+        for account in accounts {
+            let balance = forker
+                .alloy_call(*account, *token, balanceOfCall { account: *account }, true)
+                .await?
+                .typed_return
+                ._0;
+            balances.push((*token, *account, balance));
+        }
+    }
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rain_orderbook_bindings::{IOrderBookV4::deposit2Call, IERC20::approveCall};
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    #[test]
+    fn test_balance_delta_change() {
+        let delta = BalanceDelta {
+            token: Address::ZERO,
+            account: Address::ZERO,
+            before: U256::from(10),
+            after: U256::from(4),
+        };
+        assert_eq!(delta.change(), I256::try_from(-6).unwrap());
+    }
+
+    #[test]
+    fn test_report_display() {
+        let report = SimulationReport {
+            reverted: true,
+            revert_reason: Some("NotOrderOwner".to_string()),
+            gas_used: 21000,
+            balance_deltas: vec![],
+            traces: vec![],
+        };
+        assert_eq!(
+            report.to_string(),
+            "Reverted: NotOrderOwner\nGas used: 21000\n"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_simulate_local_evm() {
+        let local_evm = LocalEvm::new_with_tokens(1).await;
+        let owner = local_evm.anvil.addresses()[0];
+        let token = *local_evm.tokens[0].address();
+        let orderbook = *local_evm.orderbook.address();
+        let transaction_args = TransactionArgs {
+            orderbook_address: orderbook,
+            rpc_url: local_evm.url(),
+            ..Default::default()
+        };
+        let amount = U256::from(10);
+        let approve = SimulationCall::new(
+            owner,
+            token,
+            approveCall {
+                spender: orderbook,
+                amount,
+            },
+        );
+        let deposit = SimulationCall::new(
+            owner,
+            orderbook,
+            deposit2Call {
+                token,
+                vaultId: U256::from(1),
+                amount,
+                tasks: vec![],
+            },
+        );
+
+        let report = simulate(
+            &transaction_args,
+            vec![approve.clone(), deposit.clone()],
+            &[token],
+            &[owner, orderbook],
+        )
+        .await
+        .unwrap();
+        assert!(!report.reverted);
+        assert!(report.gas_used > 0);
+        assert_eq!(report.balance_deltas.len(), 2);
+        assert_eq!(report.balance_deltas[0].account, owner);
+        assert_eq!(
+            report.balance_deltas[0].change(),
+            I256::try_from(-10).unwrap()
+        );
+        assert_eq!(report.balance_deltas[1].account, orderbook);
+        assert_eq!(report.balance_deltas[1].before, U256::ZERO);
+        assert_eq!(report.balance_deltas[1].after, amount);
+
+        // the simulation is not committed to the chain
+        let report = simulate(&transaction_args, vec![deposit.clone()], &[token], &[owner])
+            .await
+            .unwrap();
+        assert!(report.reverted);
+        assert!(report.revert_reason.is_some());
+        assert_eq!(report.balance_deltas[0].change(), I256::ZERO);
+
+        let reasons = simulate_each(&transaction_args, vec![deposit.clone(), approve, deposit])
+            .await
+            .unwrap();
+        assert!(reasons[0].is_some());
+        assert_eq!(reasons[1..], [None, None]);
+    }
+}
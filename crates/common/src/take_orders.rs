@@ -0,0 +1,577 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::simulation::{
+    resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport,
+};
+use crate::{
+    transaction::{TransactionArgs, TransactionArgsError},
+    utils::fixed_point::{fixed_point_mul_up, scale_18, scale_n},
+};
+use alloy::primitives::{hex::FromHexError, Address, U256};
+use alloy::sol_types::SolCall;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+use alloy_ethers_typecast::transaction::{
+    ReadContractParametersBuilder, ReadContractParametersBuilderError, ReadableClient,
+    ReadableClientError, WritableClientError,
+};
+use rain_orderbook_bindings::{
+    IOrderBookV4::{takeOrders2Call, OrderV3, Quote, TakeOrderConfigV3, TakeOrdersConfigV3},
+    IERC20::{allowanceCall, approveCall},
+};
+use rain_orderbook_quote::{
+    error::Error as QuoteError, BatchQuoteTarget, OrderQuoteValue, QuoteTarget,
+};
+use rain_orderbook_subgraph_client::types::{common::Order, order_detail_traits::OrderDetailError};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TakeOrdersArgsError {
+    #[error(transparent)]
+    ReadableClientError(#[from] ReadableClientError),
+    #[error(transparent)]
+    ReadContractParametersBuilderError(#[from] ReadContractParametersBuilderError),
+    #[error(transparent)]
+    WritableClientError(#[from] WritableClientError),
+    #[error(transparent)]
+    TransactionArgs(#[from] TransactionArgsError),
+    #[error(transparent)]
+    FromHexError(#[from] FromHexError),
+    #[error(transparent)]
+    OrderDetailError(#[from] OrderDetailError),
+    #[error(transparent)]
+    QuoteError(#[from] QuoteError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+    #[error("No order on the orderbook can be taken for the given token pair and io ratio")]
+    NoTakeableOrders,
+}
+
+/// Arguments to take orders from the orderbook with takeOrders2.
+/// Minimum and maximum input are in the input token's decimals and the
+/// maximum io ratio is an 18 decimals fixed point value, same as the
+/// orderbook expects them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TakeOrdersArgs {
+    pub orders: Vec<Order>,
+    /// Token the taker receives, ie the token the orders output
+    pub input_token: Address,
+    /// Token the taker pays, ie the token the orders input
+    pub output_token: Address,
+    pub minimum_input: U256,
+    pub maximum_input: U256,
+    pub maximum_io_ratio: U256,
+}
+
+/// A takeable order together with its quote
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotedTakeOrder {
+    pub config: TakeOrderConfigV3,
+    pub quote: OrderQuoteValue,
+}
+
+impl TakeOrdersArgs {
+    /// Build the take order configs of the orders that are active on the given
+    /// orderbook and trade this args' token pair
+    pub fn get_candidates(
+        &self,
+        orderbook: Address,
+    ) -> Result<Vec<TakeOrderConfigV3>, TakeOrdersArgsError> {
+        let mut candidates = vec![];
+        for order in &self.orders {
+            if !order.active || Address::from_str(&order.orderbook.id.0)? != orderbook {
+                continue;
+            }
+            let order_v3: OrderV3 = order.clone().try_into()?;
+            let input_index = order_v3
+                .validInputs
+                .iter()
+                .position(|io| io.token == self.output_token);
+            let output_index = order_v3
+                .validOutputs
+                .iter()
+                .position(|io| io.token == self.input_token);
+            if let (Some(input_index), Some(output_index)) = (input_index, output_index) {
+                candidates.push(TakeOrderConfigV3 {
+                    order: order_v3,
+                    inputIOIndex: U256::from(input_index),
+                    outputIOIndex: U256::from(output_index),
+                    signedContext: vec![],
+                });
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Quote the candidate orders and keep those that can be taken within the
+    /// maximum io ratio, sorted from the best (lowest) io ratio to the worst
+    pub async fn quote_candidates(
+        &self,
+        rpc_url: &str,
+        orderbook: Address,
+    ) -> Result<Vec<QuotedTakeOrder>, TakeOrdersArgsError> {
+        let candidates = self.get_candidates(orderbook)?;
+        let quote_targets = candidates
+            .iter()
+            .map(|config| QuoteTarget {
+                orderbook,
+                quote_config: Quote {
+                    order: config.order.clone(),
+                    inputIOIndex: config.inputIOIndex,
+                    outputIOIndex: config.outputIOIndex,
+                    signedContext: config.signedContext.clone(),
+                },
+            })
+            .collect();
+        let quotes = BatchQuoteTarget(quote_targets)
+            .do_quote(rpc_url, None, None)
+            .await?;
+
+        let mut quoted: Vec<QuotedTakeOrder> = candidates
+            .into_iter()
+            .zip(quotes)
+            .filter_map(|(config, quote)| {
+                let quote = quote.ok()?;
+                (quote.max_output > U256::ZERO && quote.ratio <= self.maximum_io_ratio)
+                    .then_some(QuotedTakeOrder { config, quote })
+            })
+            .collect();
+        quoted.sort_by(|a, b| a.quote.ratio.cmp(&b.quote.ratio));
+
+        Ok(quoted)
+    }
+
+    /// Build the takeOrders2 call from the quoted orders
+    pub fn try_into_call(
+        &self,
+        quoted: &[QuotedTakeOrder],
+    ) -> Result<takeOrders2Call, TakeOrdersArgsError> {
+        if quoted.is_empty() {
+            return Err(TakeOrdersArgsError::NoTakeableOrders);
+        }
+        Ok(takeOrders2Call {
+            config: TakeOrdersConfigV3 {
+                minimumInput: self.minimum_input,
+                maximumInput: self.maximum_input,
+                maximumIORatio: self.maximum_io_ratio,
+                orders: quoted.iter().map(|v| v.config.clone()).collect(),
+                data: vec![].into(),
+            },
+        })
+    }
+
+    /// Calculates the amount of output token the taker needs to approve for the
+    /// orderbook to spend, in the output token's decimals, by filling the quoted
+    /// orders in sequence until the maximum input is reached
+    pub fn required_allowance(&self, quoted: &[QuotedTakeOrder]) -> U256 {
+        let Some(first) = quoted.first() else {
+            return U256::ZERO;
+        };
+        // all quoted orders trade the same tokens, so any of them has their decimals
+        let order = &first.config.order;
+        let input_decimals = order.validOutputs[first.config.outputIOIndex.to::<usize>()].decimals;
+        let output_decimals = order.validInputs[first.config.inputIOIndex.to::<usize>()].decimals;
+
+        let mut remaining_18 = scale_18(self.maximum_input, input_decimals);
+        let mut spend_18 = U256::ZERO;
+        for quoted_order in quoted {
+            if remaining_18.is_zero() {
+                break;
+            }
+            let taken_18 = remaining_18.min(quoted_order.quote.max_output);
+            spend_18 =
+                spend_18.saturating_add(fixed_point_mul_up(taken_18, quoted_order.quote.ratio));
+            remaining_18 -= taken_18;
+        }
+
+        scale_n(spend_18, output_decimals, true)
+    }
+
+    /// Execute read IERC20 allowance call of the output token
+    pub async fn read_allowance(
+        &self,
+        owner: Address,
+        transaction_args: TransactionArgs,
+    ) -> Result<U256, TakeOrdersArgsError> {
+        let readable_client = ReadableClient::new_from_url(transaction_args.rpc_url.clone())?;
+        let parameters = ReadContractParametersBuilder::<allowanceCall>::default()
+            .address(self.output_token)
+            .call(allowanceCall {
+                owner,
+                spender: transaction_args.orderbook_address,
+            })
+            .build()?;
+        let res = readable_client.read(parameters).await?;
+
+        Ok(res._0)
+    }
+
+    /// Execute IERC20 approve call of the output token for the orderbook
+    /// if the current allowance is not enough for the quoted orders
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute_approve<S: Fn(WriteTransactionStatus<approveCall>)>(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), TakeOrdersArgsError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+        let current_allowance = self
+            .read_allowance(signer.address(), transaction_args.clone())
+            .await?;
+
+        let required_allowance = self.required_allowance(quoted);
+        if current_allowance < required_allowance {
+            let approve_call = approveCall {
+                spender: transaction_args.orderbook_address,
+                amount: required_allowance,
+            };
+            let params = transaction_args
+                .try_into_write_contract_parameters(approve_call, self.output_token)
+                .await?;
+
+            signer.execute(params, transaction_status_changed).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_approve_calldata(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+    ) -> Vec<u8> {
+        approveCall {
+            spender: transaction_args.orderbook_address,
+            amount: self.required_allowance(quoted),
+        }
+        .abi_encode()
+    }
+
+    /// Execute OrderbookV4 takeOrders2 call
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute<S: Fn(WriteTransactionStatus<takeOrders2Call>)>(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), TakeOrdersArgsError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+
+        let take_orders_call = self.try_into_call(quoted)?;
+        let params = transaction_args
+            .try_into_write_contract_parameters(
+                take_orders_call,
+                transaction_args.orderbook_address,
+            )
+            .await?;
+
+        signer.execute(params, transaction_status_changed).await?;
+
+        Ok(())
+    }
+
+    /// Simulate approving the output token and taking the quoted orders on a
+    /// fork of the network and report the outcome, including the sender's
+    /// balances of the pair's tokens and the traces of the taken orders
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        quoted: &[QuotedTakeOrder],
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, TakeOrdersArgsError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let orderbook = transaction_args.orderbook_address;
+        let calls = vec![
+            SimulationCall::new(
+                from,
+                self.output_token,
+                approveCall {
+                    spender: orderbook,
+                    amount: self.required_allowance(quoted),
+                },
+            ),
+            SimulationCall::new(from, orderbook, self.try_into_call(quoted)?),
+        ];
+        let tokens = [self.input_token, self.output_token];
+        Ok(simulate(&transaction_args, calls, &tokens, &[from]).await?)
+    }
+
+    pub fn get_take_orders_calldata(
+        &self,
+        quoted: &[QuotedTakeOrder],
+    ) -> Result<Vec<u8>, TakeOrdersArgsError> {
+        Ok(self.try_into_call(quoted)?.abi_encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{add_order::AddOrderArgs, dotrain_order::DotrainOrder, transaction::SignerSource};
+    use alloy::primitives::{hex::encode_prefixed, utils::parse_ether, B256};
+    use alloy::sol_types::SolValue;
+    use rain_orderbook_bindings::IOrderBookV4::IO;
+    use rain_orderbook_subgraph_client::types::common::{BigInt, Bytes, Orderbook};
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    fn get_order(order: &OrderV3, orderbook: Address, active: bool) -> Order {
+        Order {
+            id: Bytes(B256::random().to_string()),
+            order_bytes: Bytes(encode_prefixed(order.abi_encode())),
+            order_hash: Bytes(B256::random().to_string()),
+            owner: Bytes(order.owner.to_string()),
+            outputs: vec![],
+            inputs: vec![],
+            orderbook: Orderbook {
+                id: Bytes(orderbook.to_string()),
+            },
+            active,
+            timestamp_added: BigInt("0".to_string()),
+            meta: None,
+            add_events: vec![],
+            trades: vec![],
+        }
+    }
+
+    fn get_io(token: Address, decimals: u8) -> IO {
+        IO {
+            token,
+            decimals,
+            vaultId: U256::from(1),
+        }
+    }
+
+    #[test]
+    fn test_get_candidates() {
+        let orderbook = Address::random();
+        let token1 = Address::random();
+        let token2 = Address::random();
+        let token3 = Address::random();
+
+        // sells token1 for token2, at second io indexes
+        let order1 = OrderV3 {
+            validInputs: vec![get_io(token3, 18), get_io(token2, 18)],
+            validOutputs: vec![get_io(token3, 18), get_io(token1, 18)],
+            ..Default::default()
+        };
+        // sells token2 for token1, ie opposite direction
+        let order2 = OrderV3 {
+            validInputs: vec![get_io(token1, 18)],
+            validOutputs: vec![get_io(token2, 18)],
+            ..Default::default()
+        };
+
+        let args = TakeOrdersArgs {
+            orders: vec![
+                get_order(&order1, orderbook, true),
+                get_order(&order2, orderbook, true),
+                // inactive and other orderbook orders are skipped
+                get_order(&order1, orderbook, false),
+                get_order(&order1, Address::random(), true),
+            ],
+            input_token: token1,
+            output_token: token2,
+            minimum_input: U256::ZERO,
+            maximum_input: U256::MAX,
+            maximum_io_ratio: U256::MAX,
+        };
+
+        let result = args.get_candidates(orderbook).unwrap();
+        let expected = vec![TakeOrderConfigV3 {
+            order: order1,
+            inputIOIndex: U256::from(1),
+            outputIOIndex: U256::from(1),
+            signedContext: vec![],
+        }];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_try_into_call_no_orders() {
+        let args = TakeOrdersArgs {
+            orders: vec![],
+            input_token: Address::random(),
+            output_token: Address::random(),
+            minimum_input: U256::ZERO,
+            maximum_input: U256::MAX,
+            maximum_io_ratio: U256::MAX,
+        };
+        assert!(matches!(
+            args.try_into_call(&[]),
+            Err(TakeOrdersArgsError::NoTakeableOrders)
+        ));
+    }
+
+    #[test]
+    fn test_required_allowance() {
+        let token1 = Address::random();
+        let token2 = Address::random();
+        let quoted = |max_output: &str, ratio: &str| QuotedTakeOrder {
+            config: TakeOrderConfigV3 {
+                order: OrderV3 {
+                    validInputs: vec![get_io(token2, 6)],
+                    validOutputs: vec![get_io(token1, 18)],
+                    ..Default::default()
+                },
+                inputIOIndex: U256::ZERO,
+                outputIOIndex: U256::ZERO,
+                signedContext: vec![],
+            },
+            quote: OrderQuoteValue {
+                max_output: parse_ether(max_output).unwrap(),
+                ratio: parse_ether(ratio).unwrap(),
+            },
+        };
+        let args = TakeOrdersArgs {
+            orders: vec![],
+            input_token: token1,
+            output_token: token2,
+            minimum_input: U256::ZERO,
+            maximum_input: parse_ether("15").unwrap(),
+            maximum_io_ratio: U256::MAX,
+        };
+
+        // 10 at ratio 2 + 5 at ratio 3 = 35, in 6 decimals
+        let result = args.required_allowance(&[quoted("10", "2"), quoted("100", "3")]);
+        assert_eq!(result, U256::from(35_000_000));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_take_orders_execute() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let orderbook = &local_evm.orderbook;
+        let owner = local_evm.anvil.addresses()[0];
+        let token1 = local_evm.tokens[0].clone();
+        let token2 = local_evm.tokens[1].clone();
+
+        let dotrain = format!(
+            r#"
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+        network-id: 123
+        currency: ETH
+deployers:
+    some-key:
+        address: {deployer}
+tokens:
+    t1:
+        network: some-key
+        address: {token1}
+        decimals: 18
+        label: Token1
+        symbol: Token1
+    t2:
+        network: some-key
+        address: {token2}
+        decimals: 18
+        label: Token2
+        symbol: Token2
+orderbook:
+    some-key:
+        address: {orderbook}
+orders:
+    some-key:
+        inputs:
+            - token: t2
+        outputs:
+            - token: t1
+              vault-id: 0x01
+scenarios:
+    some-key:
+deployments:
+    some-key:
+        scenario: some-key
+        order: some-key
+---
+#calculate-io
+_ _: 100e18 2e18;
+#handle-add-order
+:;
+#handle-io
+:;
+"#,
+            rpc_url = local_evm.url(),
+            orderbook = orderbook.address(),
+            deployer = local_evm.deployer.address(),
+            token1 = token1.address(),
+            token2 = token2.address(),
+        );
+
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
+            .await
+            .unwrap()
+            .try_into_call(&[local_evm.url()])
+            .await
+            .unwrap()
+            .abi_encode();
+        let order = local_evm
+            .add_order_and_deposit(
+                &calldata,
+                owner,
+                *token1.address(),
+                parse_ether("1000").unwrap(),
+                U256::from(1),
+            )
+            .await
+            .0
+            .order;
+        let order = OrderV3::abi_decode(&order.abi_encode(), true).unwrap();
+
+        std::env::set_var(
+            "TEST_TAKE_ORDERS_PRIVATE_KEY",
+            encode_prefixed(local_evm.anvil.keys()[0].to_bytes()),
+        );
+        let tx_args = TransactionArgs {
+            orderbook_address: *orderbook.address(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            rpc_url: local_evm.url(),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_TAKE_ORDERS_PRIVATE_KEY".to_string(),
+            },
+            ..Default::default()
+        };
+        let args = TakeOrdersArgs {
+            orders: vec![get_order(&order, *orderbook.address(), true)],
+            input_token: *token1.address(),
+            output_token: *token2.address(),
+            minimum_input: parse_ether("1").unwrap(),
+            maximum_input: parse_ether("10").unwrap(),
+            maximum_io_ratio: parse_ether("3").unwrap(),
+        };
+
+        let quoted = args
+            .quote_candidates(&local_evm.url(), *orderbook.address())
+            .await
+            .unwrap();
+        assert_eq!(quoted.len(), 1);
+        assert_eq!(
+            quoted[0].quote,
+            OrderQuoteValue {
+                max_output: parse_ether("100").unwrap(),
+                ratio: parse_ether("2").unwrap(),
+            }
+        );
+        assert_eq!(args.required_allowance(&quoted), parse_ether("20").unwrap());
+
+        args.execute_approve(&quoted, tx_args.clone(), |_| {})
+            .await
+            .unwrap();
+        args.execute(&quoted, tx_args, |_| {}).await.unwrap();
+
+        let balance = orderbook
+            .vaultBalance(owner, *token1.address(), U256::from(1))
+            .call()
+            .await
+            .unwrap()
+            ._0;
+        assert_eq!(balance, parse_ether("990").unwrap());
+    }
+}
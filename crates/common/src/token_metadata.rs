@@ -0,0 +1,563 @@
+use alloy::primitives::{hex::FromHex, Address};
+use alloy::sol_types::SolCall;
+use alloy_ethers_typecast::{
+    multicall::{
+        IMulticall3::{aggregate3Call, Call3},
+        MULTICALL3_ADDRESS,
+    },
+    transaction::{ReadContractParameters, ReadableClientError, ReadableClientHttp},
+};
+use futures::future::try_join_all;
+use rain_orderbook_app_settings::{
+    config_source::{NetworkConfigSource, TokenConfigSource},
+    rpc::{with_rpc_failover, RpcRequestError},
+    token::Token,
+    token_metadata::{TokenMetadata, TokenMetadataCache},
+};
+use rain_orderbook_bindings::ERC20::{decimalsCall, nameCall, symbolCall};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum TokenMetadataError {
+    #[error(transparent)]
+    ReadableClientError(#[from] ReadableClientError),
+    #[error("Network {1} of token {0} not found")]
+    NetworkNotFound(String, String),
+    #[error("Multicall returned {1} results for {0} calls")]
+    ResultCountMismatch(usize, usize),
+}
+
+impl RpcRequestError for TokenMetadataError {
+    fn is_rpc_failure(&self) -> bool {
+        match self {
+            TokenMetadataError::ReadableClientError(e) => e.is_rpc_failure(),
+            _ => false,
+        }
+    }
+
+    fn no_rpc_urls() -> Self {
+        ReadableClientError::no_rpc_urls().into()
+    }
+}
+
+/// Multicall calls reading the decimals, symbol and name of each token
+fn token_metadata_calls(tokens: &[Address]) -> Vec<Call3> {
+    tokens
+        .iter()
+        .flat_map(|token| {
+            [
+                decimalsCall {}.abi_encode(),
+                symbolCall {}.abi_encode(),
+                nameCall {}.abi_encode(),
+            ]
+            .map(|call_data| Call3 {
+                allowFailure: true,
+                target: *token,
+                callData: call_data.into(),
+            })
+        })
+        .collect()
+}
+
+/// Read the decimals, symbol and name of tokens of one network with a single
+/// multicall, or with separate calls for each token if the multicall fails,
+/// eg on a chain without multicall3. Falls back on the network's other rpc
+/// urls.
+pub async fn fetch_token_metadata(
+    rpcs: &[String],
+    tokens: &[Address],
+) -> Result<Vec<TokenMetadata>, TokenMetadataError> {
+    match fetch_token_metadata_multicall(rpcs, tokens).await {
+        Ok(metadata) => Ok(metadata),
+        Err(e) => {
+            warn!("Token metadata multicall failed, reading each token: {}", e);
+            try_join_all(
+                tokens
+                    .iter()
+                    .map(|token| fetch_single_token_metadata(rpcs, *token)),
+            )
+            .await
+        }
+    }
+}
+
+async fn fetch_token_metadata_multicall(
+    rpcs: &[String],
+    tokens: &[Address],
+) -> Result<Vec<TokenMetadata>, TokenMetadataError> {
+    let results = with_rpc_failover(rpcs, |rpc| async move {
+        let client = ReadableClientHttp::new_from_url(rpc)?;
+        let parameters = ReadContractParameters {
+            address: Address::from_hex(MULTICALL3_ADDRESS).unwrap(),
+            call: aggregate3Call {
+                calls: token_metadata_calls(tokens),
+            },
+            block_number: None,
+        };
+        Ok::<_, TokenMetadataError>(client.read(parameters).await?.returnData)
+    })
+    .await?;
+    let count = tokens.len() * 3;
+    if results.len() != count {
+        return Err(TokenMetadataError::ResultCountMismatch(
+            count,
+            results.len(),
+        ));
+    }
+
+    // tokens that do not implement a call, or return something other than
+    // the standard types for it, eg bytes32 symbols, get a `None` for it
+    Ok(results
+        .chunks(3)
+        .map(|results| {
+            let data = |i: usize| {
+                let result = &results[i];
+                result.success.then_some(&result.returnData[..])
+            };
+            TokenMetadata {
+                decimals: data(0)
+                    .and_then(|v| decimalsCall::abi_decode_returns(v, true).ok())
+                    .map(|v| v._0),
+                symbol: data(1)
+                    .and_then(|v| symbolCall::abi_decode_returns(v, true).ok())
+                    .map(|v| v._0),
+                name: data(2)
+                    .and_then(|v| nameCall::abi_decode_returns(v, true).ok())
+                    .map(|v| v._0),
+            }
+        })
+        .collect())
+}
+
+/// Read the decimals, symbol and name of a token with separate calls. A
+/// token that does not implement decimals gets `None` for them, while rpc
+/// failures reading them are errors as they are needed to add orders. Any
+/// failure reading the symbol or name gives `None` for it.
+async fn fetch_single_token_metadata(
+    rpcs: &[String],
+    token: Address,
+) -> Result<TokenMetadata, TokenMetadataError> {
+    with_rpc_failover(rpcs, |rpc| async move {
+        let client = ReadableClientHttp::new_from_url(rpc)?;
+        let decimals = match client
+            .read(ReadContractParameters {
+                address: token,
+                call: decimalsCall {},
+                block_number: None,
+            })
+            .await
+        {
+            Ok(v) => Some(v._0),
+            Err(e) if e.is_rpc_failure() => return Err(TokenMetadataError::from(e)),
+            Err(_) => None,
+        };
+        let symbol = client
+            .read(ReadContractParameters {
+                address: token,
+                call: symbolCall {},
+                block_number: None,
+            })
+            .await
+            .ok()
+            .map(|v| v._0);
+        let name = client
+            .read(ReadContractParameters {
+                address: token,
+                call: nameCall {},
+                block_number: None,
+            })
+            .await
+            .ok()
+            .map(|v| v._0);
+        Ok(TokenMetadata {
+            decimals,
+            symbol,
+            name,
+        })
+    })
+    .await
+}
+
+/// Resolves missing token metadata from chain, with one multicall per
+/// network, caching the results if a cache is set
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadataResolver {
+    pub cache: Option<TokenMetadataCache>,
+}
+
+impl TokenMetadataResolver {
+    pub fn new(cache: Option<TokenMetadataCache>) -> Self {
+        TokenMetadataResolver { cache }
+    }
+
+    /// Metadata of tokens of one network by address, read from the cache
+    /// where possible
+    pub async fn resolve(
+        &self,
+        chain_id: u64,
+        rpcs: &[String],
+        tokens: &[Address],
+    ) -> Result<HashMap<Address, TokenMetadata>, TokenMetadataError> {
+        let mut resolved = HashMap::new();
+        let mut missing = vec![];
+        for token in tokens {
+            if resolved.contains_key(token) || missing.contains(token) {
+                continue;
+            }
+            match self.cache.as_ref().and_then(|v| v.get(chain_id, *token)) {
+                Some(metadata) => {
+                    resolved.insert(*token, metadata);
+                }
+                None => missing.push(*token),
+            }
+        }
+
+        if !missing.is_empty() {
+            let fetched = fetch_token_metadata(rpcs, &missing).await?;
+            for (token, metadata) in missing.into_iter().zip(fetched) {
+                // only fully read metadata is cached, so a value that failed
+                // to be read is tried again next time
+                if let (Some(cache), true) = (&self.cache, metadata.is_complete()) {
+                    // a cache that cannot be written only costs a refetch
+                    let _ = cache.put(chain_id, token, &metadata);
+                }
+                resolved.insert(token, metadata);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Fill in the missing decimals, symbols and labels of token sources,
+    /// returns the sorted names of the tokens that changed
+    pub async fn resolve_token_sources(
+        &self,
+        tokens: &mut HashMap<String, TokenConfigSource>,
+        networks: &HashMap<String, NetworkConfigSource>,
+    ) -> Result<Vec<String>, TokenMetadataError> {
+        let mut by_network: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, token) in tokens.iter() {
+            if token.is_missing_metadata() {
+                by_network
+                    .entry(token.network.clone())
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+
+        let mut changed = vec![];
+        for (network_name, names) in by_network {
+            let network = networks.get(&network_name).ok_or_else(|| {
+                TokenMetadataError::NetworkNotFound(names[0].clone(), network_name.clone())
+            })?;
+            let rpcs: Vec<String> = network
+                .rpc
+                .urls_by_priority()
+                .iter()
+                .map(|v| v.to_string())
+                .collect();
+            let addresses: Vec<Address> = names.iter().map(|v| tokens[v].address).collect();
+            let metadata = self.resolve(network.chain_id, &rpcs, &addresses).await?;
+
+            for name in names {
+                if let Some(token) = tokens.get_mut(&name) {
+                    if let Some(metadata) = metadata.get(&token.address) {
+                        if token.apply_metadata(metadata) {
+                            changed.push(name);
+                        }
+                    }
+                }
+            }
+        }
+        changed.sort();
+        Ok(changed)
+    }
+
+    /// Metadata of config tokens in the given order, with the values the
+    /// tokens already have kept and the label used as the name. Only tokens
+    /// missing a value are looked up.
+    pub async fn resolve_tokens(
+        &self,
+        tokens: &[&Token],
+    ) -> Result<Vec<TokenMetadata>, TokenMetadataError> {
+        let mut by_network: BTreeMap<&str, Vec<&Token>> = BTreeMap::new();
+        for token in tokens {
+            if token.decimals.is_none() || token.symbol.is_none() || token.label.is_none() {
+                by_network
+                    .entry(token.network.name.as_str())
+                    .or_default()
+                    .push(*token);
+            }
+        }
+
+        let mut resolved: HashMap<(&str, Address), TokenMetadata> = HashMap::new();
+        for (network_name, network_tokens) in by_network {
+            let network = &network_tokens[0].network;
+            let addresses: Vec<Address> = network_tokens.iter().map(|v| v.address).collect();
+            let metadata = self
+                .resolve(network.chain_id, &network.rpc_urls(), &addresses)
+                .await?;
+            resolved.extend(
+                metadata
+                    .into_iter()
+                    .map(|(address, v)| ((network_name, address), v)),
+            );
+        }
+
+        Ok(tokens
+            .iter()
+            .map(|token| {
+                let fetched = resolved
+                    .get(&(token.network.name.as_str(), token.address))
+                    .cloned()
+                    .unwrap_or_default();
+                TokenMetadata {
+                    decimals: token.decimals.or(fetched.decimals),
+                    symbol: token.symbol.clone().or(fetched.symbol),
+                    name: token.label.clone().or(fetched.name),
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::hex::encode_prefixed;
+    use alloy::sol_types::SolValue;
+    use alloy_ethers_typecast::multicall::IMulticall3::Result as MulticallResult;
+    use alloy_ethers_typecast::rpc::Response;
+    use httpmock::{Method::POST, MockServer};
+    use rain_orderbook_app_settings::config_source::RpcConfigSource;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    /// Multicall response for tokens that have the given name, a `None`
+    /// name fails to be read, eg a bytes32 name
+    fn metadata_response(tokens: usize, name: Option<&str>) -> String {
+        let results: Vec<MulticallResult> = (0..tokens)
+            .flat_map(|_| {
+                [
+                    MulticallResult {
+                        success: true,
+                        returnData: decimalsCall::abi_encode_returns(&(6,)).into(),
+                    },
+                    MulticallResult {
+                        success: true,
+                        returnData: symbolCall::abi_encode_returns(&("USDC".to_string(),)).into(),
+                    },
+                    MulticallResult {
+                        success: name.is_some(),
+                        returnData: name
+                            .map(|v| nameCall::abi_encode_returns(&(v.to_string(),)))
+                            .unwrap_or_default()
+                            .into(),
+                    },
+                ]
+            })
+            .collect();
+        Response::new_success(1, encode_prefixed(results.abi_encode()).as_str())
+            .to_json_string()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_resolve_token_sources() {
+        let rpc_server = MockServer::start_async().await;
+        let mut mock = rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/rpc")
+                .json_body_partial(json!({"method": "eth_call"}).to_string());
+            then.body(metadata_response(2, None));
+        });
+        let dir = TempDir::new().unwrap();
+
+        let networks = HashMap::from([(
+            "mainnet".to_string(),
+            NetworkConfigSource {
+                rpc: RpcConfigSource::Single(rpc_server.url("/rpc").parse().unwrap()),
+                chain_id: 1,
+                label: None,
+                network_id: None,
+                currency: None,
+            },
+        )]);
+        let token = |address: Address, label: Option<&str>| TokenConfigSource {
+            network: "mainnet".to_string(),
+            address,
+            decimals: None,
+            label: label.map(|v| v.to_string()),
+            symbol: None,
+        };
+        let mut tokens = HashMap::from([
+            ("usdc".to_string(), token(Address::repeat_byte(0x01), None)),
+            (
+                "other".to_string(),
+                token(Address::repeat_byte(0x02), Some("Other")),
+            ),
+        ]);
+
+        let resolver = TokenMetadataResolver::new(Some(TokenMetadataCache::new(
+            Some(dir.path().to_path_buf()),
+            TokenMetadataCache::DEFAULT_TTL,
+        )));
+        let changed = resolver
+            .resolve_token_sources(&mut tokens, &networks)
+            .await
+            .unwrap();
+        assert_eq!(changed, vec!["other".to_string(), "usdc".to_string()]);
+        assert_eq!(tokens["usdc"].decimals, Some(6));
+        assert_eq!(tokens["usdc"].symbol, Some("USDC".to_string()));
+        assert_eq!(tokens["usdc"].label, None);
+        assert_eq!(tokens["other"].label, Some("Other".to_string()));
+        mock.assert_hits(1);
+
+        // metadata missing a value is not cached
+        let mut tokens =
+            HashMap::from([("usdc".to_string(), token(Address::repeat_byte(0x01), None))]);
+        resolver
+            .resolve_token_sources(&mut tokens, &networks)
+            .await
+            .unwrap();
+        mock.assert_hits(2);
+        mock.delete();
+
+        let mut mock = rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/rpc")
+                .json_body_partial(json!({"method": "eth_call"}).to_string());
+            then.body(metadata_response(1, Some("USD Coin")));
+        });
+        let mut tokens =
+            HashMap::from([("usdc".to_string(), token(Address::repeat_byte(0x01), None))]);
+        resolver
+            .resolve_token_sources(&mut tokens, &networks)
+            .await
+            .unwrap();
+        assert_eq!(tokens["usdc"].label, Some("USD Coin".to_string()));
+        mock.assert_hits(1);
+
+        // served from the cache
+        mock.delete();
+        let mut tokens =
+            HashMap::from([("usdc".to_string(), token(Address::repeat_byte(0x01), None))]);
+        resolver
+            .resolve_token_sources(&mut tokens, &networks)
+            .await
+            .unwrap();
+        assert_eq!(tokens["usdc"].decimals, Some(6));
+        assert_eq!(tokens["usdc"].label, Some("USD Coin".to_string()));
+
+        let mut tokens = HashMap::from([(
+            "unknown".to_string(),
+            TokenConfigSource {
+                network: "polygon".to_string(),
+                ..token(Address::repeat_byte(0x03), None)
+            },
+        )]);
+        let err = resolver
+            .resolve_token_sources(&mut tokens, &networks)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TokenMetadataError::NetworkNotFound(_, _)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_tokens() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/rpc")
+                .json_body_partial(json!({"method": "eth_call"}).to_string());
+            then.body(metadata_response(1, None));
+        });
+
+        let network = std::sync::Arc::new(rain_orderbook_app_settings::network::Network {
+            rpc: rpc_server.url("/rpc").parse().unwrap(),
+            ..rain_orderbook_app_settings::network::Network::dummy()
+        });
+        let known = Token {
+            network: network.clone(),
+            address: Address::repeat_byte(0x01),
+            decimals: Some(18),
+            label: Some("Known".to_string()),
+            symbol: Some("KNW".to_string()),
+        };
+        let unknown = Token {
+            network,
+            address: Address::repeat_byte(0x02),
+            decimals: None,
+            label: None,
+            symbol: None,
+        };
+
+        let metadata = TokenMetadataResolver::default()
+            .resolve_tokens(&[&known, &unknown])
+            .await
+            .unwrap();
+        assert_eq!(
+            metadata,
+            vec![
+                TokenMetadata {
+                    decimals: Some(18),
+                    symbol: Some("KNW".to_string()),
+                    name: Some("Known".to_string()),
+                },
+                TokenMetadata {
+                    decimals: Some(6),
+                    symbol: Some("USDC".to_string()),
+                    name: None,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_token_metadata_without_multicall() {
+        let rpc_server = MockServer::start_async().await;
+        // no multicall contract on the chain, so nothing is returned
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/rpc").body_contains("82ad56cb");
+            then.body(Response::new_success(1, "0x").to_json_string().unwrap());
+        });
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/rpc").body_contains("0x313ce567\"");
+            then.body(
+                Response::new_success(
+                    1,
+                    encode_prefixed(decimalsCall::abi_encode_returns(&(6,))).as_str(),
+                )
+                .to_json_string()
+                .unwrap(),
+            );
+        });
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/rpc").body_contains("0x95d89b41\"");
+            then.body(
+                Response::new_success(
+                    1,
+                    encode_prefixed(symbolCall::abi_encode_returns(&("USDC".to_string(),)))
+                        .as_str(),
+                )
+                .to_json_string()
+                .unwrap(),
+            );
+        });
+
+        let metadata =
+            fetch_token_metadata(&[rpc_server.url("/rpc")], &[Address::repeat_byte(0x01)])
+                .await
+                .unwrap();
+        assert_eq!(
+            metadata,
+            vec![TokenMetadata {
+                decimals: Some(6),
+                symbol: Some("USDC".to_string()),
+                name: None,
+            }]
+        );
+    }
+}
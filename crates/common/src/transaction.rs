@@ -1,7 +1,11 @@
 use alloy::primitives::{ruint::FromUintError, Address, U256};
 use alloy::sol_types::SolCall;
 #[cfg(not(target_family = "wasm"))]
-use alloy_ethers_typecast::client::{LedgerClient, LedgerClientError};
+use alloy_ethers_typecast::{
+    client::{LedgerClient, LedgerClientError},
+    ethers_address_to_alloy,
+    transaction::{WriteTransaction, WriteTransactionStatus},
+};
 use alloy_ethers_typecast::{
     gas_fee_middleware::GasFeeSpeed,
     transaction::{
@@ -9,6 +13,12 @@ use alloy_ethers_typecast::{
         WriteContractParametersBuilder, WriteContractParametersBuilderError,
     },
 };
+#[cfg(not(target_family = "wasm"))]
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Http, Provider},
+    signers::{coins_bip39::English, LocalWallet, MnemonicBuilder, Signer, WalletError},
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -38,6 +48,37 @@ pub enum TransactionArgsError {
     #[cfg(not(target_family = "wasm"))]
     #[error(transparent)]
     LedgerClient(#[from] LedgerClientError),
+    #[error("Environment variable '{0}' is not set")]
+    MissingEnvVar(String),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    Wallet(#[from] WalletError),
+    #[error(transparent)]
+    RpcUrlParse(#[from] url::ParseError),
+}
+
+/// Where the key used to sign write transactions comes from.
+/// Secrets are never stored in this struct, only the environment variables
+/// or file paths to read them from when the signer is built.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SignerSource {
+    /// Ledger hardware wallet, using `derivation_index` as the Ledger Live account index
+    #[default]
+    Ledger,
+    /// Hex encoded raw private key read from the given environment variable
+    #[serde(rename_all = "kebab-case")]
+    PrivateKey { env_var: String },
+    /// Encrypted JSON keystore file, with its password read from the given environment variable
+    #[serde(rename_all = "kebab-case")]
+    Keystore {
+        path: String,
+        password_env_var: String,
+    },
+    /// BIP-39 mnemonic phrase read from the given environment variable, using
+    /// `derivation_index` as the account index of the default derivation path
+    #[serde(rename_all = "kebab-case")]
+    Mnemonic { env_var: String },
 }
 
 #[derive(Clone, Serialize, Deserialize, Default)]
@@ -46,12 +87,70 @@ pub struct TransactionArgs {
     pub derivation_index: Option<usize>,
     pub chain_id: Option<u64>,
     pub rpc_url: String,
+    /// Rpc urls to fall back on, in order, when a read from `rpc_url` fails
+    #[serde(default)]
+    pub fallback_rpc_urls: Vec<String>,
     pub max_priority_fee_per_gas: Option<U256>,
     pub max_fee_per_gas: Option<U256>,
     pub gas_fee_speed: Option<GasFeeSpeed>,
+    #[serde(default)]
+    pub signer: SignerSource,
+}
+
+/// Client signing with a key held in memory
+#[cfg(not(target_family = "wasm"))]
+pub type LocalSignerClient = SignerMiddleware<Provider<Http>, LocalWallet>;
+
+/// A ready to use signing client built from [TransactionArgs]
+#[cfg(not(target_family = "wasm"))]
+pub enum TransactionSigner {
+    Ledger(LedgerClient),
+    Local(LocalSignerClient),
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl TransactionSigner {
+    /// Address of the account that signs the transactions
+    pub fn address(&self) -> Address {
+        match self {
+            TransactionSigner::Ledger(ledger_client) => {
+                ethers_address_to_alloy(ledger_client.client.address())
+            }
+            TransactionSigner::Local(client) => ethers_address_to_alloy(client.address()),
+        }
+    }
+
+    /// Sign, send and wait for confirmation of a contract write transaction
+    pub async fn execute<T: SolCall + Clone, S: Fn(WriteTransactionStatus<T>)>(
+        self,
+        params: WriteContractParameters<T>,
+        transaction_status_changed: S,
+    ) -> Result<(), WritableClientError> {
+        match self {
+            TransactionSigner::Ledger(ledger_client) => {
+                WriteTransaction::new(ledger_client.client, params, 4, transaction_status_changed)
+                    .execute()
+                    .await?;
+            }
+            TransactionSigner::Local(client) => {
+                WriteTransaction::new(client, params, 4, transaction_status_changed)
+                    .execute()
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl TransactionArgs {
+    /// The rpc url followed by the fallback rpc urls
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(self.rpc_url.clone())
+            .chain(self.fallback_rpc_urls.iter().cloned())
+            .collect()
+    }
+
     pub async fn try_into_write_contract_parameters<T: SolCall + Clone>(
         &self,
         call: T,
@@ -98,4 +197,209 @@ impl TransactionArgs {
             None => Err(TransactionArgsError::ChainIdNone),
         }
     }
+
+    /// Build the signing client for the configured [SignerSource]
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn try_into_signer(self) -> Result<TransactionSigner, TransactionArgsError> {
+        let wallet: LocalWallet = match &self.signer {
+            SignerSource::Ledger => {
+                return Ok(TransactionSigner::Ledger(
+                    self.try_into_ledger_client().await?,
+                ))
+            }
+            SignerSource::PrivateKey { env_var } => {
+                read_env_var(env_var)?.trim().parse::<LocalWallet>()?
+            }
+            SignerSource::Keystore {
+                path,
+                password_env_var,
+            } => LocalWallet::decrypt_keystore(path, read_env_var(password_env_var)?)?,
+            SignerSource::Mnemonic { env_var } => MnemonicBuilder::<English>::default()
+                .phrase(read_env_var(env_var)?.trim())
+                .index(self.derivation_index.unwrap_or(0) as u32)?
+                .build()?,
+        };
+
+        let chain_id = self.chain_id.ok_or(TransactionArgsError::ChainIdNone)?;
+        let provider = Provider::<Http>::try_from(self.rpc_url.as_str())?;
+        Ok(TransactionSigner::Local(SignerMiddleware::new(
+            provider,
+            wallet.with_chain_id(chain_id),
+        )))
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn read_env_var(name: &str) -> Result<String, TransactionArgsError> {
+    std::env::var(name).map_err(|_| TransactionArgsError::MissingEnvVar(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    #[test]
+    fn test_signer_source_serde() {
+        let args: TransactionArgs = serde_json::from_str(
+            r#"{
+                "orderbook_address": "0x0000000000000000000000000000000000000001",
+                "derivation_index": null,
+                "chain_id": 1,
+                "rpc_url": "https://some-rpc.com",
+                "max_priority_fee_per_gas": null,
+                "max_fee_per_gas": null,
+                "gas_fee_speed": null
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(args.signer, SignerSource::Ledger);
+        assert_eq!(args.rpc_urls(), vec!["https://some-rpc.com".to_string()]);
+
+        let signer: SignerSource = serde_json::from_str(
+            r#"{ "type": "keystore", "path": "./key.json", "password-env-var": "PASS" }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            signer,
+            SignerSource::Keystore {
+                path: "./key.json".to_string(),
+                password_env_var: "PASS".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_rpc_urls() {
+        let args = TransactionArgs {
+            rpc_url: "https://rpc-1.com".to_string(),
+            fallback_rpc_urls: vec![
+                "https://rpc-2.com".to_string(),
+                "https://rpc-3.com".to_string(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            args.rpc_urls(),
+            vec![
+                "https://rpc-1.com".to_string(),
+                "https://rpc-2.com".to_string(),
+                "https://rpc-3.com".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_private_key_signer() {
+        let local_evm = LocalEvm::new().await;
+        let key = alloy::hex::encode_prefixed(local_evm.anvil.keys()[1].to_bytes());
+        std::env::set_var("TEST_PRIVATE_KEY_SIGNER", key);
+
+        let signer = TransactionArgs {
+            rpc_url: local_evm.url(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_PRIVATE_KEY_SIGNER".to_string(),
+            },
+            ..Default::default()
+        }
+        .try_into_signer()
+        .await
+        .unwrap();
+
+        assert_eq!(signer.address(), local_evm.anvil.addresses()[1]);
+    }
+
+    #[tokio::test]
+    async fn test_mnemonic_signer() {
+        // anvil's default dev mnemonic
+        std::env::set_var(
+            "TEST_MNEMONIC_SIGNER",
+            "test test test test test test test test test test test junk",
+        );
+        let local_evm = LocalEvm::new().await;
+
+        let signer = TransactionArgs {
+            rpc_url: local_evm.url(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            derivation_index: Some(2),
+            signer: SignerSource::Mnemonic {
+                env_var: "TEST_MNEMONIC_SIGNER".to_string(),
+            },
+            ..Default::default()
+        }
+        .try_into_signer()
+        .await
+        .unwrap();
+
+        assert_eq!(signer.address(), local_evm.anvil.addresses()[2]);
+    }
+
+    #[tokio::test]
+    async fn test_keystore_signer() {
+        std::env::set_var("TEST_KEYSTORE_SIGNER_PASSWORD", "rain-keystore-test");
+
+        let signer = TransactionArgs {
+            chain_id: Some(1),
+            rpc_url: "https://some-rpc.com".to_string(),
+            signer: SignerSource::Keystore {
+                path: concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/keystore.json").to_string(),
+                password_env_var: "TEST_KEYSTORE_SIGNER_PASSWORD".to_string(),
+            },
+            ..Default::default()
+        }
+        .try_into_signer()
+        .await
+        .unwrap();
+
+        // anvil's fourth dev account
+        assert_eq!(
+            signer.address(),
+            "0x90F79bf6EB2c4f870365E785982E1f101E93b906"
+                .parse::<Address>()
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keystore_signer_wrong_password() {
+        std::env::set_var("TEST_KEYSTORE_SIGNER_WRONG_PASSWORD", "not-the-password");
+
+        let err = TransactionArgs {
+            chain_id: Some(1),
+            rpc_url: "https://some-rpc.com".to_string(),
+            signer: SignerSource::Keystore {
+                path: concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/keystore.json").to_string(),
+                password_env_var: "TEST_KEYSTORE_SIGNER_WRONG_PASSWORD".to_string(),
+            },
+            ..Default::default()
+        }
+        .try_into_signer()
+        .await
+        .err()
+        .unwrap();
+
+        assert!(matches!(err, TransactionArgsError::Wallet(_)));
+    }
+
+    #[tokio::test]
+    async fn test_signer_missing_env_var() {
+        let err = TransactionArgs {
+            chain_id: Some(1),
+            rpc_url: "https://some-rpc.com".to_string(),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_UNSET_PRIVATE_KEY_SIGNER".to_string(),
+            },
+            ..Default::default()
+        }
+        .try_into_signer()
+        .await
+        .err()
+        .unwrap();
+
+        assert!(matches!(
+            err,
+            TransactionArgsError::MissingEnvVar(name) if name == "TEST_UNSET_PRIVATE_KEY_SIGNER"
+        ));
+    }
 }
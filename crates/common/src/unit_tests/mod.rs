@@ -334,15 +334,17 @@ impl TestRunner {
             ReadableClientHttp::new_from_url(self.test_setup.deployer.network.rpc.to_string())?
                 .get_block_number()
                 .await?;
-        let blocks = self
-            .settings
-            .test_config
-            .scenario
-            .blocks
-            .as_ref()
-            .map_or(Ok(vec![block_number]), |b| {
-                b.expand_to_block_numbers(block_number)
-            })?;
+        let blocks = match &self.settings.test_config.scenario.blocks {
+            Some(blocks) => {
+                blocks
+                    .expand_to_block_numbers_with_rpc(
+                        block_number,
+                        self.test_setup.deployer.network.rpc.as_str(),
+                    )
+                    .await?
+            }
+            None => vec![block_number],
+        };
         self.test_setup.block_number = blocks[0];
 
         // Create a fork with the first block number
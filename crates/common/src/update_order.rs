@@ -0,0 +1,472 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::simulation::{new_fork, simulate, SimulationCall, SimulationError, SimulationReport};
+use crate::{
+    add_order::{AddOrderArgs, AddOrderArgsError},
+    meta::{TryDecodeRainlangSource, TryDecodeRainlangSourceError},
+    transaction::{TransactionArgs, TransactionArgsError},
+};
+use alloy::primitives::{Address, Bytes, U256};
+use alloy::sol_types::SolCall;
+use alloy_ethers_typecast::transaction::WritableClientError;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+#[cfg(not(target_family = "wasm"))]
+use rain_interpreter_eval::error::ForkCallError;
+use rain_orderbook_app_settings::{deployment::Deployment, order::OrderIO};
+use rain_orderbook_bindings::{
+    IOrderBookV4::{removeOrder2Call, OrderV3, IO},
+    OrderBook::multicallCall,
+};
+use rain_orderbook_subgraph_client::types::{common::Order, order_detail_traits::OrderDetailError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum UpdateOrderArgsError {
+    #[error(transparent)]
+    AddOrderArgsError(#[from] AddOrderArgsError),
+    #[error(transparent)]
+    OrderDetailError(#[from] OrderDetailError),
+    #[error(transparent)]
+    TryDecodeRainlangSourceError(#[from] TryDecodeRainlangSourceError),
+    #[error(transparent)]
+    TransactionArgs(#[from] TransactionArgsError),
+    #[error(transparent)]
+    WritableClientError(#[from] WritableClientError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    ForkCallError(#[from] ForkCallError),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+    #[error("Vaults do not match the old order: {0}")]
+    VaultMismatch(String),
+    #[error("Signer {0} is not the owner of the old order")]
+    NotOwner(Address),
+}
+
+/// Replaces a live order with a new version of its deployment in a single
+/// OrderBook multicall of removeOrder2 and addOrder2, keeping its vaults
+#[derive(Debug, Clone)]
+pub struct UpdateOrderArgs {
+    pub old_order: OrderV3,
+    /// Composed rainlang of the old order, if it was added with meta
+    pub old_rainlang: Option<String>,
+    pub add_order: AddOrderArgs,
+}
+
+impl UpdateOrderArgs {
+    /// Create a new instance from an order fetched from the subgraph and the
+    /// deployment replacing it
+    pub async fn new_from_sg_order(
+        order: Order,
+        dotrain: String,
+        deployment: Deployment,
+    ) -> Result<Self, UpdateOrderArgsError> {
+        let old_rainlang = order
+            .meta
+            .as_ref()
+            .map(|meta| meta.try_decode_rainlangsource())
+            .transpose()?;
+        Self::new_from_deployment(order.try_into()?, old_rainlang, dotrain, deployment).await
+    }
+
+    /// Create a new instance from the old order and the deployment replacing
+    /// it, the new order reuses the vault ids of the old one
+    pub async fn new_from_deployment(
+        old_order: OrderV3,
+        old_rainlang: Option<String>,
+        dotrain: String,
+        deployment: Deployment,
+    ) -> Result<Self, UpdateOrderArgsError> {
+        let mut add_order = AddOrderArgs::new_from_deployment_with_owner(
+            dotrain,
+            deployment.clone(),
+            Some(old_order.owner),
+        )
+        .await?;
+
+        let explicit_vault_ids =
+            |ios: &[OrderIO]| ios.iter().map(|v| v.vault_id).collect::<Vec<_>>();
+        reuse_vault_ids(
+            "input",
+            &mut add_order.inputs,
+            &explicit_vault_ids(&deployment.order.inputs),
+            &old_order.validInputs,
+        )?;
+        reuse_vault_ids(
+            "output",
+            &mut add_order.outputs,
+            &explicit_vault_ids(&deployment.order.outputs),
+            &old_order.validOutputs,
+        )?;
+
+        Ok(Self {
+            old_order,
+            old_rainlang,
+            add_order,
+        })
+    }
+
+    /// Line diff of the old order's composed rainlang and the new one
+    pub fn get_rainlang_diff(&self) -> Result<String, UpdateOrderArgsError> {
+        let new_rainlang = self.add_order.compose_to_rainlang()?;
+        Ok(diff_lines(
+            self.old_rainlang.as_deref().unwrap_or_default(),
+            &new_rainlang,
+        ))
+    }
+
+    pub fn try_into_remove_call(&self) -> removeOrder2Call {
+        removeOrder2Call {
+            order: self.old_order.clone(),
+            tasks: vec![],
+        }
+    }
+
+    /// Generate the multicall of removeOrder2 followed by addOrder2
+    pub async fn try_into_call(
+        &self,
+        rpcs: &[String],
+    ) -> Result<multicallCall, UpdateOrderArgsError> {
+        let add_order_call = self.add_order.try_into_call(rpcs).await?;
+        Ok(multicallCall {
+            data: vec![
+                Bytes::from(self.try_into_remove_call().abi_encode()),
+                Bytes::from(add_order_call.abi_encode()),
+            ],
+        })
+    }
+
+    pub async fn get_update_order_calldata(
+        &self,
+        transaction_args: TransactionArgs,
+    ) -> Result<Vec<u8>, UpdateOrderArgsError> {
+        Ok(self
+            .try_into_call(&transaction_args.rpc_urls())
+            .await?
+            .abi_encode())
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute<S: Fn(WriteTransactionStatus<multicallCall>)>(
+        &self,
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), UpdateOrderArgsError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+        if signer.address() != self.old_order.owner {
+            return Err(UpdateOrderArgsError::NotOwner(signer.address()));
+        }
+
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        let params = transaction_args
+            .try_into_write_contract_parameters(call, transaction_args.orderbook_address)
+            .await?;
+
+        signer.execute(params, transaction_status_changed).await?;
+
+        Ok(())
+    }
+
+    /// Simulate the multicall on a fork and report the outcome, including the
+    /// traces of the new order's post tasks, sent from the old order's owner
+    /// if no sender is given
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, UpdateOrderArgsError> {
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        let calls = vec![SimulationCall::new(
+            from.unwrap_or(self.old_order.owner),
+            transaction_args.orderbook_address,
+            call,
+        )];
+        Ok(simulate(&transaction_args, calls, &[], &[]).await?)
+    }
+
+    /// Simulate the multicall on a fork, sent from the old order's owner if
+    /// no sender is given
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate_execute(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<(), UpdateOrderArgsError> {
+        let mut forker = new_fork(&transaction_args).await?;
+        let call = self.try_into_call(&transaction_args.rpc_urls()).await?;
+        forker
+            .alloy_call_committing(
+                from.unwrap_or(self.old_order.owner),
+                transaction_args.orderbook_address,
+                call,
+                U256::ZERO,
+                true,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Set the vault ids of the new IOs to the ones of the old IOs at the same
+/// index, erroring if the tokens at an index differ or a vault id set on the
+/// deployment is not the old one. Matching by index keeps the vaults of IOs
+/// that share a token apart.
+fn reuse_vault_ids(
+    kind: &str,
+    new_ios: &mut [IO],
+    explicit_vault_ids: &[Option<U256>],
+    old_ios: &[IO],
+) -> Result<(), UpdateOrderArgsError> {
+    if new_ios.len() != old_ios.len() {
+        return Err(UpdateOrderArgsError::VaultMismatch(format!(
+            "expected {} {}s, got {}",
+            old_ios.len(),
+            kind,
+            new_ios.len()
+        )));
+    }
+    for (i, ((new_io, explicit_vault_id), old_io)) in new_ios
+        .iter_mut()
+        .zip(explicit_vault_ids)
+        .zip(old_ios)
+        .enumerate()
+    {
+        if new_io.token != old_io.token {
+            return Err(UpdateOrderArgsError::VaultMismatch(format!(
+                "{} {} token {} differs from the old token {}",
+                kind, i, new_io.token, old_io.token
+            )));
+        }
+        if let Some(vault_id) = explicit_vault_id {
+            if *vault_id != old_io.vaultId {
+                return Err(UpdateOrderArgsError::VaultMismatch(format!(
+                    "{} {} vault id {} differs from the old vault id {}",
+                    kind, i, vault_id, old_io.vaultId
+                )));
+            }
+        }
+        new_io.vaultId = old_io.vaultId;
+    }
+    Ok(())
+}
+
+/// Minimal line diff, lines only in `old` are prefixed with `-`, lines only
+/// in `new` with `+` and common lines with a space
+pub fn diff_lines(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // longest common subsequence lengths of the suffixes
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            diff.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|v| format!("- {}", v)));
+    diff.extend(new[j..].iter().map(|v| format!("+ {}", v)));
+    diff.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dotrain_order::DotrainOrder;
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    #[test]
+    fn test_diff_lines() {
+        let old = "a\nb\nc";
+        let new = "a\nx\nc\nd";
+        assert_eq!(diff_lines(old, new), "  a\n- b\n+ x\n  c\n+ d");
+        assert_eq!(diff_lines("", "a"), "+ a");
+        assert_eq!(diff_lines("a", "a"), "  a");
+    }
+
+    #[test]
+    fn test_reuse_vault_ids() {
+        let io = |token: u8, vault_id: u64| IO {
+            token: Address::repeat_byte(token),
+            decimals: 18,
+            vaultId: U256::from(vault_id),
+        };
+        let old = vec![io(1, 10), io(2, 20)];
+
+        let mut new = vec![io(1, 99), io(2, 99)];
+        reuse_vault_ids("input", &mut new, &[None, None], &old).unwrap();
+        assert_eq!(new, vec![io(1, 10), io(2, 20)]);
+
+        // ios of the same token keep their own vaults
+        let same_token_old = vec![io(1, 10), io(1, 20)];
+        let mut new = vec![io(1, 99), io(1, 99)];
+        reuse_vault_ids(
+            "input",
+            &mut new,
+            &[None, Some(U256::from(20))],
+            &same_token_old,
+        )
+        .unwrap();
+        assert_eq!(new, same_token_old);
+
+        // reordered tokens do not match
+        let mut new = vec![io(2, 99), io(1, 99)];
+        let result = reuse_vault_ids("input", &mut new, &[None, None], &old);
+        assert!(matches!(
+            result,
+            Err(UpdateOrderArgsError::VaultMismatch(_))
+        ));
+
+        let mut new = vec![io(1, 99), io(2, 99)];
+        let result = reuse_vault_ids("input", &mut new, &[Some(U256::from(5)), None], &old);
+        assert!(matches!(
+            result,
+            Err(UpdateOrderArgsError::VaultMismatch(_))
+        ));
+
+        let mut new = vec![io(3, 99), io(1, 99)];
+        let result = reuse_vault_ids("input", &mut new, &[None, None], &old);
+        assert!(matches!(
+            result,
+            Err(UpdateOrderArgsError::VaultMismatch(_))
+        ));
+
+        let mut new = vec![io(1, 99)];
+        let result = reuse_vault_ids("input", &mut new, &[None], &old);
+        assert!(matches!(
+            result,
+            Err(UpdateOrderArgsError::VaultMismatch(_))
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_update_order() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let owner = local_evm.anvil.addresses()[0];
+
+        let get_dotrain = |ratio: &str| {
+            format!(
+                r#"
+networks:
+    some-key:
+        rpc: {rpc_url}
+        chain-id: 123
+        network-id: 123
+        currency: ETH
+deployers:
+    some-key:
+        address: {deployer}
+tokens:
+    t1:
+        network: some-key
+        address: {token1}
+        decimals: 18
+    t2:
+        network: some-key
+        address: {token2}
+        decimals: 18
+orders:
+    some-key:
+        inputs:
+            - token: t1
+        outputs:
+            - token: t2
+scenarios:
+    some-key:
+deployments:
+    some-key:
+        scenario: some-key
+        order: some-key
+---
+#calculate-io
+_ _: 100e18 {ratio};
+#handle-add-order
+:;
+#handle-io
+:;
+"#,
+                rpc_url = local_evm.url(),
+                deployer = local_evm.deployer.address(),
+                token1 = local_evm.tokens[0].address(),
+                token2 = local_evm.tokens[1].address(),
+            )
+        };
+
+        let dotrain = get_dotrain("2e18");
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let old_add_order = AddOrderArgs::new_from_deployment(dotrain, deployment)
+            .await
+            .unwrap();
+        let calldata = old_add_order
+            .try_into_call(&[local_evm.url()])
+            .await
+            .unwrap()
+            .abi_encode();
+        let old_order = local_evm.add_order(&calldata, owner).await.0.order;
+        let old_rainlang = old_add_order.compose_to_rainlang().unwrap();
+
+        let dotrain = get_dotrain("3e18");
+        let order = DotrainOrder::new(dotrain.clone(), None).await.unwrap();
+        let deployment = order.config().deployments["some-key"].as_ref().clone();
+        let args = UpdateOrderArgs::new_from_deployment(
+            old_order.clone(),
+            Some(old_rainlang),
+            dotrain,
+            deployment,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(args.add_order.inputs, old_order.validInputs);
+        assert_eq!(args.add_order.outputs, old_order.validOutputs);
+        let diff = args.get_rainlang_diff().unwrap();
+        assert!(diff
+            .lines()
+            .any(|v| v.starts_with("- ") && v.contains("2e18")));
+        assert!(diff
+            .lines()
+            .any(|v| v.starts_with("+ ") && v.contains("3e18")));
+
+        let tx_args = TransactionArgs {
+            orderbook_address: *local_evm.orderbook.address(),
+            rpc_url: local_evm.url(),
+            ..Default::default()
+        };
+        args.simulate_execute(tx_args.clone(), None).await.unwrap();
+        let report = args.simulate(tx_args.clone(), None).await.unwrap();
+        assert!(!report.reverted);
+
+        // only the owner can remove the old order
+        assert!(args
+            .simulate_execute(tx_args.clone(), Some(local_evm.anvil.addresses()[1]))
+            .await
+            .is_err());
+        let report = args
+            .simulate(tx_args, Some(local_evm.anvil.addresses()[1]))
+            .await
+            .unwrap();
+        assert!(report.reverted);
+    }
+}
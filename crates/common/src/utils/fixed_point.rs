@@ -0,0 +1,74 @@
+use alloy::primitives::U256;
+
+/// 1e18, the scale of the orderbook's 18 decimals fixed point values
+pub const ONE_18: U256 = U256::from_limbs([1_000_000_000_000_000_000, 0, 0, 0]);
+
+/// Rescales an amount with the given decimals to 18 decimals, rounding down
+/// and saturating on overflow
+pub fn scale_18(amount: U256, decimals: u8) -> U256 {
+    if decimals > 18 {
+        amount / U256::from(10).pow(U256::from(decimals - 18))
+    } else {
+        amount.saturating_mul(U256::from(10).pow(U256::from(18 - decimals)))
+    }
+}
+
+/// Rescales an 18 decimals amount to the given decimals, optionally rounding up
+pub fn scale_n(amount_18: U256, decimals: u8, round_up: bool) -> U256 {
+    if decimals >= 18 {
+        amount_18.saturating_mul(U256::from(10).pow(U256::from(decimals - 18)))
+    } else {
+        let scale = U256::from(10).pow(U256::from(18 - decimals));
+        if round_up {
+            amount_18.div_ceil(scale)
+        } else {
+            amount_18 / scale
+        }
+    }
+}
+
+/// Multiplies two 18 decimals fixed point values, rounding up and saturating
+/// on overflow
+pub fn fixed_point_mul_up(a: U256, b: U256) -> U256 {
+    match a.checked_mul(b) {
+        Some(product) => product.div_ceil(ONE_18),
+        None => U256::MAX,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_18() {
+        assert_eq!(
+            scale_18(U256::from(1_500_000), 6),
+            U256::from(15) * ONE_18 / U256::from(10)
+        );
+        assert_eq!(scale_18(U256::from(7), 18), U256::from(7));
+        assert_eq!(scale_18(U256::from(1234), 20), U256::from(12));
+        assert_eq!(scale_18(U256::MAX, 6), U256::MAX);
+    }
+
+    #[test]
+    fn test_scale_n() {
+        let amount_18 = U256::from(1_500_000_000_000_000_001u64);
+        assert_eq!(scale_n(amount_18, 6, false), U256::from(1_500_000));
+        assert_eq!(scale_n(amount_18, 6, true), U256::from(1_500_001));
+        assert_eq!(scale_n(amount_18, 18, true), amount_18);
+        assert_eq!(scale_n(U256::from(12), 20, false), U256::from(1200));
+    }
+
+    #[test]
+    fn test_fixed_point_mul_up() {
+        let two = U256::from(2) * ONE_18;
+        let three = U256::from(3) * ONE_18;
+        assert_eq!(fixed_point_mul_up(two, three), U256::from(6) * ONE_18);
+        assert_eq!(
+            fixed_point_mul_up(U256::from(1), U256::from(1)),
+            U256::from(1)
+        );
+        assert_eq!(fixed_point_mul_up(U256::MAX, two), U256::MAX);
+    }
+}
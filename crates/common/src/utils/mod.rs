@@ -1 +1,2 @@
+pub mod fixed_point;
 pub mod timestamp;
@@ -0,0 +1,546 @@
+#[cfg(not(target_family = "wasm"))]
+use crate::simulation::{
+    resolve_sender, simulate, simulate_each, SimulationCall, SimulationError, SimulationReport,
+};
+use crate::{
+    deposit::{DepositArgs, DepositError},
+    transaction::{TransactionArgs, TransactionArgsError, WritableTransactionExecuteError},
+    withdraw::WithdrawArgs,
+};
+use alloy::primitives::{ruint::ParseError, Address, U256};
+use alloy::sol_types::SolCall;
+#[cfg(not(target_family = "wasm"))]
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
+use rain_orderbook_bindings::{
+    IOrderBookV4::{deposit2Call, withdraw2Call},
+    OrderBook::multicallCall,
+    IERC20::approveCall,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+#[cfg(not(target_family = "wasm"))]
+use tracing::warn;
+
+#[derive(Error, Debug)]
+pub enum VaultBatchError {
+    #[error(transparent)]
+    DepositError(#[from] DepositError),
+    #[error(transparent)]
+    WritableTransactionExecuteError(#[from] WritableTransactionExecuteError),
+    #[error(transparent)]
+    TransactionArgsError(#[from] TransactionArgsError),
+    #[error(transparent)]
+    YamlError(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    CsvError(#[from] csv::Error),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SimulationError(#[from] SimulationError),
+    #[error("Invalid amount '{0}' in batch item {1}: {2}")]
+    InvalidAmount(String, usize, ParseError),
+    #[error("Invalid vault id '{0}' in batch item {1}: {2}")]
+    InvalidVaultId(String, usize, ParseError),
+    #[error("Batch has no items")]
+    EmptyBatch,
+}
+
+/// A single deposit or withdrawal of a vault batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum VaultBatchItem {
+    Deposit(DepositArgs),
+    Withdraw(WithdrawArgs),
+}
+
+impl VaultBatchItem {
+    pub fn token(&self) -> Address {
+        match self {
+            VaultBatchItem::Deposit(args) => args.token,
+            VaultBatchItem::Withdraw(args) => args.token,
+        }
+    }
+
+    /// Encoded deposit2 or withdraw2 call of this item
+    pub fn abi_encode(&self) -> Vec<u8> {
+        match self {
+            VaultBatchItem::Deposit(args) => deposit2Call::from(args.clone()).abi_encode(),
+            VaultBatchItem::Withdraw(args) => withdraw2Call::from(args.clone()).abi_encode(),
+        }
+    }
+}
+
+/// Outcome of a single deposit or withdrawal of an executed batch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "reason", rename_all = "kebab-case")]
+pub enum VaultBatchItemResult {
+    /// Went through in the confirmed batch transaction
+    Success,
+    /// Reverts on its own, with the decoded revert reason, and so reverted
+    /// the whole batch
+    Reverted(String),
+    /// Did not go through because other items reverted the batch transaction
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VaultBatchAction {
+    Deposit,
+    Withdraw,
+}
+
+/// A row of a yaml or csv batch file, the amount is the withdraw target amount
+/// for withdrawals. Amounts and vault ids can be decimal or 0x prefixed hex.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct VaultBatchRow {
+    pub action: VaultBatchAction,
+    pub token: Address,
+    #[serde(alias = "vault_id")]
+    pub vault_id: String,
+    pub amount: String,
+}
+
+/// Deposits and withdrawals sent as a single OrderBook multicall transaction
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct VaultBatchArgs {
+    pub items: Vec<VaultBatchItem>,
+}
+
+impl TryFrom<Vec<VaultBatchRow>> for VaultBatchArgs {
+    type Error = VaultBatchError;
+
+    fn try_from(rows: Vec<VaultBatchRow>) -> Result<Self, Self::Error> {
+        let mut items = vec![];
+        for (i, row) in rows.into_iter().enumerate() {
+            let vault_id = U256::from_str(row.vault_id.trim())
+                .map_err(|e| VaultBatchError::InvalidVaultId(row.vault_id.clone(), i, e))?;
+            let amount = U256::from_str(row.amount.trim())
+                .map_err(|e| VaultBatchError::InvalidAmount(row.amount.clone(), i, e))?;
+            items.push(match row.action {
+                VaultBatchAction::Deposit => VaultBatchItem::Deposit(DepositArgs {
+                    token: row.token,
+                    vault_id,
+                    amount,
+                }),
+                VaultBatchAction::Withdraw => VaultBatchItem::Withdraw(WithdrawArgs {
+                    token: row.token,
+                    vault_id,
+                    target_amount: amount,
+                }),
+            });
+        }
+        Ok(Self { items })
+    }
+}
+
+impl VaultBatchArgs {
+    /// Parse a yaml list of [VaultBatchRow]
+    pub fn from_yaml(text: &str) -> Result<Self, VaultBatchError> {
+        serde_yaml::from_str::<Vec<VaultBatchRow>>(text)?.try_into()
+    }
+
+    /// Parse a csv of [VaultBatchRow] with an `action,token,vault-id,amount` header
+    pub fn from_csv(text: &str) -> Result<Self, VaultBatchError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(text.as_bytes());
+        let rows = reader
+            .deserialize::<VaultBatchRow>()
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.try_into()
+    }
+
+    /// Total deposit amount of each token, in the order the tokens first
+    /// appear in the batch, each needs to be approved for the orderbook
+    pub fn deposit_totals(&self) -> Vec<DepositArgs> {
+        let mut totals: Vec<DepositArgs> = vec![];
+        for item in &self.items {
+            if let VaultBatchItem::Deposit(args) = item {
+                match totals.iter_mut().find(|v| v.token == args.token) {
+                    Some(total) => total.amount = total.amount.saturating_add(args.amount),
+                    None => totals.push(DepositArgs {
+                        token: args.token,
+                        vault_id: U256::ZERO,
+                        amount: args.amount,
+                    }),
+                }
+            }
+        }
+        totals
+    }
+
+    pub fn try_into_call(&self) -> Result<multicallCall, VaultBatchError> {
+        if self.items.is_empty() {
+            return Err(VaultBatchError::EmptyBatch);
+        }
+        Ok(multicallCall {
+            data: self.items.iter().map(|v| v.abi_encode().into()).collect(),
+        })
+    }
+
+    /// Execute IERC20 approve calls for every deposited token that does not
+    /// have enough allowance yet, reporting each approval's status
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute_approvals<S: Fn(Address, WriteTransactionStatus<approveCall>)>(
+        &self,
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<(), VaultBatchError> {
+        for total in self.deposit_totals() {
+            total
+                .execute_approve(transaction_args.clone(), |status| {
+                    transaction_status_changed(total.token, status)
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Approve calldata of each deposited token whose current allowance is
+    /// not enough, approving the token's total deposit amount since approve
+    /// sets the allowance rather than adding to it
+    pub async fn get_approve_calldatas(
+        &self,
+        transaction_args: TransactionArgs,
+        owner: Address,
+    ) -> Result<Vec<(Address, Vec<u8>)>, VaultBatchError> {
+        let mut calldatas = vec![];
+        for total in self.deposit_totals() {
+            let current_allowance = total
+                .read_allowance(owner, transaction_args.clone())
+                .await?;
+            if current_allowance < total.amount {
+                calldatas.push((
+                    total.token,
+                    approveCall {
+                        spender: transaction_args.orderbook_address,
+                        amount: total.amount,
+                    }
+                    .abi_encode(),
+                ));
+            }
+        }
+        Ok(calldatas)
+    }
+
+    /// Execute OrderBook multicall of all the batch items and return the
+    /// result of each item in batch order. The multicall goes through or
+    /// reverts as a whole, so if it fails each item is simulated on its own
+    /// to find the ones that make it revert. Fails with the transaction's
+    /// error if none of the items reverts on its own.
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn execute<S: Fn(WriteTransactionStatus<multicallCall>)>(
+        &self,
+        transaction_args: TransactionArgs,
+        transaction_status_changed: S,
+    ) -> Result<Vec<VaultBatchItemResult>, VaultBatchError> {
+        let signer = transaction_args.clone().try_into_signer().await?;
+        let from = signer.address();
+
+        let multicall = self.try_into_call()?;
+        let params = transaction_args
+            .try_into_write_contract_parameters(multicall, transaction_args.orderbook_address)
+            .await?;
+
+        if let Err(e) = signer.execute(params, transaction_status_changed).await {
+            // only a batch that reverts because of some of its items is
+            // reported per item, any other failure, eg of the signer or the
+            // rpc, is returned as is since the transaction may have been sent
+            let reasons = match self.simulate_items(&transaction_args, from).await {
+                Ok(v) => v,
+                Err(sim_e) => {
+                    warn!("Failed to simulate the batch items: {}", sim_e);
+                    return Err(WritableTransactionExecuteError::from(e).into());
+                }
+            };
+            if reasons.iter().all(Option::is_none) {
+                return Err(WritableTransactionExecuteError::from(e).into());
+            }
+            return Ok(reasons
+                .into_iter()
+                .map(|reason| match reason {
+                    Some(reason) => VaultBatchItemResult::Reverted(reason),
+                    None => VaultBatchItemResult::Failed(e.to_string()),
+                })
+                .collect());
+        }
+
+        Ok(vec![VaultBatchItemResult::Success; self.items.len()])
+    }
+
+    /// Revert reason of each item, in batch order, when run one by one on a
+    /// fork of the network from the given address after the batch's
+    /// approvals, None for the items that go through
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate_items(
+        &self,
+        transaction_args: &TransactionArgs,
+        from: Address,
+    ) -> Result<Vec<Option<String>>, SimulationError> {
+        let orderbook = transaction_args.orderbook_address;
+        let mut calls = self.approve_simulation_calls(from, orderbook);
+        let approvals = calls.len();
+        calls.extend(self.items.iter().map(|item| SimulationCall {
+            from,
+            to: orderbook,
+            calldata: item.abi_encode(),
+        }));
+
+        let mut reasons = simulate_each(transaction_args, calls).await?;
+        Ok(reasons.split_off(approvals))
+    }
+
+    /// Simulate the approvals and the multicall on a fork of the network and
+    /// report the outcome, including the balances of the batch's tokens
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, VaultBatchError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let orderbook = transaction_args.orderbook_address;
+        let mut calls = self.approve_simulation_calls(from, orderbook);
+        calls.push(SimulationCall::new(from, orderbook, self.try_into_call()?));
+
+        let mut tokens: Vec<Address> = vec![];
+        for item in &self.items {
+            if !tokens.contains(&item.token()) {
+                tokens.push(item.token());
+            }
+        }
+        Ok(simulate(&transaction_args, calls, &tokens, &[from, orderbook]).await?)
+    }
+
+    /// Approvals of each token's total deposit amount for the orderbook, to
+    /// run before the batch's deposits in a simulation
+    #[cfg(not(target_family = "wasm"))]
+    pub fn approve_simulation_calls(
+        &self,
+        from: Address,
+        orderbook: Address,
+    ) -> Vec<SimulationCall> {
+        self.deposit_totals()
+            .iter()
+            .map(|total| {
+                SimulationCall::new(
+                    from,
+                    total.token,
+                    approveCall {
+                        spender: orderbook,
+                        amount: total.amount,
+                    },
+                )
+            })
+            .collect()
+    }
+
+    pub fn get_multicall_calldata(&self) -> Result<Vec<u8>, VaultBatchError> {
+        Ok(self.try_into_call()?.abi_encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::SignerSource;
+    use alloy::primitives::{hex::encode_prefixed, utils::parse_ether};
+    use rain_orderbook_test_fixtures::LocalEvm;
+
+    #[test]
+    fn test_from_yaml_and_csv() {
+        let yaml = r#"
+- action: deposit
+  token: 0x0000000000000000000000000000000000000001
+  vault-id: 0x01
+  amount: "100"
+- action: withdraw
+  token: 0x0000000000000000000000000000000000000002
+  vault-id: "2"
+  amount: 0xff
+"#;
+        let csv = "action,token,vault-id,amount
+deposit, 0x0000000000000000000000000000000000000001, 0x01, 100
+withdraw, 0x0000000000000000000000000000000000000002, 2, 0xff
+";
+        for args in [
+            VaultBatchArgs::from_yaml(yaml).unwrap(),
+            VaultBatchArgs::from_csv(csv).unwrap(),
+        ] {
+            assert_eq!(args.items.len(), 2);
+            let VaultBatchItem::Deposit(deposit) = &args.items[0] else {
+                panic!("expected deposit");
+            };
+            assert_eq!(deposit.vault_id, U256::from(1));
+            assert_eq!(deposit.amount, U256::from(100));
+            let VaultBatchItem::Withdraw(withdraw) = &args.items[1] else {
+                panic!("expected withdraw");
+            };
+            assert_eq!(withdraw.token, Address::with_last_byte(2));
+            assert_eq!(withdraw.vault_id, U256::from(2));
+            assert_eq!(withdraw.target_amount, U256::from(255));
+        }
+
+        let csv = "action,token,vault-id,amount
+deposit,0x0000000000000000000000000000000000000001,0x01,abc
+";
+        assert!(matches!(
+            VaultBatchArgs::from_csv(csv),
+            Err(VaultBatchError::InvalidAmount(amount, 0, _)) if amount == "abc"
+        ));
+    }
+
+    #[test]
+    fn test_deposit_totals_and_call() {
+        let token1 = Address::random();
+        let token2 = Address::random();
+        let deposit = |token, vault_id: u64, amount: u64| {
+            VaultBatchItem::Deposit(DepositArgs {
+                token,
+                vault_id: U256::from(vault_id),
+                amount: U256::from(amount),
+            })
+        };
+        let args = VaultBatchArgs {
+            items: vec![
+                deposit(token1, 1, 10),
+                deposit(token2, 1, 5),
+                VaultBatchItem::Withdraw(WithdrawArgs {
+                    token: token1,
+                    vault_id: U256::from(3),
+                    target_amount: U256::from(100),
+                }),
+                deposit(token1, 2, 15),
+            ],
+        };
+
+        let totals = args.deposit_totals();
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].token, token1);
+        assert_eq!(totals[0].amount, U256::from(25));
+        assert_eq!(totals[1].token, token2);
+        assert_eq!(totals[1].amount, U256::from(5));
+
+        let call = args.try_into_call().unwrap();
+        assert_eq!(call.data.len(), 4);
+        let withdraw = withdraw2Call::abi_decode(&call.data[2], true).unwrap();
+        assert_eq!(withdraw.vaultId, U256::from(3));
+
+        assert!(matches!(
+            VaultBatchArgs::default().try_into_call(),
+            Err(VaultBatchError::EmptyBatch)
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_execute_batch() {
+        let local_evm = LocalEvm::new_with_tokens(2).await;
+        let owner = local_evm.anvil.addresses()[0];
+        let token1 = *local_evm.tokens[0].address();
+        let token2 = *local_evm.tokens[1].address();
+
+        std::env::set_var(
+            "TEST_VAULT_BATCH_PRIVATE_KEY",
+            encode_prefixed(local_evm.anvil.keys()[0].to_bytes()),
+        );
+        let tx_args = TransactionArgs {
+            orderbook_address: *local_evm.orderbook.address(),
+            chain_id: Some(local_evm.anvil.chain_id()),
+            rpc_url: local_evm.url(),
+            signer: SignerSource::PrivateKey {
+                env_var: "TEST_VAULT_BATCH_PRIVATE_KEY".to_string(),
+            },
+            ..Default::default()
+        };
+
+        let args = VaultBatchArgs {
+            items: vec![
+                VaultBatchItem::Deposit(DepositArgs {
+                    token: token1,
+                    vault_id: U256::from(1),
+                    amount: parse_ether("10").unwrap(),
+                }),
+                VaultBatchItem::Deposit(DepositArgs {
+                    token: token2,
+                    vault_id: U256::from(2),
+                    amount: parse_ether("20").unwrap(),
+                }),
+                VaultBatchItem::Withdraw(WithdrawArgs {
+                    token: token1,
+                    vault_id: U256::from(1),
+                    target_amount: parse_ether("4").unwrap(),
+                }),
+            ],
+        };
+        let approvals = args
+            .get_approve_calldatas(tx_args.clone(), owner)
+            .await
+            .unwrap();
+        assert_eq!(approvals.len(), 2);
+
+        args.execute_approvals(tx_args.clone(), |_, _| {})
+            .await
+            .unwrap();
+
+        // approve sets the allowance, so a bigger deposit needs approving in
+        // full rather than by the missing difference
+        let bigger = VaultBatchArgs {
+            items: vec![VaultBatchItem::Deposit(DepositArgs {
+                token: token1,
+                vault_id: U256::from(1),
+                amount: parse_ether("15").unwrap(),
+            })],
+        };
+        let approvals = bigger
+            .get_approve_calldatas(tx_args.clone(), owner)
+            .await
+            .unwrap();
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(
+            approveCall::abi_decode(&approvals[0].1, true)
+                .unwrap()
+                .amount,
+            parse_ether("15").unwrap()
+        );
+
+        let results = args.execute(tx_args.clone(), |_| {}).await.unwrap();
+        assert_eq!(results, vec![VaultBatchItemResult::Success; 3]);
+
+        let orderbook = &local_evm.orderbook;
+        let balance1 = orderbook
+            .vaultBalance(owner, token1, U256::from(1))
+            .call()
+            .await
+            .unwrap()
+            ._0;
+        let balance2 = orderbook
+            .vaultBalance(owner, token2, U256::from(2))
+            .call()
+            .await
+            .unwrap()
+            ._0;
+        assert_eq!(balance1, parse_ether("6").unwrap());
+        assert_eq!(balance2, parse_ether("20").unwrap());
+
+        // a zero deposit reverts the whole batch and is reported on its own
+        let args = VaultBatchArgs {
+            items: vec![
+                VaultBatchItem::Withdraw(WithdrawArgs {
+                    token: token1,
+                    vault_id: U256::from(1),
+                    target_amount: parse_ether("1").unwrap(),
+                }),
+                VaultBatchItem::Deposit(DepositArgs {
+                    token: token2,
+                    vault_id: U256::from(2),
+                    amount: U256::ZERO,
+                }),
+            ],
+        };
+        let results = args.execute(tx_args, |_| {}).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], VaultBatchItemResult::Failed(_)));
+        assert!(matches!(results[1], VaultBatchItemResult::Reverted(_)));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::frontmatter::parse_frontmatter;
+use alloy::primitives::{hex::encode_prefixed, keccak256, Address, U256};
+use alloy::sol_types::SolValue;
+use rain_orderbook_app_settings::{
+    config::{Config, ParseConfigSourceError},
+    deployment::{Deployment, VaultIdMode},
+};
+use rain_orderbook_subgraph_client::{
+    types::common::Bytes, OrderbookSubgraphClient, OrderbookSubgraphClientError,
+};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VaultIdLookupError {
+    #[error(transparent)]
+    ParseConfigSourceError(#[from] ParseConfigSourceError),
+    #[error(transparent)]
+    OrderbookSubgraphClientError(#[from] OrderbookSubgraphClientError),
+    #[error("Deployment {0} not found")]
+    DeploymentNotFound(String),
+}
+
+/// Derive the vault id of a token for a deployment and owner, ie
+/// `keccak256(abi.encode(owner, deploymentName, token))`
+pub fn derive_vault_id(owner: Address, deployment_name: &str, token: Address) -> U256 {
+    let encoded = (owner, deployment_name.to_string(), token).abi_encode_params();
+    U256::from_be_bytes(keccak256(encoded).0)
+}
+
+/// A vault of a deployment, with its balance if it exists on the subgraph
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeploymentVault {
+    pub deployment: String,
+    pub token: Address,
+    pub vault_id: U256,
+    pub balance: Option<U256>,
+}
+
+/// Vault ids of a deployment's inputs and outputs for the given owner, ie the
+/// ids set on the order or derived ones in deterministic mode. Random vault
+/// ids can not be reconstructed and are left out.
+pub fn deployment_vault_ids(deployment: &Deployment, owner: Address) -> Vec<(Address, U256)> {
+    let mut vault_ids: Vec<(Address, U256)> = vec![];
+    for io in deployment
+        .order
+        .inputs
+        .iter()
+        .chain(deployment.order.outputs.iter())
+    {
+        let vault_id = match (io.vault_id, deployment.vault_id_mode.unwrap_or_default()) {
+            (Some(vault_id), _) => vault_id,
+            (None, VaultIdMode::Deterministic) => {
+                derive_vault_id(owner, &deployment.name, io.token.address)
+            }
+            (None, VaultIdMode::Random) => continue,
+        };
+        if !vault_ids.contains(&(io.token.address, vault_id)) {
+            vault_ids.push((io.token.address, vault_id));
+        }
+    }
+    vault_ids
+}
+
+/// Reconstruct the vault ids of the deployments of a .rain file for an owner
+/// and fetch their balances from the subgraph. Looks up all deployments if
+/// none is given.
+pub async fn lookup_deployment_vaults(
+    dotrain: String,
+    deployment: Option<String>,
+    owner: Address,
+    client: &OrderbookSubgraphClient,
+) -> Result<Vec<DeploymentVault>, VaultIdLookupError> {
+    let config: Config = parse_frontmatter(dotrain).await?.try_into()?;
+    let mut deployments = match deployment {
+        Some(name) => vec![config
+            .deployments
+            .get(&name)
+            .ok_or(VaultIdLookupError::DeploymentNotFound(name))?
+            .clone()],
+        None => config.deployments.values().cloned().collect(),
+    };
+    deployments.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let vaults = client
+        .owners_vaults_list_all(vec![Bytes(encode_prefixed(owner))])
+        .await?;
+
+    let mut result = vec![];
+    for deployment in deployments {
+        for (token, vault_id) in deployment_vault_ids(&deployment, owner) {
+            let balance = vaults
+                .iter()
+                .find(|v| {
+                    Address::from_str(&v.token.address.0).ok() == Some(token)
+                        && U256::from_str(&v.vault_id.0).ok() == Some(vault_id)
+                })
+                .and_then(|v| U256::from_str(&v.balance.0).ok());
+            result.push(DeploymentVault {
+                deployment: deployment.name.clone(),
+                token,
+                vault_id,
+                balance,
+            });
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rain_orderbook_app_settings::{
+        deployer::Deployer,
+        network::Network,
+        order::{Order, OrderIO},
+        scenario::Scenario,
+        token::Token,
+    };
+    use std::{collections::HashMap, sync::Arc};
+
+    fn token(address: Address) -> Arc<Token> {
+        Arc::new(Token {
+            network: Arc::new(Network::dummy()),
+            address,
+            decimals: Some(18),
+            label: None,
+            symbol: None,
+        })
+    }
+
+    fn deployment(vault_id_mode: Option<VaultIdMode>) -> Deployment {
+        Deployment {
+            name: "some-deployment".to_string(),
+            scenario: Arc::new(Scenario {
+                name: "some-scenario".to_string(),
+                bindings: HashMap::new(),
+                runs: None,
+                blocks: None,
+                deployer: Arc::new(Deployer::dummy()),
+            }),
+            order: Arc::new(Order {
+                inputs: vec![OrderIO {
+                    token: token(Address::repeat_byte(1)),
+                    vault_id: None,
+                }],
+                outputs: vec![
+                    OrderIO {
+                        token: token(Address::repeat_byte(2)),
+                        vault_id: None,
+                    },
+                    OrderIO {
+                        token: token(Address::repeat_byte(3)),
+                        vault_id: Some(U256::from(5)),
+                    },
+                ],
+                network: Arc::new(Network::dummy()),
+                deployer: None,
+                orderbook: None,
+            }),
+            deposits: None,
+            vault_id_mode,
+        }
+    }
+
+    #[test]
+    fn test_derive_vault_id() {
+        let owner = Address::repeat_byte(0xaa);
+        let token = Address::repeat_byte(1);
+        let vault_id = derive_vault_id(owner, "some-deployment", token);
+
+        assert_eq!(vault_id, derive_vault_id(owner, "some-deployment", token));
+        assert_ne!(vault_id, derive_vault_id(owner, "other-deployment", token));
+        assert_ne!(
+            vault_id,
+            derive_vault_id(Address::repeat_byte(0xbb), "some-deployment", token)
+        );
+        assert_ne!(
+            vault_id,
+            derive_vault_id(owner, "some-deployment", Address::repeat_byte(2))
+        );
+    }
+
+    #[test]
+    fn test_deployment_vault_ids() {
+        let owner = Address::repeat_byte(0xaa);
+
+        let result = deployment_vault_ids(&deployment(None), owner);
+        assert_eq!(result, vec![(Address::repeat_byte(3), U256::from(5))]);
+
+        let result = deployment_vault_ids(&deployment(Some(VaultIdMode::Deterministic)), owner);
+        assert_eq!(
+            result,
+            vec![
+                (
+                    Address::repeat_byte(1),
+                    derive_vault_id(owner, "some-deployment", Address::repeat_byte(1))
+                ),
+                (
+                    Address::repeat_byte(2),
+                    derive_vault_id(owner, "some-deployment", Address::repeat_byte(2))
+                ),
+                (Address::repeat_byte(3), U256::from(5)),
+            ]
+        );
+    }
+}
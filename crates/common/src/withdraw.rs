@@ -1,10 +1,14 @@
 #[cfg(not(target_family = "wasm"))]
+use crate::simulation::{
+    resolve_sender, simulate, SimulationCall, SimulationError, SimulationReport,
+};
+#[cfg(not(target_family = "wasm"))]
 use crate::transaction::TransactionArgs;
 use crate::transaction::WritableTransactionExecuteError;
 use alloy::primitives::{Address, U256};
 use alloy::sol_types::SolCall;
 #[cfg(not(target_family = "wasm"))]
-use alloy_ethers_typecast::transaction::{WriteTransaction, WriteTransactionStatus};
+use alloy_ethers_typecast::transaction::WriteTransactionStatus;
 use rain_orderbook_bindings::IOrderBookV4::withdraw2Call;
 use serde::{Deserialize, Serialize};
 
@@ -34,20 +38,35 @@ impl WithdrawArgs {
         transaction_args: TransactionArgs,
         transaction_status_changed: S,
     ) -> Result<(), WritableTransactionExecuteError> {
-        let ledger_client = transaction_args.clone().try_into_ledger_client().await?;
+        let signer = transaction_args.clone().try_into_signer().await?;
 
         let withdraw_call: withdraw2Call = self.clone().into();
         let params = transaction_args
             .try_into_write_contract_parameters(withdraw_call, transaction_args.orderbook_address)
             .await?;
 
-        WriteTransaction::new(ledger_client.client, params, 4, transaction_status_changed)
-            .execute()
-            .await?;
+        signer.execute(params, transaction_status_changed).await?;
 
         Ok(())
     }
 
+    /// Simulate withdrawing on a fork of the network
+    #[cfg(not(target_family = "wasm"))]
+    pub async fn simulate(
+        &self,
+        transaction_args: TransactionArgs,
+        from: Option<Address>,
+    ) -> Result<SimulationReport, SimulationError> {
+        let from = resolve_sender(&transaction_args, from).await?;
+        let orderbook = transaction_args.orderbook_address;
+        let calls = vec![SimulationCall::new(
+            from,
+            orderbook,
+            withdraw2Call::from(self.clone()),
+        )];
+        simulate(&transaction_args, calls, &[self.token], &[from, orderbook]).await
+    }
+
     pub async fn get_withdraw_calldata(&self) -> Result<Vec<u8>, WritableTransactionExecuteError> {
         let withdraw_call: withdraw2Call = self.clone().into();
         Ok(withdraw_call.abi_encode())
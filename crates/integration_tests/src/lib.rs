@@ -82,7 +82,7 @@ amount price: get("amount") 52;
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
@@ -180,7 +180,7 @@ amount price: get("amount") 52;
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
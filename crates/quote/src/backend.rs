@@ -0,0 +1,269 @@
+use crate::{
+    error::{Error, FailedQuote},
+    quote::{QuoteResult, QuoteTarget},
+};
+use alloy::primitives::{Address, Bytes, U64};
+use alloy_ethers_typecast::transaction::{ReadContractParameters, ReadableClient};
+use clap::ValueEnum;
+use futures::{stream, StreamExt};
+use rain_orderbook_bindings::IOrderBookV4::quoteCall;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_family = "wasm"))]
+use crate::rpc::decode_quote_return;
+#[cfg(not(target_family = "wasm"))]
+use alloy::{
+    primitives::{hex::encode_prefixed, U256},
+    sol_types::SolCall,
+};
+#[cfg(not(target_family = "wasm"))]
+use rain_error_decoding::AbiDecodedErrorType;
+#[cfg(not(target_family = "wasm"))]
+use rain_interpreter_eval::fork::{Forker, NewForkedEvm};
+
+/// How quotes are read from the chain
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteBackend {
+    /// Multicalls through Multicall3
+    #[default]
+    Multicall,
+    /// An eth_call per quote, for chains without Multicall3
+    EthCall,
+    /// Calls on a local fork of the rpc, after the fork calls if any
+    #[cfg(not(target_family = "wasm"))]
+    Fork,
+}
+
+/// A call committed to the fork before quoting with the fork backend, eg a
+/// deposit that is yet to happen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForkCall {
+    pub from: Address,
+    pub to: Address,
+    pub calldata: Bytes,
+}
+
+/// Quotes the targets with an eth_call each, with up to the given number of
+/// calls in flight at once. A call that reverts gets its decoded revert as
+/// error, same as with multicalls. Errors if the rpc fails.
+pub async fn eth_call_quote(
+    quote_targets: &[QuoteTarget],
+    rpc: &str,
+    block_number: Option<u64>,
+    concurrency: usize,
+) -> Result<Vec<QuoteResult>, Error> {
+    let client = ReadableClient::new_from_url(rpc.to_string())?;
+    stream::iter(quote_targets)
+        .map(|quote_target| {
+            let client = &client;
+            async move {
+                let quote = client
+                    .read(ReadContractParameters {
+                        address: quote_target.orderbook,
+                        block_number: block_number.map(U64::from),
+                        call: quoteCall {
+                            quoteConfig: quote_target.quote_config.clone(),
+                        },
+                    })
+                    .await
+                    .map_err(Error::from);
+                match quote {
+                    Ok(quote) if quote.exists => Ok(Ok(quote.into())),
+                    Ok(_) => Ok(Err(FailedQuote::NonExistent)),
+                    Err(e) if e.is_revert() => Ok(Err(FailedQuote::from_call_error(e))),
+                    Err(e) => Err(e),
+                }
+            }
+        })
+        .buffered(concurrency.max(1))
+        .collect::<Vec<Result<QuoteResult, Error>>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+/// Quotes the targets on a local fork of the rpc at the given block, after
+/// committing the fork calls in order. Errors if a fork call reverts.
+#[cfg(not(target_family = "wasm"))]
+pub async fn fork_quote(
+    quote_targets: &[QuoteTarget],
+    rpc: &str,
+    block_number: Option<u64>,
+    fork_calls: &[ForkCall],
+) -> Result<Vec<QuoteResult>, Error> {
+    let mut forker = Forker::new_with_fork(
+        NewForkedEvm {
+            fork_url: rpc.to_string(),
+            fork_block_number: block_number,
+        },
+        None,
+        None,
+    )
+    .await?;
+
+    for (i, fork_call) in fork_calls.iter().enumerate() {
+        let res = forker.call_committing(
+            fork_call.from.as_slice(),
+            fork_call.to.as_slice(),
+            &fork_call.calldata,
+            U256::ZERO,
+        )?;
+        if res.exit_reason.is_revert() {
+            let reason = match AbiDecodedErrorType::selector_registry_abi_decode(&res.result).await
+            {
+                Ok(decoded) => decoded.to_string(),
+                Err(_) => encode_prefixed(&res.result),
+            };
+            return Err(Error::ForkCallReverted(i, reason));
+        }
+    }
+
+    let mut result = vec![];
+    for quote_target in quote_targets {
+        let res = forker.call(
+            Address::default().as_slice(),
+            quote_target.orderbook.as_slice(),
+            &quoteCall {
+                quoteConfig: quote_target.quote_config.clone(),
+            }
+            .abi_encode(),
+        )?;
+        result.push(decode_quote_return(!res.exit_reason.is_revert(), &res.result).await);
+    }
+    Ok(result)
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderQuoteValue;
+    use alloy_ethers_typecast::rpc::Response;
+    use httpmock::{Method::POST, MockServer};
+    use rain_orderbook_test_fixtures::LocalEvm;
+    use serde_json::{from_str, json, Value};
+
+    #[tokio::test]
+    async fn test_eth_call_quote() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/rpc").body_contains("eth_call");
+            then.json_body_obj(
+                &from_str::<Value>(
+                    &Response::new_success(
+                        1,
+                        encode_prefixed(quoteCall::abi_encode_returns(&(
+                            true,
+                            U256::from(1),
+                            U256::from(2),
+                        )))
+                        .as_str(),
+                    )
+                    .to_json_string()
+                    .unwrap(),
+                )
+                .unwrap(),
+            );
+        });
+
+        let result = eth_call_quote(
+            &[QuoteTarget::default(), QuoteTarget::default()],
+            rpc_server.url("/rpc").as_str(),
+            None,
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 2);
+        for quote in result {
+            assert_eq!(
+                quote.unwrap(),
+                OrderQuoteValue {
+                    max_output: U256::from(1),
+                    ratio: U256::from(2),
+                }
+            );
+        }
+
+        // reverts are decoded per target
+        let revert_server = MockServer::start_async().await;
+        revert_server.mock(|when, then| {
+            when.method(POST).path("/rpc").body_contains("eth_call");
+            then.json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {
+                    "code": 3,
+                    "message": "execution reverted",
+                    "data": encode_prefixed([0xde, 0xad, 0xbe, 0xef]),
+                },
+            }));
+        });
+        let result = eth_call_quote(
+            &[QuoteTarget::default(), QuoteTarget::default()],
+            revert_server.url("/rpc").as_str(),
+            None,
+            2,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 2);
+        for quote in result {
+            assert!(matches!(
+                quote,
+                Err(FailedQuote::RevertError(_)) | Err(FailedQuote::RevertErrorDecodeFailed(_))
+            ));
+        }
+
+        // errors when the rpc fails
+        let err = eth_call_quote(
+            &[QuoteTarget::default()],
+            rpc_server.url("/bad").as_str(),
+            None,
+            2,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::RpcCallError(_)));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    async fn test_fork_quote() {
+        let local_evm = LocalEvm::new().await;
+        let orderbook = *local_evm.orderbook.address();
+        let quote_target = QuoteTarget {
+            orderbook,
+            ..Default::default()
+        };
+
+        let result = fork_quote(&[quote_target.clone()], &local_evm.url(), None, &[])
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(matches!(result[0], Err(FailedQuote::NonExistent)));
+
+        // a reverting fork call fails the whole quote
+        let fork_calls = vec![ForkCall {
+            from: Address::repeat_byte(0x03),
+            to: orderbook,
+            calldata: Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]),
+        }];
+        let err = fork_quote(&[quote_target], &local_evm.url(), None, &fork_calls)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ForkCallReverted(0, _)));
+    }
+
+    #[test]
+    fn test_fork_call_json() {
+        let fork_call: ForkCall = from_str(&format!(
+            r#"{{"from": "{}", "to": "{}", "calldata": "0x1234"}}"#,
+            Address::repeat_byte(0x01),
+            Address::repeat_byte(0x02)
+        ))
+        .unwrap();
+        assert_eq!(fork_call.to, Address::repeat_byte(0x02));
+        assert_eq!(fork_call.calldata, Bytes::from(vec![0x12, 0x34]));
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{
+    backend::{ForkCall, QuoteBackend},
+    rpc::BatchQuoteConfig,
+};
+use clap::Args;
+
+/// Cli args of a [BatchQuoteConfig]
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct CliBatchQuoteConfig {
+    /// Max number of quotes in one multicall
+    #[arg(long, value_name = "INTEGER", default_value_t = BatchQuoteConfig::default().chunk_size)]
+    pub chunk_size: usize,
+
+    /// Max estimated gas of one multicall
+    #[arg(long, value_name = "INTEGER", default_value_t = BatchQuoteConfig::default().chunk_gas)]
+    pub chunk_gas: u64,
+
+    /// Max number of multicalls, or of eth_calls with the eth-call backend,
+    /// in flight at once
+    #[arg(long, value_name = "INTEGER", default_value_t = BatchQuoteConfig::default().concurrency)]
+    pub concurrency: usize,
+
+    /// How quotes are read from the chain
+    #[arg(long, value_enum, default_value_t = QuoteBackend::Multicall)]
+    pub backend: QuoteBackend,
+
+    /// Call to commit on the fork before quoting with the fork backend as
+    /// json `{"from": ..., "to": ..., "calldata": ...}`, eg a deposit that is
+    /// yet to happen, can be given multiple times and runs in order
+    #[arg(long = "fork-call", value_name = "JSON", value_parser = parse_fork_call)]
+    pub fork_calls: Vec<ForkCall>,
+}
+
+impl Default for CliBatchQuoteConfig {
+    fn default() -> Self {
+        let config = BatchQuoteConfig::default();
+        CliBatchQuoteConfig {
+            chunk_size: config.chunk_size,
+            chunk_gas: config.chunk_gas,
+            concurrency: config.concurrency,
+            backend: config.backend,
+            fork_calls: config.fork_calls,
+        }
+    }
+}
+
+impl From<CliBatchQuoteConfig> for BatchQuoteConfig {
+    fn from(value: CliBatchQuoteConfig) -> Self {
+        BatchQuoteConfig {
+            chunk_size: value.chunk_size,
+            chunk_gas: value.chunk_gas,
+            concurrency: value.concurrency,
+            backend: value.backend,
+            fork_calls: value.fork_calls,
+        }
+    }
+}
+
+/// Parses a [ForkCall] from its json
+pub fn parse_fork_call(value: &str) -> Result<ForkCall, String> {
+    serde_json::from_str(value).map_err(|e| e.to_string())
+}
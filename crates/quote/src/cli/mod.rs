@@ -1,12 +1,25 @@
-use crate::{OrderQuoteValue, QuoteResult};
-use alloy::primitives::Address;
+use crate::{
+    batch_quote_found_targets,
+    rpc::{batch_quote_with_config, BatchQuoteConfig},
+    signed_context::SignedContextSource,
+    OrderQuoteValue, QuoteResult,
+};
+use alloy::primitives::{Address, B256, U256};
 use clap::{command, ArgAction, Parser};
+use rain_orderbook_app_settings::blocks::Blocks;
+use rain_orderbook_bindings::IOrderBookV4::SignedContextV1;
 use serde::{Deserialize, Serialize};
 use std::{fs::write, io::Write, path::PathBuf};
 use url::Url;
 
+mod batch_config;
 mod input;
+mod series;
+mod watch;
+pub use batch_config::*;
 pub use input::*;
+pub use series::*;
+pub use watch::*;
 
 /// Rain orderbook Quoter CLI app entrypoint sruct
 #[derive(Parser, Debug, Clone, PartialEq)]
@@ -51,6 +64,66 @@ pub struct Quoter {
     /// Pretty format the result
     #[arg(short, long, action = ArgAction::SetTrue)]
     pub pretty: bool,
+
+    /// Signed context to quote all inputs with as json, either a signed
+    /// context or `{"signerKey": ..., "context": [...]}` to sign the context
+    /// values with, can be given multiple times
+    #[arg(long, value_name = "JSON", value_parser = parse_signed_context)]
+    pub signed_context: Vec<SignedContextSource>,
+
+    /// Private key to sign the `--context` values with
+    #[arg(long, env, value_name = "HEX_STRING", hide_env_values = true)]
+    pub signer_key: Option<B256>,
+
+    /// Context values to sign with `--signer-key` and quote all inputs with
+    #[arg(long, value_name = "INTEGER", num_args = 1.., requires = "signer_key")]
+    pub context: Option<Vec<U256>>,
+
+    /// Keep quoting at new blocks and output the quotes that changed as
+    /// newline delimited json
+    #[arg(long, action = ArgAction::SetTrue, conflicts_with = "block_number")]
+    pub watch: bool,
+
+    /// Number of new blocks between quotes in watch mode
+    #[arg(long, value_name = "INTEGER", default_value_t = 1)]
+    pub watch_every: u64,
+
+    /// Websocket RPC URL to subscribe to new blocks from in watch mode,
+    /// new blocks are polled from the rpc if not given or not reachable
+    #[arg(long, env, value_name = "URL", hide_env_values = true)]
+    pub ws_rpc: Option<Url>,
+
+    /// Seconds between polls for new blocks in watch mode
+    #[arg(long, value_name = "INTEGER", default_value_t = 4)]
+    pub poll_interval: u64,
+
+    /// Quote at each of a range of blocks instead, given as yaml the same
+    /// as a scenario's blocks, eg `[-7d..]` or `{range: [-7d..], interval: 6h}`
+    #[arg(
+        long,
+        value_name = "YAML",
+        value_parser = parse_blocks,
+        conflicts_with_all = ["block_number", "watch"]
+    )]
+    pub blocks: Option<Blocks>,
+
+    /// Output the quotes over `--blocks` as csv instead of json
+    #[arg(long, action = ArgAction::SetTrue, requires = "blocks")]
+    pub csv: bool,
+
+    // how quotes are read and split into multicalls
+    #[command(flatten)]
+    pub batch_config: CliBatchQuoteConfig,
+}
+
+/// Parses [Blocks] from their yaml
+pub fn parse_blocks(value: &str) -> Result<Blocks, String> {
+    serde_yaml::from_str(value).map_err(|e| e.to_string())
+}
+
+/// Parses a [SignedContextSource] from its json
+pub fn parse_signed_context(value: &str) -> Result<SignedContextSource, String> {
+    serde_json::from_str(value).map_err(|e| e.to_string())
 }
 
 /// A serializable/deserializable struct that bridges [QuoteResult] for cli
@@ -86,48 +159,120 @@ impl From<Vec<QuoteResult>> for QuoterResult {
 }
 
 impl Quoter {
+    /// The [BatchQuoteConfig] of the options of self
+    pub fn batch_quote_config(&self) -> BatchQuoteConfig {
+        self.batch_config.clone().into()
+    }
+
+    /// Signed context of the options of self, with the `--context` values
+    /// signed if given
+    pub fn signed_context(&self) -> anyhow::Result<Vec<SignedContextV1>> {
+        let mut sources = self.signed_context.clone();
+        if let (Some(signer_key), Some(context)) = (self.signer_key, &self.context) {
+            sources.push(SignedContextSource::Unsigned {
+                signer_key,
+                context: context.clone(),
+            });
+        }
+        Ok(sources
+            .into_iter()
+            .map(SignedContextSource::resolve)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+
+    fn subgraph_url(&self) -> anyhow::Result<&str> {
+        self.subgraph
+            .as_ref()
+            .map(|v| v.as_str())
+            .ok_or(anyhow::anyhow!(
+                "requires '--subgraph' url to read orders details from"
+            ))
+    }
+
     /// Executes the CLI call based on the given options of self
     pub async fn run(&self) -> anyhow::Result<QuoterResult> {
-        let result: QuoterResult = match self.input.read_content()? {
-            InputContentType::Target(v) => v
-                .do_quote(self.rpc.as_str(), self.block_number, self.multicall_address)
-                .await?
-                .into(),
-            InputContentType::Spec(v) => {
-                if let Some(sg) = &self.subgraph {
-                    v.do_quote(
-                        sg.as_str(),
-                        self.rpc.as_str(),
-                        self.block_number,
-                        self.multicall_address,
-                    )
-                    .await?
-                    .into()
-                } else {
-                    return Err(anyhow::anyhow!(
-                        "requires '--subgraph' url to read orders details from"
-                    ));
+        let mut content = self.input.read_content()?;
+        let signed_context = self.signed_context()?;
+        if !signed_context.is_empty() {
+            match &mut content {
+                InputContentType::Target(v) => {
+                    for target in &mut v.0 {
+                        target.quote_config.signedContext = signed_context.clone();
+                    }
+                }
+                InputContentType::Spec(v) => {
+                    for spec in &mut v.0 {
+                        spec.signed_context = signed_context.clone();
+                    }
                 }
             }
-        };
+        }
 
-        if !self.no_stdout || self.output.is_some() {
-            let stringified_result = if self.pretty {
-                serde_json::to_string_pretty::<QuoterResult>(&result)?
-            } else {
-                serde_json::to_string::<QuoterResult>(&result)?
+        if self.watch || self.blocks.is_some() {
+            // subgraph specs are resolved once for all the blocks
+            let targets = match content {
+                InputContentType::Target(v) => v.0.into_iter().map(Some).collect(),
+                InputContentType::Spec(v) => {
+                    v.get_batch_quote_target_from_subgraph(self.subgraph_url()?)
+                        .await?
+                }
             };
-            if !self.no_stdout {
-                let mut stdout = std::io::stdout().lock();
-                stdout.write_all(stringified_result.as_bytes())?;
-            }
-            if let Some(v) = &self.output {
-                write(v, stringified_result)?;
+            if let Some(blocks) = &self.blocks {
+                return self.quote_series(&targets, blocks).await;
             }
+            return self.watch(targets).await;
         }
 
+        let result: QuoterResult = match content {
+            InputContentType::Target(v) => batch_quote_with_config(
+                &v.0,
+                self.rpc.as_str(),
+                self.block_number,
+                self.multicall_address,
+                &self.batch_quote_config(),
+            )
+            .await?
+            .into(),
+            InputContentType::Spec(v) => {
+                let targets = v
+                    .get_batch_quote_target_from_subgraph(self.subgraph_url()?)
+                    .await?;
+                batch_quote_found_targets(
+                    &targets,
+                    self.rpc.as_str(),
+                    self.block_number,
+                    self.multicall_address,
+                    &self.batch_quote_config(),
+                )
+                .await?
+                .into()
+            }
+        };
+
+        self.write_output(&self.stringify(&result)?)?;
         Ok(result)
     }
+
+    /// Json of the given value, pretty if set so
+    fn stringify<T: Serialize>(&self, value: &T) -> anyhow::Result<String> {
+        Ok(if self.pretty {
+            serde_json::to_string_pretty(value)?
+        } else {
+            serde_json::to_string(value)?
+        })
+    }
+
+    /// Writes the output to stdout and the output file as set
+    fn write_output(&self, output: &str) -> anyhow::Result<()> {
+        if !self.no_stdout {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(output.as_bytes())?;
+        }
+        if let Some(v) = &self.output {
+            write(v, output)?;
+        }
+        Ok(())
+    }
 }
 
 /// The main entrypoint for this crate's cli
@@ -140,7 +285,12 @@ pub async fn main() -> anyhow::Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{error::FailedQuote, BatchQuoteSpec, QuoteSpec};
+    use crate::{
+        backend::{ForkCall, QuoteBackend},
+        error::FailedQuote,
+        signed_context::sign_context,
+        BatchQuoteSpec, QuoteSpec,
+    };
     use alloy::primitives::{hex::encode_prefixed, keccak256, B256, U256};
     use alloy::sol_types::{SolCall, SolValue};
     use alloy_ethers_typecast::{multicall::IMulticall3::Result as MulticallResult, rpc::Response};
@@ -250,6 +400,95 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn test_cli_blocks_args() {
+        let args = |extra: &[&str]| {
+            let mut args = vec![
+                "cmd".to_string(),
+                "--rpc".to_string(),
+                "https://rpc.com".to_string(),
+                "--target".to_string(),
+                encode_prefixed(Address::random().0),
+                "0".to_string(),
+                "0".to_string(),
+                encode_prefixed(OrderV3::default().abi_encode()),
+            ];
+            args.extend(extra.iter().map(|v| v.to_string()));
+            args
+        };
+
+        let cli = Quoter::try_parse_from(args(&[
+            "--blocks",
+            "{range: [-7d..], interval: 6h}",
+            "--csv",
+        ]))
+        .unwrap();
+        assert_eq!(
+            cli.blocks,
+            Some(parse_blocks("range: [-7d..]\ninterval: 6h").unwrap())
+        );
+        assert!(cli.csv);
+
+        assert!(Quoter::try_parse_from(args(&["--csv"])).is_err());
+        assert!(Quoter::try_parse_from(args(&["--blocks", "[10..]", "--watch"])).is_err());
+        assert!(Quoter::try_parse_from(args(&["--blocks", "[yesterday..]"])).is_err());
+    }
+
+    #[test]
+    fn test_cli_backend_args() {
+        let args = |extra: &[&str]| {
+            let mut args = vec![
+                "cmd".to_string(),
+                "--rpc".to_string(),
+                "https://rpc.com".to_string(),
+                "--target".to_string(),
+                encode_prefixed(Address::random().0),
+                "0".to_string(),
+                "0".to_string(),
+                encode_prefixed(OrderV3::default().abi_encode()),
+            ];
+            args.extend(extra.iter().map(|v| v.to_string()));
+            args
+        };
+
+        let cli = Quoter::try_parse_from(args(&[])).unwrap();
+        assert_eq!(cli.batch_quote_config(), BatchQuoteConfig::default());
+
+        let cli = Quoter::try_parse_from(args(&["--backend", "eth-call"])).unwrap();
+        assert_eq!(cli.batch_config.backend, QuoteBackend::EthCall);
+
+        let from = Address::random();
+        let to = Address::random();
+        let fork_call = format!(
+            r#"{{"from": "{}", "to": "{}", "calldata": "0x1234"}}"#,
+            from, to
+        );
+        let cli = Quoter::try_parse_from(args(&[
+            "--backend",
+            "fork",
+            "--fork-call",
+            &fork_call,
+            "--fork-call",
+            &fork_call,
+        ]))
+        .unwrap();
+        assert_eq!(cli.batch_config.backend, QuoteBackend::Fork);
+        assert_eq!(
+            cli.batch_config.fork_calls,
+            vec![
+                ForkCall {
+                    from,
+                    to,
+                    calldata: vec![0x12, 0x34].into(),
+                };
+                2
+            ]
+        );
+
+        assert!(Quoter::try_parse_from(args(&["--backend", "multicall4"])).is_err());
+        assert!(Quoter::try_parse_from(args(&["--fork-call", "0x1234"])).is_err());
+    }
+
     #[tokio::test]
     async fn test_run_err() {
         let cli = Quoter {
@@ -260,6 +499,16 @@ mod tests {
             multicall_address: None,
             no_stdout: true,
             pretty: true,
+            signed_context: vec![],
+            signer_key: None,
+            context: None,
+            watch: false,
+            watch_every: 1,
+            ws_rpc: None,
+            poll_interval: 4,
+            blocks: None,
+            csv: false,
+            batch_config: CliBatchQuoteConfig::default(),
             input: Input {
                 target: None,
                 spec: None,
@@ -276,6 +525,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cli_signed_context_args() {
+        let signer_key = B256::repeat_byte(0x11);
+        let signed = sign_context(&B256::repeat_byte(0x22), vec![U256::from(3)]).unwrap();
+        let cli = Quoter::try_parse_from(vec![
+            "cmd",
+            "--rpc",
+            "https://rpc.com",
+            "--target",
+            &encode_prefixed(Address::random().0),
+            "0",
+            "0",
+            &encode_prefixed(OrderV3::default().abi_encode()),
+            "--signed-context",
+            &serde_json::to_string(&signed).unwrap(),
+            "--signer-key",
+            &signer_key.to_string(),
+            "--context",
+            "1",
+            "0x02",
+        ])
+        .unwrap();
+
+        let result = cli.signed_context().unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0], signed);
+        assert_eq!(
+            result[1],
+            sign_context(&signer_key, vec![U256::from(1), U256::from(2)]).unwrap()
+        );
+
+        // context values cannot be signed without a key
+        assert!(Quoter::try_parse_from(vec![
+            "cmd",
+            "--rpc",
+            "https://rpc.com",
+            "--spec",
+            &encode_prefixed(Address::random().0),
+            "0",
+            "0",
+            &U256::from(1).to_string(),
+            "--context",
+            "1",
+        ])
+        .is_err());
+    }
+
     #[tokio::test]
     async fn test_run_ok_spec_inputs() {
         let rpc_server = MockServer::start_async().await;
@@ -400,6 +696,16 @@ mod tests {
             multicall_address: None,
             no_stdout: true,
             pretty: false,
+            signed_context: vec![],
+            signer_key: None,
+            context: None,
+            watch: false,
+            watch_every: 1,
+            ws_rpc: None,
+            poll_interval: 4,
+            blocks: None,
+            csv: false,
+            batch_config: CliBatchQuoteConfig::default(),
             input: Input {
                 target: None,
                 spec: None,
@@ -434,6 +740,16 @@ mod tests {
             multicall_address: None,
             no_stdout: true,
             pretty: false,
+            signed_context: vec![],
+            signer_key: None,
+            context: None,
+            watch: false,
+            watch_every: 1,
+            ws_rpc: None,
+            poll_interval: 4,
+            blocks: None,
+            csv: false,
+            batch_config: CliBatchQuoteConfig::default(),
             input: Input {
                 target: None,
                 input: None,
@@ -474,6 +790,16 @@ mod tests {
             multicall_address: None,
             no_stdout: false,
             pretty: false,
+            signed_context: vec![],
+            signer_key: None,
+            context: None,
+            watch: false,
+            watch_every: 1,
+            ws_rpc: None,
+            poll_interval: 4,
+            blocks: None,
+            csv: false,
+            batch_config: CliBatchQuoteConfig::default(),
             input: Input {
                 input: None,
                 spec: None,
@@ -0,0 +1,65 @@
+use super::{Quoter, QuoterResult};
+use crate::{
+    series::{QuoteSeries, QuoteSeriesPoint},
+    QuoteTarget,
+};
+use rain_orderbook_app_settings::blocks::Blocks;
+use serde::{Deserialize, Serialize};
+
+/// A serializable/deserializable struct that bridges [QuoteSeriesPoint] for
+/// cli output, same as [QuoterResult]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoterSeriesPoint {
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    pub timestamp_error: Option<String>,
+    pub quotes: QuoterResult,
+}
+
+impl From<QuoteSeriesPoint> for QuoterSeriesPoint {
+    fn from(value: QuoteSeriesPoint) -> Self {
+        Self {
+            block_number: value.block_number,
+            timestamp: value.timestamp.as_ref().ok().copied(),
+            timestamp_error: value.timestamp.as_ref().err().map(|e| e.to_string()),
+            quotes: value.quotes.into(),
+        }
+    }
+}
+
+impl Quoter {
+    /// Quotes the given targets at each of the blocks and outputs the series
+    /// as json or csv, returns the quotes at the last block
+    pub async fn quote_series(
+        &self,
+        targets: &[Option<QuoteTarget>],
+        blocks: &Blocks,
+    ) -> anyhow::Result<QuoterResult> {
+        let series = QuoteSeries::quote(
+            targets,
+            blocks,
+            self.rpc.as_str(),
+            self.multicall_address,
+            &self.batch_quote_config(),
+        )
+        .await?;
+
+        let csv = if self.csv {
+            Some(series.to_csv()?)
+        } else {
+            None
+        };
+        let mut points: Vec<QuoterSeriesPoint> = series.0.into_iter().map(Into::into).collect();
+        let output = match csv {
+            Some(v) => v,
+            None => self.stringify(&points)?,
+        };
+        self.write_output(&output)?;
+
+        Ok(points
+            .pop()
+            .map(|v| v.quotes)
+            .unwrap_or(QuoterResult(vec![])))
+    }
+}
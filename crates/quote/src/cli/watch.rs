@@ -0,0 +1,280 @@
+use super::{Quoter, QuoterResult, QuoterResultInner};
+use crate::{batch_quote_found_targets, QuoteTarget};
+use alloy::providers::{Provider, ProviderBuilder, RootProvider, WsConnect};
+use alloy::pubsub::PubSubFrontend;
+use alloy_ethers_typecast::transaction::ReadableClient;
+use futures::{stream::BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{fs::OpenOptions, io::Write, time::Duration};
+use tracing::warn;
+use url::Url;
+
+/// A quote that changed in watch mode, written out as a line of json
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteUpdate {
+    pub block_number: u64,
+    /// Index of the quote in the input
+    pub index: usize,
+    pub quote: QuoterResultInner,
+}
+
+/// Source of new block numbers in watch mode, the headers of a websocket
+/// subscription or polls of the latest block number of an rpc. Polling takes
+/// over if the subscription ends, and failed polls are retried.
+pub struct NewBlocks {
+    /// The provider is kept to keep the subscription alive
+    subscription: Option<(
+        RootProvider<PubSubFrontend>,
+        BoxStream<'static, Option<u64>>,
+    )>,
+    client: ReadableClient,
+    interval: Duration,
+    last: Option<u64>,
+}
+
+impl NewBlocks {
+    /// Subscribes to new blocks on the websocket rpc if given, and polls the
+    /// latest block of the rpc otherwise or if subscribing fails
+    pub async fn new(
+        ws_rpc: Option<&Url>,
+        rpc: &str,
+        poll_interval: Duration,
+    ) -> anyhow::Result<Self> {
+        let subscription = match ws_rpc {
+            Some(ws_rpc) => match Self::subscribe(ws_rpc).await {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    warn!(
+                        "Failed to subscribe to new blocks on {}, polling instead: {}",
+                        ws_rpc, e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        Ok(NewBlocks {
+            subscription,
+            client: ReadableClient::new_from_url(rpc.to_string())?,
+            interval: poll_interval,
+            last: None,
+        })
+    }
+
+    async fn subscribe(
+        ws_rpc: &Url,
+    ) -> anyhow::Result<(
+        RootProvider<PubSubFrontend>,
+        BoxStream<'static, Option<u64>>,
+    )> {
+        let provider = ProviderBuilder::new()
+            .on_ws(WsConnect::new(ws_rpc.as_str()))
+            .await?;
+        let stream = provider
+            .subscribe_blocks()
+            .await?
+            .into_stream()
+            .map(|block| block.header.number)
+            .boxed();
+        Ok((provider, stream))
+    }
+
+    /// Waits for the next new block number
+    pub async fn next(&mut self) -> u64 {
+        if let Some((_, stream)) = &mut self.subscription {
+            while let Some(number) = stream.next().await {
+                if let Some(number) = number {
+                    self.last = Some(number);
+                    return number;
+                }
+            }
+            warn!("Subscription to new blocks ended, polling instead");
+            self.subscription = None;
+        }
+        loop {
+            match self.client.get_block_number().await {
+                Ok(number) if self.last.map_or(true, |v| number > v) => {
+                    self.last = Some(number);
+                    return number;
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Failed to poll the latest block, retrying: {}", e),
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+/// Quote results that differ from the last ones at the same index, the last
+/// ones are updated in place
+pub fn changed_quotes(
+    last: &mut [Option<QuoterResultInner>],
+    results: &QuoterResult,
+    block_number: u64,
+) -> Vec<QuoteUpdate> {
+    let mut updates = vec![];
+    for (index, (result, previous)) in results.0.iter().zip(last.iter_mut()).enumerate() {
+        if previous.as_ref() != Some(result) {
+            *previous = Some(result.clone());
+            updates.push(QuoteUpdate {
+                block_number,
+                index,
+                quote: result.clone(),
+            });
+        }
+    }
+    updates
+}
+
+impl Quoter {
+    /// Quotes the given targets at every `--watch-every` new blocks and
+    /// writes the quotes that changed as newline delimited json, until it is
+    /// stopped. Failed quote calls are logged and retried at the next block.
+    pub async fn watch(&self, targets: Vec<Option<QuoteTarget>>) -> anyhow::Result<QuoterResult> {
+        let mut blocks = NewBlocks::new(
+            self.ws_rpc.as_ref(),
+            self.rpc.as_str(),
+            Duration::from_secs(self.poll_interval),
+        )
+        .await?;
+        let mut output = match &self.output {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+
+        let mut last = vec![None; targets.len()];
+        let mut next_block = 0;
+        loop {
+            let block_number = blocks.next().await;
+            if block_number < next_block {
+                continue;
+            }
+            next_block = block_number + self.watch_every.max(1);
+
+            let result: QuoterResult = match batch_quote_found_targets(
+                &targets,
+                self.rpc.as_str(),
+                Some(block_number),
+                self.multicall_address,
+                &self.batch_quote_config(),
+            )
+            .await
+            {
+                Ok(v) => v.into(),
+                Err(e) => {
+                    warn!("Failed to quote at block {}: {}", block_number, e);
+                    continue;
+                }
+            };
+            for update in changed_quotes(&mut last, &result, block_number) {
+                let line = serde_json::to_string(&update)?;
+                if !self.no_stdout {
+                    let mut stdout = std::io::stdout().lock();
+                    writeln!(stdout, "{}", line)?;
+                    stdout.flush()?;
+                }
+                if let Some(file) = &mut output {
+                    writeln!(file, "{}", line)?;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderQuoteValue;
+    use alloy::primitives::U256;
+    use alloy_ethers_typecast::rpc::Response;
+    use httpmock::{Method::POST, MockServer};
+
+    #[test]
+    fn test_changed_quotes() {
+        let ok = QuoterResultInner::Ok(OrderQuoteValue::default());
+        let other = QuoterResultInner::Ok(OrderQuoteValue {
+            max_output: U256::from(1),
+            ratio: U256::from(2),
+        });
+        let err = QuoterResultInner::Error("Order does not exist".to_string());
+        let mut last = vec![None, None];
+
+        let updates = changed_quotes(&mut last, &QuoterResult(vec![ok.clone(), err.clone()]), 1);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[1].index, 1);
+        assert_eq!(updates[1].quote, err);
+
+        // unchanged quotes are left out
+        let updates = changed_quotes(&mut last, &QuoterResult(vec![ok.clone(), err.clone()]), 2);
+        assert!(updates.is_empty());
+
+        let updates = changed_quotes(&mut last, &QuoterResult(vec![other.clone(), err]), 3);
+        assert_eq!(
+            updates,
+            vec![QuoteUpdate {
+                block_number: 3,
+                index: 0,
+                quote: other.clone(),
+            }]
+        );
+        assert_eq!(last[0], Some(other));
+    }
+
+    #[test]
+    fn test_quote_update_json() {
+        let update = QuoteUpdate {
+            block_number: 10,
+            index: 0,
+            quote: QuoterResultInner::Error("Order does not exist".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_string(&update).unwrap(),
+            r#"{"blockNumber":10,"index":0,"quote":{"status":"error","message":"Order does not exist"}}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poll_new_blocks() {
+        let rpc_server = MockServer::start_async().await;
+        // polls fail at first and are retried
+        let mut failing = rpc_server.mock(|when, then| {
+            when.method(POST).path("/rpc");
+            then.status(500);
+        });
+
+        // unreachable websocket rpc falls back to polling
+        let ws_rpc = Url::parse("ws://127.0.0.1:1").unwrap();
+        let mut blocks = NewBlocks::new(
+            Some(&ws_rpc),
+            &rpc_server.url("/rpc"),
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap();
+        assert!(blocks.subscription.is_none());
+
+        let next = tokio::spawn(async move {
+            let number = blocks.next().await;
+            (blocks, number)
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(failing.hits() > 0);
+        failing.delete();
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/rpc");
+            then.json_body_obj(
+                &serde_json::from_str::<serde_json::Value>(
+                    &Response::new_success(1, "0x10").to_json_string().unwrap(),
+                )
+                .unwrap(),
+            );
+        });
+        let (mut blocks, number) = next.await.unwrap();
+        assert_eq!(number, 16);
+
+        // the same block is not returned twice
+        let next = tokio::time::timeout(Duration::from_millis(100), blocks.next()).await;
+        assert!(next.is_err());
+    }
+}
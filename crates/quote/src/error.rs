@@ -1,6 +1,8 @@
 use alloy::primitives::{hex::FromHexError, U256};
 use alloy_ethers_typecast::transaction::ReadableClientError;
 use rain_error_decoding::{AbiDecodeFailedErrors, AbiDecodedErrorType};
+use rain_orderbook_app_settings::blocks::BlockError;
+use rain_orderbook_app_settings::rpc::RpcRequestError;
 use rain_orderbook_subgraph_client::{
     types::order_detail_traits::OrderDetailError, OrderbookSubgraphClientError,
 };
@@ -20,6 +22,10 @@ pub enum FailedQuote {
     CorruptReturnData(String),
     #[error(transparent)]
     RevertErrorDecodeFailed(#[from] AbiDecodeFailedErrors),
+    #[error("Quote call failed: {0}")]
+    CallFailed(String),
+    #[error("Quote call returned no result")]
+    MissingResult,
     #[cfg(target_family = "wasm")]
     #[error(transparent)]
     SerdeWasmBindgenError(#[from] serde_wasm_bindgen::Error),
@@ -44,6 +50,62 @@ pub enum Error {
     SerdeWasmBindgenError(#[from] serde_wasm_bindgen::Error),
     #[error("Invalid quote target: index {0} is out of bounds for this Order")]
     InvalidQuoteTarget(U256),
+    #[error("Invalid signer key: {0}")]
+    InvalidSignerKey(String),
+    #[error(transparent)]
+    BlockError(#[from] BlockError),
+    #[error(transparent)]
+    CsvError(#[from] csv::Error),
+    #[error("Fork call {0} reverted: {1}")]
+    ForkCallReverted(usize, String),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    SignerError(#[from] alloy::signers::Error),
+    #[cfg(not(target_family = "wasm"))]
+    #[error(transparent)]
+    ForkCallError(#[from] rain_interpreter_eval::error::ForkCallError),
+}
+
+impl FailedQuote {
+    /// The quote error of a call that failed, its revert if it reverted
+    pub fn from_call_error(error: Error) -> Self {
+        match error {
+            Error::RpcCallError(ReadableClientError::AbiDecodedErrorType(e)) => {
+                FailedQuote::RevertError(e)
+            }
+            Error::RpcCallError(ReadableClientError::AbiDecodeFailedErrors(e)) => {
+                FailedQuote::RevertErrorDecodeFailed(e)
+            }
+            e => FailedQuote::CallFailed(e.to_string()),
+        }
+    }
+}
+
+impl Error {
+    /// Whether the error is the call reverting, rather than a failure to make
+    /// the call
+    pub fn is_revert(&self) -> bool {
+        matches!(
+            self,
+            Error::RpcCallError(
+                ReadableClientError::AbiDecodedErrorType(_)
+                    | ReadableClientError::AbiDecodeFailedErrors(_)
+            )
+        )
+    }
+}
+
+impl RpcRequestError for Error {
+    fn is_rpc_failure(&self) -> bool {
+        match self {
+            Error::RpcCallError(e) => e.is_rpc_failure(),
+            _ => false,
+        }
+    }
+
+    fn no_rpc_urls() -> Self {
+        ReadableClientError::no_rpc_urls().into()
+    }
 }
 
 #[cfg(target_family = "wasm")]
@@ -198,16 +198,22 @@ pub struct BatchOrderQuotesResponse {
     pub error: Option<String>,
 }
 
-/// Get the quote for an order
+/// Get the quote for an order, optionally with signed context
 /// Resolves with a BatchOrderQuotesResponse object
 #[wasm_bindgen(js_name = "getOrderQuote")]
 pub async fn get_order_quote(
     order: Vec<Order>,
     rpc_url: &str,
     block_number: Option<u64>,
+    signed_context: Option<Vec<SignedContextV1>>,
 ) -> Result<JsValue, Error> {
+    let signed_context = signed_context
+        .unwrap_or_default()
+        .into_iter()
+        .map(Into::into)
+        .collect();
     Ok(to_value(
-        &get_order_quotes(order, block_number, rpc_url.to_string())
+        &get_order_quotes(order, block_number, rpc_url.to_string(), signed_context)
             .await
             .map(|v| {
                 v.into_iter()
@@ -1,3 +1,4 @@
+pub mod backend;
 #[cfg(not(target_family = "wasm"))]
 pub mod cli;
 pub mod error;
@@ -5,6 +6,9 @@ mod quote;
 #[cfg(not(target_family = "wasm"))]
 mod quote_debug;
 pub mod rpc;
+pub mod series;
+#[cfg(not(target_family = "wasm"))]
+pub mod signed_context;
 
 #[cfg(target_family = "wasm")]
 pub mod js_api;
@@ -1,13 +1,10 @@
 use std::str::FromStr;
 
-use crate::{
-    error::Error,
-    quote::{BatchQuoteTarget, QuoteTarget},
-    OrderQuoteValue,
-};
+use crate::{error::Error, quote::QuoteTarget, rpc::batch_quote_with_failover, OrderQuoteValue};
 use alloy::primitives::{Address, U256};
 use alloy_ethers_typecast::transaction::ReadableClient;
-use rain_orderbook_bindings::IOrderBookV4::{OrderV3, Quote};
+use rain_orderbook_app_settings::rpc::with_rpc_failover;
+use rain_orderbook_bindings::IOrderBookV4::{OrderV3, Quote, SignedContextV1};
 use rain_orderbook_subgraph_client::types::common::Order;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
@@ -35,6 +32,18 @@ pub async fn get_order_quotes(
     orders: Vec<Order>,
     block_number: Option<u64>,
     rpc_url: String,
+    signed_context: Vec<SignedContextV1>,
+) -> Result<Vec<BatchOrderQuotesResponse>, Error> {
+    get_order_quotes_with_failover(orders, block_number, vec![rpc_url], signed_context).await
+}
+
+/// Quotes all pairs of the given orders with the given signed context,
+/// falling back on the next rpc url if a request fails
+pub async fn get_order_quotes_with_failover(
+    orders: Vec<Order>,
+    block_number: Option<u64>,
+    rpcs: Vec<String>,
+    signed_context: Vec<SignedContextV1>,
 ) -> Result<Vec<BatchOrderQuotesResponse>, Error> {
     let mut results: Vec<BatchOrderQuotesResponse> = Vec::new();
 
@@ -78,7 +87,7 @@ pub async fn get_order_quotes(
                         order: order_struct.clone(),
                         inputIOIndex: U256::from(input_index),
                         outputIOIndex: U256::from(output_index),
-                        signedContext: vec![],
+                        signedContext: signed_context.clone(),
                     },
                 };
 
@@ -93,15 +102,22 @@ pub async fn get_order_quotes(
             }
         }
 
-        let req_block_number = block_number.unwrap_or(
-            ReadableClient::new_from_url(rpc_url.clone())?
-                .get_block_number()
-                .await?,
-        );
+        let req_block_number = match block_number {
+            Some(block_number) => block_number,
+            None => {
+                with_rpc_failover(&rpcs, |rpc| async move {
+                    Ok::<_, Error>(
+                        ReadableClient::new_from_url(rpc)?
+                            .get_block_number()
+                            .await?,
+                    )
+                })
+                .await?
+            }
+        };
 
-        let quote_values = BatchQuoteTarget(quote_targets)
-            .do_quote(&rpc_url, Some(req_block_number), None)
-            .await;
+        let quote_values =
+            batch_quote_with_failover(&quote_targets, &rpcs, Some(req_block_number), None).await;
 
         if let Ok(quote_values) = quote_values {
             for (quote_value_result, pair) in quote_values.into_iter().zip(pairs) {
@@ -232,7 +248,7 @@ amount price: context<3 0>() context<4 0>();
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
@@ -315,7 +331,7 @@ amount price: context<3 0>() context<4 0>();
             trades: vec![],
         };
 
-        let result = get_order_quotes(vec![order], None, local_evm.url())
+        let result = get_order_quotes(vec![order], None, local_evm.url(), vec![])
             .await
             .unwrap();
 
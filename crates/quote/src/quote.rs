@@ -1,6 +1,6 @@
 use crate::{
     error::{Error, FailedQuote},
-    rpc::batch_quote,
+    rpc::{batch_quote, batch_quote_with_config, BatchQuoteConfig},
 };
 use alloy::primitives::{
     hex::{decode, encode_prefixed},
@@ -236,33 +236,59 @@ impl BatchQuoteSpec {
         let opts_quote_targets = self
             .get_batch_quote_target_from_subgraph(subgraph_url)
             .await?;
+        batch_quote_found_targets(
+            &opts_quote_targets,
+            rpc_url,
+            block_number,
+            multicall_address,
+            &BatchQuoteConfig::default(),
+        )
+        .await
+    }
+}
 
-        // quote the valid quote targets
-        let quote_targets: Vec<QuoteTarget> = opts_quote_targets
-            .iter()
-            .filter_map(|v| v.clone())
-            .collect();
-        let mut quote_results = VecDeque::from(
-            batch_quote(&quote_targets, rpc_url, block_number, multicall_address).await?,
-        );
-
-        // fill the array with quote results and invalid quote targets following
-        // their original order
-        let mut result = vec![];
-        opts_quote_targets.iter().for_each(|v| {
-            if v.is_some() {
-                result.push(
-                    quote_results
-                        .pop_front()
-                        .unwrap_or(Err(FailedQuote::NonExistent)),
-                );
-            } else {
-                result.push(Err(FailedQuote::NonExistent))
-            }
-        });
+/// Quotes the given quote targets, those that are None, ie were not found,
+/// are excluded from quoting and their place in the result is left as
+/// [FailedQuote::NonExistent]
+pub async fn batch_quote_found_targets(
+    opts_quote_targets: &[Option<QuoteTarget>],
+    rpc_url: &str,
+    block_number: Option<u64>,
+    multicall_address: Option<Address>,
+    config: &BatchQuoteConfig,
+) -> Result<Vec<QuoteResult>, Error> {
+    // quote the valid quote targets
+    let quote_targets: Vec<QuoteTarget> = opts_quote_targets
+        .iter()
+        .filter_map(|v| v.clone())
+        .collect();
+    let mut quote_results = VecDeque::from(
+        batch_quote_with_config(
+            &quote_targets,
+            rpc_url,
+            block_number,
+            multicall_address,
+            config,
+        )
+        .await?,
+    );
+
+    // fill the array with quote results and invalid quote targets following
+    // their original order
+    let mut result = vec![];
+    opts_quote_targets.iter().for_each(|v| {
+        if v.is_some() {
+            result.push(
+                quote_results
+                    .pop_front()
+                    .unwrap_or(Err(FailedQuote::NonExistent)),
+            );
+        } else {
+            result.push(Err(FailedQuote::NonExistent))
+        }
+    });
 
-        Ok(result)
-    }
+    Ok(result)
 }
 
 #[cfg(not(target_family = "wasm"))]
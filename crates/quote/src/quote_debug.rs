@@ -154,7 +154,7 @@ amount price: 16 52;
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
@@ -273,7 +273,7 @@ _: 1;
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
@@ -1,4 +1,5 @@
 use crate::{
+    backend::{eth_call_quote, ForkCall, QuoteBackend},
     error::{Error, FailedQuote},
     quote::{QuoteResult, QuoteTarget},
 };
@@ -11,58 +12,285 @@ use alloy_ethers_typecast::{
     },
     transaction::{ReadContractParameters, ReadableClient},
 };
+use futures::{stream, StreamExt};
 use rain_error_decoding::AbiDecodedErrorType;
+use rain_orderbook_app_settings::rpc::with_rpc_failover;
 use rain_orderbook_bindings::IOrderBookV4::quoteCall;
+use std::ops::Range;
+use tracing::warn;
 
-/// Quotes array of given quote targets using the given rpc url
+/// Gas a quote is estimated to cost before its call data
+pub const QUOTE_BASE_GAS: u64 = 100_000;
+/// Gas a quote is estimated to cost per byte of its call data, which is
+/// mostly the order's bytecode
+pub const QUOTE_GAS_PER_BYTE: u64 = 100;
+
+/// How quotes are read, how quote targets are split into multicalls and how
+/// many calls are sent at once
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchQuoteConfig {
+    /// Max number of quotes in one multicall
+    pub chunk_size: usize,
+    /// Max estimated gas of one multicall
+    pub chunk_gas: u64,
+    /// Max number of multicalls, or of eth_calls with the eth-call backend,
+    /// in flight at once
+    pub concurrency: usize,
+    /// How quotes are read from the chain
+    pub backend: QuoteBackend,
+    /// Calls to commit on the fork before quoting with the fork backend, in
+    /// order, eg a deposit that is yet to happen
+    pub fork_calls: Vec<ForkCall>,
+}
+
+impl Default for BatchQuoteConfig {
+    fn default() -> Self {
+        BatchQuoteConfig {
+            chunk_size: 50,
+            chunk_gas: 25_000_000,
+            concurrency: 4,
+            backend: QuoteBackend::Multicall,
+            fork_calls: vec![],
+        }
+    }
+}
+
+impl BatchQuoteConfig {
+    /// Splits the targets into ranges of consecutive targets that each fit
+    /// in one multicall, a target that is too big alone gets its own range
+    pub fn chunks(&self, quote_targets: &[QuoteTarget]) -> Vec<Range<usize>> {
+        let mut chunks = vec![];
+        let mut start = 0;
+        let mut gas = 0;
+        for (i, quote_target) in quote_targets.iter().enumerate() {
+            let target_gas = estimate_quote_gas(quote_target);
+            if i > start && (i - start >= self.chunk_size || gas + target_gas > self.chunk_gas) {
+                chunks.push(start..i);
+                start = i;
+                gas = 0;
+            }
+            gas += target_gas;
+        }
+        if start < quote_targets.len() {
+            chunks.push(start..quote_targets.len());
+        }
+        chunks
+    }
+}
+
+/// Rough estimate of the gas of quoting the target
+pub fn estimate_quote_gas(quote_target: &QuoteTarget) -> u64 {
+    QUOTE_BASE_GAS + QUOTE_GAS_PER_BYTE * quote_call(quote_target).callData.len() as u64
+}
+
+fn quote_call(quote_target: &QuoteTarget) -> Call3 {
+    Call3 {
+        allowFailure: true,
+        target: quote_target.orderbook,
+        callData: quoteCall {
+            quoteConfig: quote_target.quote_config.clone(),
+        }
+        .abi_encode()
+        .into(),
+    }
+}
+
+/// Quotes array of given quote targets, falling back on the next rpc url
+/// if a request fails
+pub async fn batch_quote_with_failover(
+    quote_targets: &[QuoteTarget],
+    rpcs: &[String],
+    block_number: Option<u64>,
+    multicall_address: Option<Address>,
+) -> Result<Vec<QuoteResult>, Error> {
+    with_rpc_failover(rpcs, |rpc| async move {
+        batch_quote(quote_targets, &rpc, block_number, multicall_address).await
+    })
+    .await
+}
+
+/// Quotes array of given quote targets using the given rpc url, in
+/// multicalls split by the default [BatchQuoteConfig]
 pub async fn batch_quote(
     quote_targets: &[QuoteTarget],
     rpc: &str,
     block_number: Option<u64>,
     multicall_address: Option<Address>,
+) -> Result<Vec<QuoteResult>, Error> {
+    batch_quote_with_config(
+        quote_targets,
+        rpc,
+        block_number,
+        multicall_address,
+        &BatchQuoteConfig::default(),
+    )
+    .await
+}
+
+/// Quotes array of given quote targets using the given rpc url with the
+/// config's backend. With the multicall backend the targets are quoted in
+/// multicalls split by the config, a multicall that reverts is bisected until
+/// the targets that make it revert are found, those get their revert as error
+/// while the rest are quoted as usual. Errors if the rpc fails or if no
+/// multicall succeeds at all. The other backends ignore the multicall address
+/// and chunking. Results are in the order of the targets.
+pub async fn batch_quote_with_config(
+    quote_targets: &[QuoteTarget],
+    rpc: &str,
+    block_number: Option<u64>,
+    multicall_address: Option<Address>,
+    config: &BatchQuoteConfig,
+) -> Result<Vec<QuoteResult>, Error> {
+    match config.backend {
+        QuoteBackend::Multicall => {}
+        QuoteBackend::EthCall => {
+            return eth_call_quote(quote_targets, rpc, block_number, config.concurrency).await
+        }
+        #[cfg(not(target_family = "wasm"))]
+        QuoteBackend::Fork => {
+            return crate::backend::fork_quote(quote_targets, rpc, block_number, &config.fork_calls)
+                .await
+        }
+    }
+
+    let chunks = config.chunks(quote_targets);
+    let chunk_results: Vec<Result<Vec<QuoteResult>, Error>> = stream::iter(chunks.iter())
+        .map(|chunk| {
+            bisect_quote(
+                &quote_targets[chunk.clone()],
+                rpc,
+                block_number,
+                multicall_address,
+            )
+        })
+        .buffered(config.concurrency.max(1))
+        .collect()
+        .await;
+
+    // rpc failures are surfaced so the request can be retried on another rpc
+    if let Some(i) = chunk_results
+        .iter()
+        .position(|v| matches!(v, Err(e) if !e.is_revert()))
+    {
+        return Err(chunk_results.into_iter().nth(i).unwrap().unwrap_err());
+    }
+    if chunk_results.iter().all(|v| v.is_err()) {
+        if let Some(Err(e)) = chunk_results.into_iter().next() {
+            return Err(e);
+        }
+        return Ok(vec![]);
+    }
+    let mut result = vec![];
+    for (chunk, chunk_result) in chunks.into_iter().zip(chunk_results) {
+        match chunk_result {
+            Ok(v) => result.extend(v),
+            Err(e) if chunk.len() == 1 => result.push(Err(FailedQuote::from_call_error(e))),
+            Err(e) => result.extend(chunk.map(|_| Err(FailedQuote::CallFailed(e.to_string())))),
+        }
+    }
+    Ok(result)
+}
+
+/// Quotes the targets in one multicall, bisecting it if it reverts until the
+/// targets that make it revert are found. Errors if the rpc fails or if none
+/// of the bisected multicalls succeed.
+async fn bisect_quote(
+    quote_targets: &[QuoteTarget],
+    rpc: &str,
+    block_number: Option<u64>,
+    multicall_address: Option<Address>,
+) -> Result<Vec<QuoteResult>, Error> {
+    let first_error =
+        match multicall_quote(quote_targets, rpc, block_number, multicall_address).await {
+            Ok(v) => return Ok(v),
+            Err(e) if quote_targets.len() <= 1 || !e.is_revert() => return Err(e),
+            Err(e) => e,
+        };
+
+    let mut results: Vec<Option<QuoteResult>> = quote_targets.iter().map(|_| None).collect();
+    let mid = quote_targets.len() / 2;
+    let mut pending = vec![mid..quote_targets.len(), 0..mid];
+    let mut succeeded = false;
+    while let Some(range) = pending.pop() {
+        match multicall_quote(
+            &quote_targets[range.clone()],
+            rpc,
+            block_number,
+            multicall_address,
+        )
+        .await
+        {
+            Ok(v) => {
+                succeeded = true;
+                for (i, quote_result) in range.zip(v) {
+                    results[i] = Some(quote_result);
+                }
+            }
+            Err(e) if !e.is_revert() => return Err(e),
+            Err(_) if range.len() > 1 => {
+                let mid = range.start + range.len() / 2;
+                pending.push(mid..range.end);
+                pending.push(range.start..mid);
+            }
+            Err(e) => {
+                warn!("Quote of target {} reverted: {}", range.start, e);
+                results[range.start] = Some(Err(FailedQuote::from_call_error(e)));
+            }
+        }
+    }
+
+    if !succeeded {
+        return Err(first_error);
+    }
+    Ok(results
+        .into_iter()
+        .map(|v| v.unwrap_or(Err(FailedQuote::MissingResult)))
+        .collect())
+}
+
+/// Quotes the targets in a single multicall
+async fn multicall_quote(
+    quote_targets: &[QuoteTarget],
+    rpc: &str,
+    block_number: Option<u64>,
+    multicall_address: Option<Address>,
 ) -> Result<Vec<QuoteResult>, Error> {
     let client = ReadableClient::new_from_url(rpc.to_string())?;
     let parameters = ReadContractParameters {
         address: multicall_address.unwrap_or(Address::from_hex(MULTICALL3_ADDRESS).unwrap()),
         block_number: block_number.map(U64::from),
         call: aggregate3Call {
-            calls: quote_targets
-                .iter()
-                .map(|quote_target| Call3 {
-                    allowFailure: true,
-                    target: quote_target.orderbook,
-                    callData: quoteCall {
-                        quoteConfig: quote_target.quote_config.clone(),
-                    }
-                    .abi_encode()
-                    .into(),
-                })
-                .collect(),
+            calls: quote_targets.iter().map(quote_call).collect(),
         },
     };
     let multicall_result = client.read(parameters).await?;
 
     let mut result: Vec<QuoteResult> = vec![];
     for res in multicall_result.returnData {
-        if res.success {
-            match quoteCall::abi_decode_returns(&res.returnData, true) {
-                Ok(v) => {
-                    if v.exists {
-                        result.push(Ok(v.into()));
-                    } else {
-                        result.push(Err(FailedQuote::NonExistent));
-                    }
+        result.push(decode_quote_return(res.success, &res.returnData).await);
+    }
+    Ok(result)
+}
+
+/// Decodes the return data of a quote call, or its revert data if it failed
+pub(crate) async fn decode_quote_return(success: bool, return_data: &[u8]) -> QuoteResult {
+    if success {
+        match quoteCall::abi_decode_returns(return_data, true) {
+            Ok(v) => {
+                if v.exists {
+                    Ok(v.into())
+                } else {
+                    Err(FailedQuote::NonExistent)
                 }
-                Err(e) => result.push(Err(FailedQuote::CorruptReturnData(e.to_string()))),
-            }
-        } else {
-            match AbiDecodedErrorType::selector_registry_abi_decode(&res.returnData).await {
-                Ok(e) => result.push(Err(FailedQuote::RevertError(e))),
-                Err(e) => result.push(Err(FailedQuote::RevertErrorDecodeFailed(e))),
             }
+            Err(e) => Err(FailedQuote::CorruptReturnData(e.to_string())),
+        }
+    } else {
+        match AbiDecodedErrorType::selector_registry_abi_decode(return_data).await {
+            Ok(e) => Err(FailedQuote::RevertError(e)),
+            Err(e) => Err(FailedQuote::RevertErrorDecodeFailed(e)),
         }
     }
-    Ok(result)
 }
 
 #[cfg(not(target_family = "wasm"))]
@@ -77,8 +305,8 @@ mod tests {
         request_shim::{AlloyTransactionRequest, TransactionRequestShim},
         rpc::{eip2718::TypedTransaction, BlockNumber, Request, Response},
     };
-    use httpmock::{Method::POST, MockServer};
-    use serde_json::{from_str, Value};
+    use httpmock::{prelude::HttpMockRequest, Method::POST, MockServer};
+    use serde_json::{from_str, json, Value};
 
     #[tokio::test]
     async fn test_batch_quote() {
@@ -173,4 +401,166 @@ mod tests {
         );
         assert!(iter_result.next().is_none());
     }
+
+    #[tokio::test]
+    async fn test_batch_quote_surfaces_rpc_failures() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").matches(has_bad_target);
+            then.status(429);
+        });
+        mock_quotes(&rpc_server, 0x11, 1);
+        mock_quotes(&rpc_server, 0x33, 1);
+
+        // a failing request is not bisected, the rpc failure is returned so
+        // the quote can be retried on another rpc
+        let targets = vec![target(0x11), target(0xba), target(0x33)];
+        let config = BatchQuoteConfig {
+            chunk_size: 1,
+            ..Default::default()
+        };
+        let result =
+            batch_quote_with_config(&targets, rpc_server.url("/").as_str(), None, None, &config)
+                .await;
+        assert!(matches!(result, Err(e) if !e.is_revert()));
+
+        let failing_server = MockServer::start_async().await;
+        failing_server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(429);
+        });
+        let result = batch_quote_with_failover(
+            &targets[..1],
+            &[failing_server.url("/"), rpc_server.url("/")],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_ok());
+    }
+
+    fn target(orderbook_byte: u8) -> QuoteTarget {
+        QuoteTarget {
+            orderbook: Address::repeat_byte(orderbook_byte),
+            ..Default::default()
+        }
+    }
+
+    fn has_bad_target(req: &HttpMockRequest) -> bool {
+        String::from_utf8_lossy(req.body.as_deref().unwrap_or_default()).contains(&"ba".repeat(20))
+    }
+
+    fn has_no_bad_target(req: &HttpMockRequest) -> bool {
+        !has_bad_target(req)
+    }
+
+    fn mock_quotes(rpc_server: &MockServer, orderbook_byte: u8, count: usize) {
+        let response_data = (0..count)
+            .map(|_| MulticallResult {
+                success: true,
+                returnData: quoteCall::abi_encode_returns(&(true, U256::from(1), U256::from(2)))
+                    .into(),
+            })
+            .collect::<Vec<_>>()
+            .abi_encode();
+        rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .body_contains(format!("{:02x}", orderbook_byte).repeat(20))
+                .matches(has_no_bad_target);
+            then.json_body_obj(
+                &from_str::<Value>(
+                    &Response::new_success(1, encode_prefixed(response_data).as_str())
+                        .to_json_string()
+                        .unwrap(),
+                )
+                .unwrap(),
+            );
+        });
+    }
+
+    #[test]
+    fn test_batch_quote_config_chunks() {
+        let targets = vec![QuoteTarget::default(); 5];
+        let target_gas = estimate_quote_gas(&targets[0]);
+        assert!(target_gas > QUOTE_BASE_GAS);
+
+        let config = BatchQuoteConfig {
+            chunk_size: 2,
+            ..Default::default()
+        };
+        assert_eq!(config.chunks(&targets), vec![0..2, 2..4, 4..5]);
+
+        let config = BatchQuoteConfig {
+            chunk_gas: target_gas * 3,
+            ..Default::default()
+        };
+        assert_eq!(config.chunks(&targets), vec![0..3, 3..5]);
+
+        // a target above the gas limit alone still gets quoted
+        let config = BatchQuoteConfig {
+            chunk_gas: 1,
+            ..Default::default()
+        };
+        assert_eq!(config.chunks(&targets[..2]), vec![0..1, 1..2]);
+        assert!(config.chunks(&[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batch_quote_bisects_failed_chunks() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").matches(has_bad_target);
+            then.json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "error": {
+                    "code": 3,
+                    "message": "execution reverted",
+                    "data": encode_prefixed([0xde, 0xad, 0xbe, 0xef]),
+                },
+            }));
+        });
+        mock_quotes(&rpc_server, 0x11, 2);
+        mock_quotes(&rpc_server, 0x33, 1);
+
+        // the first chunk reverts as a whole and is bisected down to the bad
+        // target, the second chunk is quoted in one go
+        let targets = vec![target(0x11), target(0x11), target(0xba), target(0x33)];
+        let config = BatchQuoteConfig {
+            chunk_size: 3,
+            concurrency: 2,
+            ..Default::default()
+        };
+        let result =
+            batch_quote_with_config(&targets, rpc_server.url("/").as_str(), None, None, &config)
+                .await
+                .unwrap();
+
+        assert_eq!(result.len(), 4);
+        let quote = OrderQuoteValue {
+            max_output: U256::from(1),
+            ratio: U256::from(2),
+        };
+        assert_eq!(result[0].as_ref().unwrap(), &quote);
+        assert_eq!(result[1].as_ref().unwrap(), &quote);
+        assert!(matches!(
+            result[2],
+            Err(FailedQuote::RevertError(_)) | Err(FailedQuote::RevertErrorDecodeFailed(_))
+        ));
+        assert_eq!(result[3].as_ref().unwrap(), &quote);
+
+        // errors if nothing can be quoted
+        let result = batch_quote_with_config(
+            &[target(0xba), target(0xba)],
+            rpc_server.url("/").as_str(),
+            None,
+            None,
+            &config,
+        )
+        .await;
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,351 @@
+use crate::{
+    error::{Error, FailedQuote},
+    quote::{batch_quote_found_targets, QuoteResult, QuoteTarget},
+    rpc::BatchQuoteConfig,
+};
+use alloy::primitives::Address;
+use alloy_ethers_typecast::transaction::ReadableClient;
+use futures::{future::join, stream, StreamExt};
+use rain_orderbook_app_settings::blocks::{BlockError, BlockTimestamps, Blocks};
+use serde::Serialize;
+
+/// Quotes of a batch of targets at a block, the timestamp of the block is
+/// an error if it failed to be read
+#[derive(Debug)]
+pub struct QuoteSeriesPoint {
+    pub block_number: u64,
+    pub timestamp: Result<u64, BlockError>,
+    pub quotes: Vec<QuoteResult>,
+}
+
+/// Quotes of a batch of targets over a range of blocks, in block order
+#[derive(Debug, Default)]
+pub struct QuoteSeries(pub Vec<QuoteSeriesPoint>);
+
+/// A row of a quote series csv, one per target per block. The error is the
+/// one of the quote, or of reading the block timestamp if the quote is ok.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuoteSeriesRow {
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    pub index: usize,
+    pub max_output: Option<String>,
+    pub ratio: Option<String>,
+    pub error: Option<String>,
+}
+
+impl QuoteSeries {
+    /// Quotes the targets at each of the blocks, those that are None are
+    /// not quoted, same as [batch_quote_found_targets]. Up to the config's
+    /// concurrency of blocks are quoted at once, and the quotes of a block
+    /// that fails to be quoted are [FailedQuote::CallFailed]. A block whose
+    /// timestamp fails to be read is still quoted.
+    pub async fn quote(
+        opts_quote_targets: &[Option<QuoteTarget>],
+        blocks: &Blocks,
+        rpc_url: &str,
+        multicall_address: Option<Address>,
+        config: &BatchQuoteConfig,
+    ) -> Result<Self, Error> {
+        let latest_block = ReadableClient::new_from_url(rpc_url.to_string())?
+            .get_block_number()
+            .await?;
+        let mut timestamps = BlockTimestamps::new(rpc_url)?;
+        let block_numbers = blocks
+            .expand_to_block_numbers_with_timestamps(latest_block, &mut timestamps)
+            .await?;
+
+        // the timestamp of each block is read along with its quotes, reusing
+        // the ones read while expanding the blocks
+        let timestamps = &timestamps;
+        let points = stream::iter(block_numbers)
+            .map(|block_number| async move {
+                let (timestamp, quotes) = join(
+                    timestamps.read_timestamp(block_number),
+                    batch_quote_found_targets(
+                        opts_quote_targets,
+                        rpc_url,
+                        Some(block_number),
+                        multicall_address,
+                        config,
+                    ),
+                )
+                .await;
+                // a block that fails to be quoted gets a failed quote for each
+                // target and one whose timestamp fails to be read keeps the
+                // error, so that the rest of the series is still quoted
+                let quotes = quotes.unwrap_or_else(|e| {
+                    opts_quote_targets
+                        .iter()
+                        .map(|v| match v {
+                            Some(_) => Err(FailedQuote::CallFailed(e.to_string())),
+                            None => Err(FailedQuote::NonExistent),
+                        })
+                        .collect()
+                });
+                QuoteSeriesPoint {
+                    block_number,
+                    timestamp,
+                    quotes,
+                }
+            })
+            .buffered(config.concurrency.max(1))
+            .collect()
+            .await;
+
+        Ok(QuoteSeries(points))
+    }
+
+    /// Rows of the series for csv export
+    pub fn rows(&self) -> Vec<QuoteSeriesRow> {
+        let mut rows = vec![];
+        for point in &self.0 {
+            for (index, quote) in point.quotes.iter().enumerate() {
+                let (max_output, ratio, error) = match quote {
+                    Ok(v) => (
+                        Some(v.max_output.to_string()),
+                        Some(v.ratio.to_string()),
+                        None,
+                    ),
+                    Err(e) => (None, None, Some(e.to_string())),
+                };
+                rows.push(QuoteSeriesRow {
+                    block_number: point.block_number,
+                    timestamp: point.timestamp.as_ref().ok().copied(),
+                    index,
+                    max_output,
+                    ratio,
+                    error: error.or_else(|| point.timestamp.as_ref().err().map(|e| e.to_string())),
+                });
+            }
+        }
+        rows
+    }
+
+    /// The series as csv with a row per target per block, amounts are
+    /// decimal strings of their 18 decimals fixed point values
+    pub fn to_csv(&self) -> Result<String, Error> {
+        let mut csv_writer = csv::Writer::from_writer(vec![]);
+        for row in self.rows() {
+            csv_writer.serialize(row)?;
+        }
+        let bytes = csv_writer
+            .into_inner()
+            .map_err(|e| csv::Error::from(e.into_error()))?;
+        Ok(String::from_utf8_lossy(&bytes).to_string())
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderQuoteValue;
+    use alloy::primitives::{hex::encode_prefixed, U256};
+    use alloy::sol_types::{SolCall, SolValue};
+    use alloy_ethers_typecast::{multicall::IMulticall3::Result as MulticallResult, rpc::Response};
+    use httpmock::{Method::POST, MockServer};
+    use rain_orderbook_bindings::IOrderBookV4::quoteCall;
+    use serde_json::{json, Value};
+
+    fn rpc_success(result: &str) -> Value {
+        serde_json::from_str(&Response::new_success(1, result).to_json_string().unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_quote_series() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("eth_blockNumber");
+            then.json_body_obj(&rpc_success("0x10"));
+        });
+        for (block, timestamp) in [("0xa", 100), ("0xb", 112)] {
+            rpc_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/")
+                    .body_contains("eth_getBlockByNumber")
+                    .body_contains(format!("\"{}\"", block));
+                then.json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": { "timestamp": format!("{:#x}", timestamp) }
+                }));
+            });
+        }
+        let response_data = vec![MulticallResult {
+            success: true,
+            returnData: quoteCall::abi_encode_returns(&(true, U256::from(1), U256::from(2))).into(),
+        }]
+        .abi_encode();
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("82ad56cb");
+            then.json_body_obj(&rpc_success(encode_prefixed(response_data).as_str()));
+        });
+
+        let blocks: Blocks = serde_yaml::from_str("[10..11]").unwrap();
+        let series = QuoteSeries::quote(
+            &[Some(QuoteTarget::default()), None],
+            &blocks,
+            rpc_server.url("/").as_str(),
+            None,
+            &BatchQuoteConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(series.0.len(), 2);
+        assert_eq!(series.0[0].block_number, 10);
+        assert_eq!(series.0[0].timestamp, Ok(100));
+        assert_eq!(series.0[1].block_number, 11);
+        assert_eq!(series.0[1].timestamp, Ok(112));
+        assert_eq!(
+            series.0[1].quotes[0].as_ref().unwrap(),
+            &OrderQuoteValue {
+                max_output: U256::from(1),
+                ratio: U256::from(2),
+            }
+        );
+        assert!(matches!(
+            series.0[1].quotes[1],
+            Err(FailedQuote::NonExistent)
+        ));
+
+        assert_eq!(
+            series.to_csv().unwrap(),
+            "block_number,timestamp,index,max_output,ratio,error
+10,100,0,1,2,
+10,100,1,,,Order does not exist
+11,112,0,1,2,
+11,112,1,,,Order does not exist
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quote_series_failed_block() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("eth_blockNumber");
+            then.json_body_obj(&rpc_success("0x10"));
+        });
+        for (block, timestamp) in [("0xa", 100), ("0xb", 112)] {
+            rpc_server.mock(|when, then| {
+                when.method(POST)
+                    .path("/")
+                    .body_contains("eth_getBlockByNumber")
+                    .body_contains(format!("\"{}\"", block));
+                then.json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": { "timestamp": format!("{:#x}", timestamp) }
+                }));
+            });
+        }
+        let response_data = vec![MulticallResult {
+            success: true,
+            returnData: quoteCall::abi_encode_returns(&(true, U256::from(1), U256::from(2))).into(),
+        }]
+        .abi_encode();
+        rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .body_contains("82ad56cb")
+                .body_contains("\"0xa\"");
+            then.json_body_obj(&rpc_success(encode_prefixed(response_data).as_str()));
+        });
+        // quoting at block 11 fails
+        rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .body_contains("82ad56cb")
+                .body_contains("\"0xb\"");
+            then.status(500);
+        });
+
+        let blocks: Blocks = serde_yaml::from_str("[10..11]").unwrap();
+        let series = QuoteSeries::quote(
+            &[Some(QuoteTarget::default()), None],
+            &blocks,
+            rpc_server.url("/").as_str(),
+            None,
+            &BatchQuoteConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(series.0.len(), 2);
+        assert!(series.0[0].quotes[0].is_ok());
+        assert_eq!(series.0[1].block_number, 11);
+        assert_eq!(series.0[1].timestamp, Ok(112));
+        assert!(matches!(
+            series.0[1].quotes[0],
+            Err(FailedQuote::CallFailed(_))
+        ));
+        assert!(matches!(
+            series.0[1].quotes[1],
+            Err(FailedQuote::NonExistent)
+        ));
+
+        let rows = series.rows();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[2].block_number, 11);
+        assert!(rows[2].max_output.is_none());
+        assert!(rows[2].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_quote_series_failed_timestamp() {
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("eth_blockNumber");
+            then.json_body_obj(&rpc_success("0x10"));
+        });
+        // only the timestamp of block 10 can be read
+        rpc_server.mock(|when, then| {
+            when.method(POST)
+                .path("/")
+                .body_contains("eth_getBlockByNumber")
+                .body_contains("\"0xa\"");
+            then.json_body(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "result": { "timestamp": "0x64" }
+            }));
+        });
+        let response_data = vec![MulticallResult {
+            success: true,
+            returnData: quoteCall::abi_encode_returns(&(true, U256::from(1), U256::from(2))).into(),
+        }]
+        .abi_encode();
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("82ad56cb");
+            then.json_body_obj(&rpc_success(encode_prefixed(response_data).as_str()));
+        });
+
+        let blocks: Blocks = serde_yaml::from_str("[10..11]").unwrap();
+        let series = QuoteSeries::quote(
+            &[Some(QuoteTarget::default())],
+            &blocks,
+            rpc_server.url("/").as_str(),
+            None,
+            &BatchQuoteConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(series.0.len(), 2);
+        assert_eq!(series.0[0].timestamp, Ok(100));
+        assert!(matches!(
+            series.0[1].timestamp,
+            Err(BlockError::BlockTimestampError(11, _))
+        ));
+        assert!(series.0[1].quotes[0].is_ok());
+
+        let rows = series.rows();
+        assert_eq!(rows[0].timestamp, Some(100));
+        assert_eq!(rows[0].error, None);
+        assert_eq!(rows[1].timestamp, None);
+        assert_eq!(rows[1].max_output, Some("1".to_string()));
+        assert!(rows[1].error.is_some());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::error::Error;
+use alloy::primitives::{keccak256, B256, U256};
+use alloy::signers::{local::PrivateKeySigner, SignerSync};
+use rain_orderbook_bindings::IOrderBookV4::SignedContextV1;
+use serde::{Deserialize, Serialize};
+
+/// Signed context of a quote, either already signed or as context values
+/// with the private key to sign them with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SignedContextSource {
+    Signed(SignedContextV1),
+    #[serde(rename_all = "camelCase")]
+    Unsigned {
+        signer_key: B256,
+        context: Vec<U256>,
+    },
+}
+
+impl SignedContextSource {
+    /// Signs the context values if not signed yet
+    pub fn resolve(self) -> Result<SignedContextV1, Error> {
+        match self {
+            SignedContextSource::Signed(v) => Ok(v),
+            SignedContextSource::Unsigned {
+                signer_key,
+                context,
+            } => sign_context(&signer_key, context),
+        }
+    }
+}
+
+/// Signs context values the way the orderbook verifies signed context, ie
+/// an EIP-191 signature of the keccak256 of the packed context values
+pub fn sign_context(signer_key: &B256, context: Vec<U256>) -> Result<SignedContextV1, Error> {
+    let signer = PrivateKeySigner::from_bytes(signer_key)
+        .map_err(|e| Error::InvalidSignerKey(e.to_string()))?;
+    let hash = keccak256(
+        context
+            .iter()
+            .flat_map(|v| v.to_be_bytes::<32>())
+            .collect::<Vec<u8>>(),
+    );
+    let signature = signer.sign_message_sync(hash.as_slice())?;
+
+    // r, s and v as 27 or 28 which is what the orderbook's ecdsa recover takes
+    let mut bytes = Vec::with_capacity(65);
+    bytes.extend_from_slice(&signature.r().to_be_bytes::<32>());
+    bytes.extend_from_slice(&signature.s().to_be_bytes::<32>());
+    bytes.push(27 + signature.v().y_parity() as u8);
+
+    Ok(SignedContextV1 {
+        signer: signer.address(),
+        context,
+        signature: bytes.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Address, Signature};
+
+    #[test]
+    fn test_sign_context() {
+        let signer_key = B256::repeat_byte(0x11);
+        let context = vec![U256::from(1), U256::from(2), U256::MAX];
+        let signed = sign_context(&signer_key, context.clone()).unwrap();
+
+        let signer = PrivateKeySigner::from_bytes(&signer_key).unwrap();
+        assert_eq!(signed.signer, signer.address());
+        assert_eq!(signed.context, context);
+        assert_eq!(signed.signature.len(), 65);
+        assert!([27, 28].contains(&signed.signature[64]));
+
+        let mut packed = vec![];
+        for v in &context {
+            packed.extend_from_slice(&v.to_be_bytes::<32>());
+        }
+        let signature = Signature::try_from(signed.signature.as_ref()).unwrap();
+        assert_eq!(
+            signature
+                .recover_address_from_msg(keccak256(packed))
+                .unwrap(),
+            signed.signer
+        );
+    }
+
+    #[test]
+    fn test_sign_context_invalid_key() {
+        let err = sign_context(&B256::ZERO, vec![U256::from(1)]).unwrap_err();
+        assert!(matches!(err, Error::InvalidSignerKey(_)));
+    }
+
+    #[test]
+    fn test_signed_context_source_json() {
+        let source: SignedContextSource = serde_json::from_str(
+            r#"{"signerKey": "0x1111111111111111111111111111111111111111111111111111111111111111", "context": ["0x1", "0x2"]}"#,
+        )
+        .unwrap();
+        let signed = source.resolve().unwrap();
+        assert_eq!(signed.context, vec![U256::from(1), U256::from(2)]);
+
+        let json = serde_json::to_string(&signed).unwrap();
+        let source: SignedContextSource = serde_json::from_str(&json).unwrap();
+        assert_eq!(source, SignedContextSource::Signed(signed.clone()));
+        assert_eq!(source.resolve().unwrap(), signed);
+
+        let source: SignedContextSource = serde_json::from_str(&format!(
+            r#"{{"signer": "{}", "context": [], "signature": "0x1234"}}"#,
+            Address::repeat_byte(0x01)
+        ))
+        .unwrap();
+        assert!(matches!(source, SignedContextSource::Signed(_)));
+    }
+}
@@ -1,8 +1,18 @@
+use crate::schema::string_schema;
 use alloy::primitives::BlockNumber;
+use chrono::{DateTime, NaiveDate, SecondsFormat, TimeZone, Utc};
+use ethers::providers::{Http, Provider};
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{ArrayValidation, InstanceType, Schema, SchemaObject, SubschemaValidation},
+    JsonSchema,
+};
 use serde::{
     de::{self, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt;
 use thiserror::Error;
 use typeshare::typeshare;
@@ -14,16 +24,105 @@ pub enum Block {
     Number(BlockNumber),
     Genesis,
     Latest,
+    /// Block at a unix timestamp in seconds, the first one at or after it
+    /// as the start of a range and the last one at or before it as the end
+    #[typeshare(skip)]
+    Timestamp(u64),
+    /// Block at the given time before the latest block, resolved the same
+    /// as a timestamp
+    #[typeshare(skip)]
+    Ago(TimeInterval),
 }
 
 impl Block {
+    /// Block number of a block, time based blocks have to be resolved
+    /// against an rpc and are taken as the latest block here
     pub fn to_block_number(&self, latest_block: BlockNumber) -> BlockNumber {
         match self {
             Block::Number(n) => *n,
             Block::Genesis => 0,
-            Block::Latest => latest_block,
+            Block::Latest | Block::Timestamp(_) | Block::Ago(_) => latest_block,
         }
     }
+
+    pub fn is_time_based(&self) -> bool {
+        matches!(self, Block::Timestamp(_) | Block::Ago(_))
+    }
+}
+
+/// A duration in seconds, written as a number and a unit, eg `30s`, `15m`,
+/// `6h`, `7d` or `2w`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TimeInterval(pub u64);
+
+const TIME_UNITS: [(char, u64); 5] = [
+    ('w', 7 * 24 * 60 * 60),
+    ('d', 24 * 60 * 60),
+    ('h', 60 * 60),
+    ('m', 60),
+    ('s', 1),
+];
+
+impl std::str::FromStr for TimeInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid time interval: {}", s);
+        let unit = s.chars().last().ok_or_else(invalid)?;
+        let (_, seconds) = TIME_UNITS
+            .iter()
+            .find(|(v, _)| *v == unit)
+            .ok_or_else(invalid)?;
+        let count: u64 = s[..s.len() - 1].parse().map_err(|_| invalid())?;
+        count
+            .checked_mul(*seconds)
+            .map(TimeInterval)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for TimeInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, seconds) = TIME_UNITS
+            .iter()
+            .find(|(_, seconds)| self.0 > 0 && self.0 % seconds == 0)
+            .unwrap_or(&('s', 1));
+        write!(f, "{}{}", self.0 / seconds, unit)
+    }
+}
+
+impl Serialize for TimeInterval {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeInterval {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl JsonSchema for TimeInterval {
+    fn schema_name() -> String {
+        "TimeInterval".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema(
+            "^[0-9]+[smhdw]$",
+            "Duration as a number and a unit of s, m, h, d or w",
+        )
+        .into()
+    }
 }
 
 #[typeshare]
@@ -34,6 +133,10 @@ pub struct BlockRange {
 }
 
 impl BlockRange {
+    pub fn is_time_based(&self) -> bool {
+        self.start.is_time_based() || self.end.is_time_based()
+    }
+
     pub fn validate(&self, latest_block: BlockNumber) -> Result<(), BlockError> {
         let start = self.start.to_block_number(latest_block);
         let end = self.end.to_block_number(latest_block);
@@ -50,26 +153,24 @@ impl Serialize for BlockRange {
     where
         S: Serializer,
     {
-        let mut range_string = String::new();
-
-        match &self.start {
-            Block::Genesis => range_string.push_str(""),
-            Block::Latest => range_string.push_str(""),
-            Block::Number(n) => range_string.push_str(&n.to_string()),
-        }
-
-        range_string.push_str("..");
-
-        match &self.end {
-            Block::Genesis => range_string.push_str(""),
-            Block::Latest => range_string.push_str(""),
-            Block::Number(n) => range_string.push_str(&n.to_string()),
-        }
-
+        let range_string = format!("{}..{}", format_block(&self.start), format_block(&self.end));
         serializer.serialize_str(&range_string)
     }
 }
 
+fn format_block(block: &Block) -> String {
+    match block {
+        Block::Genesis | Block::Latest => String::new(),
+        Block::Number(n) => n.to_string(),
+        Block::Timestamp(timestamp) => Utc
+            .timestamp_opt(*timestamp as i64, 0)
+            .single()
+            .map(|v| v.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .unwrap_or_default(),
+        Block::Ago(interval) => format!("-{}", interval),
+    }
+}
+
 // Deserialize implementation for BlockRange
 impl<'de> Deserialize<'de> for BlockRange {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -80,6 +181,43 @@ impl<'de> Deserialize<'de> for BlockRange {
     }
 }
 
+impl JsonSchema for BlockRange {
+    fn schema_name() -> String {
+        "BlockRange".to_string()
+    }
+
+    // a range is a string like `100..200`, `100..` or `..200`, which yaml
+    // also allows to write in a flow sequence such as `[100..200]`, where
+    // either end can also be a date or a time before the latest block
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let block =
+            r"([0-9]*|-[0-9]+[smhdw]|[0-9]{4}-[0-9]{2}-[0-9]{2}(T[0-9:.]+(Z|[+-][0-9:]+))?)";
+        let pattern = &format!(r"^{}\.\.{}$", block, block);
+        let description = "Block range in the form a..b, a.. or ..b, where a and b are block numbers, dates such as 2024-01-31 or 2024-01-31T12:00:00Z, or times before the latest block such as -7d";
+        let sequence = SchemaObject {
+            instance_type: Some(InstanceType::Array.into()),
+            array: Some(Box::new(ArrayValidation {
+                items: Some(Schema::Object(string_schema(pattern, description)).into()),
+                min_items: Some(1),
+                max_items: Some(1),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    string_schema(pattern, description).into(),
+                    sequence.into(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
 struct BlockRangeVisitor;
 
 impl<'de> Visitor<'de> for BlockRangeVisitor {
@@ -115,21 +253,52 @@ fn parse_range(s: &str) -> Result<BlockRange, String> {
     if parts.len() == 2 {
         let start = match parts[0] {
             "" => Block::Genesis,
-            s => Block::Number(s.parse().map_err(|_| "Invalid block number")?),
+            s => parse_block(s)?,
         };
         let end = match parts[1] {
             "" => Block::Latest,
-            s => Block::Number(s.parse().map_err(|_| "Invalid block number")?),
+            s => parse_block(s)?,
         };
         return Ok(BlockRange { start, end });
     }
     Err(format!("Invalid range syntax: {}", s))
 }
 
-#[derive(Debug, PartialEq, Deserialize, Serialize, Clone)]
+/// Parse a block number, a date or rfc3339 date time, or a time before the
+/// latest block such as `-7d`
+fn parse_block(s: &str) -> Result<Block, String> {
+    if let Ok(n) = s.parse() {
+        return Ok(Block::Number(n));
+    }
+    if let Some(interval) = s.strip_prefix('-') {
+        return Ok(Block::Ago(interval.parse()?));
+    }
+    let timestamp = DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|v| v.timestamp())
+        .or_else(|| {
+            NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+                .map(|v| Utc.from_utc_datetime(&v).timestamp())
+        })
+        .ok_or("Invalid block number")?;
+    u64::try_from(timestamp)
+        .map(Block::Timestamp)
+        .map_err(|_| format!("Invalid block time: {}", s))
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Clone, JsonSchema)]
 #[serde(untagged)]
 pub enum Blocks {
-    RangeWithInterval { range: BlockRange, interval: u32 },
+    RangeWithInterval {
+        range: BlockRange,
+        interval: u32,
+    },
+    RangeWithTimeInterval {
+        range: BlockRange,
+        interval: TimeInterval,
+    },
     SimpleRange(BlockRange),
 }
 
@@ -137,13 +306,218 @@ pub enum Blocks {
 pub enum BlockError {
     #[error("Invalid block range")]
     InvalidBlockRange,
+    #[error("Time based block ranges have to be resolved against an rpc")]
+    UnresolvedTimeRange,
+    #[error("Failed to read the timestamp of block {0}: {1}")]
+    BlockTimestampError(BlockNumber, String),
+    #[error("Failed to create a client for the rpc: {0}")]
+    RpcClientError(String),
+}
+
+/// Reads block timestamps from the block headers of an rpc, remembering
+/// the ones it has read
+pub struct BlockTimestamps {
+    provider: Provider<Http>,
+    timestamps: HashMap<BlockNumber, u64>,
+}
+
+impl BlockTimestamps {
+    pub fn new(rpc_url: &str) -> Result<Self, BlockError> {
+        Ok(BlockTimestamps {
+            provider: Provider::<Http>::try_from(rpc_url)
+                .map_err(|e| BlockError::RpcClientError(e.to_string()))?,
+            timestamps: HashMap::new(),
+        })
+    }
+
+    pub async fn timestamp(&mut self, block: BlockNumber) -> Result<u64, BlockError> {
+        let timestamp = self.read_timestamp(block).await?;
+        self.timestamps.insert(block, timestamp);
+        Ok(timestamp)
+    }
+
+    /// Same as [BlockTimestamps::timestamp] without remembering the read
+    /// timestamp, so that timestamps of many blocks can be read at once
+    pub async fn read_timestamp(&self, block: BlockNumber) -> Result<u64, BlockError> {
+        if let Some(timestamp) = self.timestamps.get(&block) {
+            return Ok(*timestamp);
+        }
+        let error = |e: String| BlockError::BlockTimestampError(block, e);
+        let header: Value = self
+            .provider
+            .request("eth_getBlockByNumber", (format!("{:#x}", block), false))
+            .await
+            .map_err(|e| error(e.to_string()))?;
+        header["timestamp"]
+            .as_str()
+            .and_then(|v| u64::from_str_radix(v.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| error(format!("unexpected block header {}", header)))
+    }
+
+    /// Search for the first block between low and high with a timestamp at
+    /// or after the given time, or high if there is none. Steps back from
+    /// high in doubling steps before a binary search, so that recent times
+    /// only read recent blocks.
+    async fn first_block_at(
+        &mut self,
+        time: u64,
+        mut low: BlockNumber,
+        mut high: BlockNumber,
+    ) -> Result<BlockNumber, BlockError> {
+        let mut step = 1;
+        let mut bound = high;
+        while bound > low && self.timestamp(bound).await? >= time {
+            high = bound;
+            bound = high.saturating_sub(step).max(low);
+            step *= 2;
+        }
+        low = bound;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.timestamp(mid).await? < time {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Search for the last block between low and high with a timestamp at or
+    /// before the given time, or low if there is none
+    async fn last_block_at(
+        &mut self,
+        time: u64,
+        low: BlockNumber,
+        high: BlockNumber,
+    ) -> Result<BlockNumber, BlockError> {
+        let block = self
+            .first_block_at(time.saturating_add(1), low, high)
+            .await?;
+        if block > low && self.timestamp(block).await? > time {
+            Ok(block - 1)
+        } else {
+            Ok(block)
+        }
+    }
+
+    /// Block number of the start or end of a range, time based starts are
+    /// the first block at or after the time and time based ends the last
+    /// block at or before it
+    async fn block_number(
+        &mut self,
+        block: &Block,
+        is_end: bool,
+        latest_block: BlockNumber,
+    ) -> Result<BlockNumber, BlockError> {
+        let time = match block {
+            Block::Timestamp(timestamp) => *timestamp,
+            Block::Ago(interval) => self
+                .timestamp(latest_block)
+                .await?
+                .saturating_sub(interval.0),
+            block => return Ok(block.to_block_number(latest_block)),
+        };
+        if is_end {
+            self.last_block_at(time, 0, latest_block).await
+        } else {
+            self.first_block_at(time, 0, latest_block).await
+        }
+    }
 }
 
 impl Blocks {
+    fn range(&self) -> &BlockRange {
+        match self {
+            Blocks::RangeWithInterval { range, .. }
+            | Blocks::RangeWithTimeInterval { range, .. }
+            | Blocks::SimpleRange(range) => range,
+        }
+    }
+
+    /// Whether the blocks can only be expanded with
+    /// [Blocks::expand_to_block_numbers_with_rpc]
+    pub fn is_time_based(&self) -> bool {
+        matches!(self, Blocks::RangeWithTimeInterval { .. }) || self.range().is_time_based()
+    }
+
+    /// Expand to block numbers, resolving dates and times against block
+    /// timestamps read from the rpc. Ranges of block numbers only are
+    /// expanded without reading anything from the rpc.
+    pub async fn expand_to_block_numbers_with_rpc(
+        &self,
+        latest_block: BlockNumber,
+        rpc_url: &str,
+    ) -> Result<Vec<BlockNumber>, BlockError> {
+        self.expand_to_block_numbers_with_timestamps(
+            latest_block,
+            &mut BlockTimestamps::new(rpc_url)?,
+        )
+        .await
+    }
+
+    /// Same as [Blocks::expand_to_block_numbers_with_rpc] with the given
+    /// timestamps, so that the ones read along the way can be reused
+    pub async fn expand_to_block_numbers_with_timestamps(
+        &self,
+        latest_block: BlockNumber,
+        timestamps: &mut BlockTimestamps,
+    ) -> Result<Vec<BlockNumber>, BlockError> {
+        if !self.is_time_based() {
+            return self.expand_to_block_numbers(latest_block);
+        }
+
+        let range = self.range();
+        let start = timestamps
+            .block_number(&range.start, false, latest_block)
+            .await?;
+        let end = timestamps
+            .block_number(&range.end, true, latest_block)
+            .await?;
+        let range = BlockRange {
+            start: Block::Number(start),
+            end: Block::Number(end),
+        };
+
+        match self {
+            Blocks::RangeWithTimeInterval { interval, .. } => {
+                range.validate(latest_block)?;
+                // the first block at or after every interval from the start,
+                // searching from the previous one as blocks only go forward
+                let end_time = timestamps.timestamp(end).await?;
+                let mut time = timestamps.timestamp(start).await?;
+                let mut blocks = vec![];
+                let mut low = start;
+                while time <= end_time {
+                    let block = timestamps.first_block_at(time, low, end).await?;
+                    if blocks.last() != Some(&block) {
+                        blocks.push(block);
+                    }
+                    low = block;
+                    time += interval.0.max(1);
+                }
+                Ok(blocks)
+            }
+            Blocks::RangeWithInterval { interval, .. } => Blocks::RangeWithInterval {
+                range,
+                interval: *interval,
+            }
+            .expand_to_block_numbers(latest_block),
+            Blocks::SimpleRange(_) => {
+                Blocks::SimpleRange(range).expand_to_block_numbers(latest_block)
+            }
+        }
+    }
+
+    /// Expand a range of block numbers to the block numbers in it, time based
+    /// ranges fail with [BlockError::UnresolvedTimeRange]
     pub fn expand_to_block_numbers(
         &self,
         latest_block: BlockNumber,
     ) -> Result<Vec<BlockNumber>, BlockError> {
+        if self.is_time_based() {
+            return Err(BlockError::UnresolvedTimeRange);
+        }
         match self {
             Blocks::RangeWithInterval { range, interval } => {
                 range.validate(latest_block)?;
@@ -162,6 +536,7 @@ impl Blocks {
                 let end_block = range.end.to_block_number(latest_block);
                 Ok((start_block..=end_block).collect())
             }
+            Blocks::RangeWithTimeInterval { .. } => Err(BlockError::UnresolvedTimeRange),
         }
     }
 }
@@ -181,6 +556,7 @@ impl Default for Blocks {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_range_with_interval() {
@@ -369,4 +745,157 @@ interval: 5
         let expected = vec![0, 1, 2, 3, 4, 5];
         assert_eq!(blocks.expand_to_block_numbers(100).unwrap(), expected);
     }
+
+    #[test]
+    fn test_time_based_range() {
+        let yaml_data = r#"
+range: [-7d..]
+interval: 6h
+"#;
+        let expected = Blocks::RangeWithTimeInterval {
+            range: BlockRange {
+                start: Block::Ago(TimeInterval(7 * 24 * 60 * 60)),
+                end: Block::Latest,
+            },
+            interval: TimeInterval(6 * 60 * 60),
+        };
+
+        let result: Blocks = serde_yaml::from_str(yaml_data).unwrap();
+        assert_eq!(result, expected);
+        assert!(result.is_time_based());
+
+        let serialized = serde_yaml::to_string(&result).unwrap();
+        assert!(serialized.contains("-1w.."));
+        let deserialized: Blocks = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, expected);
+
+        assert_eq!(
+            result.expand_to_block_numbers(100),
+            Err(BlockError::UnresolvedTimeRange)
+        );
+    }
+
+    #[test]
+    fn test_date_range() {
+        let result: Blocks = serde_yaml::from_str("['2024-01-01..2024-01-02T12:00:00Z']").unwrap();
+        let expected = Blocks::SimpleRange(BlockRange {
+            start: Block::Timestamp(1704067200),
+            end: Block::Timestamp(1704196800),
+        });
+        assert_eq!(result, expected);
+
+        let serialized = serde_yaml::to_string(&result).unwrap();
+        let deserialized: Blocks = serde_yaml::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, expected);
+
+        assert!(serde_yaml::from_str::<Blocks>("[-7x..]").is_err());
+        assert!(serde_yaml::from_str::<Blocks>("[yesterday..]").is_err());
+    }
+
+    #[test]
+    fn test_time_interval() {
+        assert_eq!("90s".parse(), Ok(TimeInterval(90)));
+        assert_eq!("15m".parse(), Ok(TimeInterval(15 * 60)));
+        assert_eq!("2w".parse(), Ok(TimeInterval(14 * 24 * 60 * 60)));
+        assert!("15".parse::<TimeInterval>().is_err());
+        assert!("m".parse::<TimeInterval>().is_err());
+
+        assert_eq!(TimeInterval(90).to_string(), "90s");
+        assert_eq!(TimeInterval(120).to_string(), "2m");
+        assert_eq!(TimeInterval(36 * 60 * 60).to_string(), "36h");
+        assert_eq!(TimeInterval(0).to_string(), "0s");
+    }
+
+    /// Rpc with blocks 0 to 100, 12 seconds apart from a timestamp of 1000
+    async fn mock_rpc() -> httpmock::MockServer {
+        let server = httpmock::MockServer::start_async().await;
+        for block in 0..=100u64 {
+            server.mock(|when, then| {
+                when.method(httpmock::Method::POST).json_body_partial(
+                    json!({
+                        "method": "eth_getBlockByNumber",
+                        "params": [format!("{:#x}", block), false],
+                    })
+                    .to_string(),
+                );
+                then.json_body(json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": {"timestamp": format!("{:#x}", 1000 + 12 * block)},
+                }));
+            });
+        }
+        server
+    }
+
+    #[tokio::test]
+    async fn test_expand_to_block_numbers_with_rpc() {
+        let server = mock_rpc().await;
+        let rpc_url = server.url("/");
+
+        let blocks: Blocks = serde_yaml::from_str("[-120s..]").unwrap();
+        assert_eq!(
+            blocks
+                .expand_to_block_numbers_with_rpc(100, &rpc_url)
+                .await
+                .unwrap(),
+            (90..=100).collect::<Vec<BlockNumber>>()
+        );
+
+        let blocks: Blocks = serde_yaml::from_str("range: [-10m..]\ninterval: 1m").unwrap();
+        assert_eq!(
+            blocks
+                .expand_to_block_numbers_with_rpc(100, &rpc_url)
+                .await
+                .unwrap(),
+            (50..=100).step_by(5).collect::<Vec<BlockNumber>>()
+        );
+
+        let blocks: Blocks = serde_yaml::from_str(
+            "range: ['1970-01-01T00:20:00Z..1970-01-01T00:30:00Z']\ninterval: 10",
+        )
+        .unwrap();
+        assert_eq!(
+            blocks
+                .expand_to_block_numbers_with_rpc(100, &rpc_url)
+                .await
+                .unwrap(),
+            vec![17, 27, 37, 47, 57]
+        );
+
+        // a time based end is the last block at or before the time
+        let blocks: Blocks = serde_yaml::from_str("[..-60s]").unwrap();
+        assert_eq!(
+            blocks
+                .expand_to_block_numbers_with_rpc(100, &rpc_url)
+                .await
+                .unwrap(),
+            (0..=95).collect::<Vec<BlockNumber>>()
+        );
+        let blocks: Blocks = serde_yaml::from_str("[..-61s]").unwrap();
+        assert_eq!(
+            blocks
+                .expand_to_block_numbers_with_rpc(100, &rpc_url)
+                .await
+                .unwrap(),
+            (0..=94).collect::<Vec<BlockNumber>>()
+        );
+
+        // block number ranges are expanded without the rpc
+        let blocks: Blocks = serde_yaml::from_str("[0..5]").unwrap();
+        assert_eq!(
+            blocks
+                .expand_to_block_numbers_with_rpc(100, "http://localhost:1")
+                .await
+                .unwrap(),
+            vec![0, 1, 2, 3, 4, 5]
+        );
+
+        let blocks: Blocks = serde_yaml::from_str("[-1d..]").unwrap();
+        let err = blocks
+            .expand_to_block_numbers_with_rpc(100, "http://localhost:1")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BlockError::BlockTimestampError(100, _)));
+    }
 }
@@ -66,19 +66,56 @@ pub enum ParseConfigSourceError {
     YamlDeserializerError(#[from] serde_yaml::Error),
     #[error(transparent)]
     ConfigSourceError(#[from] ConfigSourceError),
+    #[error("{path}: {error}")]
+    AtPathError {
+        path: String,
+        error: Box<ParseConfigSourceError>,
+    },
+}
+
+impl ParseConfigSourceError {
+    /// Attach the yaml key path of the entity that failed to parse
+    fn at(path: String, error: impl Into<ParseConfigSourceError>) -> Self {
+        ParseConfigSourceError::AtPathError {
+            path,
+            error: Box::new(error.into()),
+        }
+    }
 }
 
 impl TryFrom<ConfigSource> for Config {
     type Error = ParseConfigSourceError;
 
     fn try_from(item: ConfigSource) -> Result<Self, Self::Error> {
+        let config = Config::try_from_source_unchecked(item)?;
+        for (name, order) in &config.orders {
+            order
+                .check_networks()
+                .map_err(|e| ParseConfigSourceError::at(format!("orders.{}", name), e))?;
+        }
+        for (name, deployment) in &config.deployments {
+            deployment
+                .check_deployer()
+                .map_err(|e| ParseConfigSourceError::at(format!("deployments.{}", name), e))?;
+        }
+        Ok(config)
+    }
+}
+
+impl Config {
+    /// Convert a config source by resolving its references, without the
+    /// checks of the [TryFrom] conversion that the entities it references
+    /// match, so [Config::validate] can report every such problem instead
+    pub fn try_from_source_unchecked(item: ConfigSource) -> Result<Config, ParseConfigSourceError> {
         let networks = item
             .networks
             .into_iter()
             .map(|(name, network)| {
                 Ok((
                     name.clone(),
-                    Arc::new(network.try_into_network(name.clone())?),
+                    Arc::new(network.try_into_network(name.clone()).map_err(|e| {
+                        ParseConfigSourceError::at(format!("networks.{}", name), e)
+                    })?),
                 ))
             })
             .collect::<Result<HashMap<String, Arc<Network>>, ParseConfigSourceError>>()?;
@@ -101,7 +138,13 @@ impl TryFrom<ConfigSource> for Config {
             .map(|(name, orderbook)| {
                 Ok((
                     name.clone(),
-                    Arc::new(orderbook.try_into_orderbook(name, &networks, &subgraphs)?),
+                    Arc::new(
+                        orderbook
+                            .try_into_orderbook(name.clone(), &networks, &subgraphs)
+                            .map_err(|e| {
+                                ParseConfigSourceError::at(format!("orderbooks.{}", name), e)
+                            })?,
+                    ),
                 ))
             })
             .collect::<Result<HashMap<String, Arc<Orderbook>>, ParseConfigSourceError>>()?;
@@ -109,7 +152,12 @@ impl TryFrom<ConfigSource> for Config {
         let tokens = item
             .tokens
             .into_iter()
-            .map(|(name, token)| Ok((name, Arc::new(token.try_into_token(&networks)?))))
+            .map(|(name, token)| {
+                let token = token
+                    .try_into_token(&networks)
+                    .map_err(|e| ParseConfigSourceError::at(format!("tokens.{}", name), e))?;
+                Ok((name, Arc::new(token)))
+            })
             .collect::<Result<HashMap<String, Arc<Token>>, ParseConfigSourceError>>()?;
 
         let deployers = item
@@ -118,7 +166,13 @@ impl TryFrom<ConfigSource> for Config {
             .map(|(name, deployer)| {
                 Ok((
                     name.clone(),
-                    Arc::new(deployer.try_into_deployer(name, &networks)?),
+                    Arc::new(
+                        deployer
+                            .try_into_deployer(name.clone(), &networks)
+                            .map_err(|e| {
+                                ParseConfigSourceError::at(format!("deployers.{}", name), e)
+                            })?,
+                    ),
                 ))
             })
             .collect::<Result<HashMap<String, Arc<Deployer>>, ParseConfigSourceError>>()?;
@@ -127,10 +181,10 @@ impl TryFrom<ConfigSource> for Config {
             .orders
             .into_iter()
             .map(|(name, order)| {
-                Ok((
-                    name,
-                    Arc::new(order.try_into_order(&deployers, &orderbooks, &tokens)?),
-                ))
+                let order = order
+                    .try_into_order(&deployers, &orderbooks, &tokens)
+                    .map_err(|e| ParseConfigSourceError::at(format!("orders.{}", name), e))?;
+                Ok((name, Arc::new(order)))
             })
             .collect::<Result<HashMap<String, Arc<Order>>, ParseConfigSourceError>>()?;
 
@@ -139,11 +193,9 @@ impl TryFrom<ConfigSource> for Config {
 
         // Directly iterate over scenarios if it's a HashMap
         for (name, scenario_string) in item.scenarios {
-            let scenario_map = scenario_string.try_into_scenarios(
-                name.clone(),
-                &ScenarioParent::default(),
-                &deployers,
-            )?;
+            let scenario_map = scenario_string
+                .try_into_scenarios(name.clone(), &ScenarioParent::default(), &deployers)
+                .map_err(|e| ParseConfigSourceError::at(format!("scenarios.{}", name), e))?;
 
             // Merge the scenarios
             scenarios.extend(scenario_map);
@@ -154,8 +206,14 @@ impl TryFrom<ConfigSource> for Config {
             .into_iter()
             .map(|(name, deployment)| {
                 Ok((
-                    name,
-                    Arc::new(deployment.try_into_deployment(&scenarios, &orders)?),
+                    name.clone(),
+                    Arc::new(
+                        deployment
+                            .try_into_deployment(name.clone(), &scenarios, &orders, &tokens)
+                            .map_err(|e| {
+                                ParseConfigSourceError::at(format!("deployments.{}", name), e)
+                            })?,
+                    ),
                 ))
             })
             .collect::<Result<HashMap<String, Arc<Deployment>>, ParseConfigSourceError>>()?;
@@ -166,7 +224,13 @@ impl TryFrom<ConfigSource> for Config {
             .map(|(name, chart)| {
                 Ok((
                     name.clone(),
-                    Arc::new(chart.try_into_chart(name, &scenarios)?),
+                    Arc::new(
+                        chart
+                            .try_into_chart(name.clone(), &scenarios)
+                            .map_err(|e| {
+                                ParseConfigSourceError::at(format!("charts.{}", name), e)
+                            })?,
+                    ),
                 ))
             })
             .collect::<Result<HashMap<String, Arc<Chart>>, ParseConfigSourceError>>()?;
@@ -195,9 +259,7 @@ impl TryFrom<ConfigSource> for Config {
 
         Ok(config)
     }
-}
 
-impl Config {
     pub async fn try_from_string(val: String) -> Result<Config, ParseConfigSourceError> {
         let config_source = ConfigSource::try_from_string(val, None).await?.0;
         std::convert::TryInto::<Config>::try_into(config_source)
@@ -217,7 +279,7 @@ mod tests {
         networks.insert(
             "mainnet".to_string(),
             NetworkConfigSource {
-                rpc: Url::parse("https://mainnet.node").unwrap(),
+                rpc: Url::parse("https://mainnet.node").unwrap().into(),
                 chain_id: 1,
                 label: Some("Ethereum Mainnet".to_string()),
                 network_id: Some(1),
@@ -289,6 +351,7 @@ mod tests {
 
         let config_string = ConfigSource {
             raindex_version: Some("0x123".to_string()),
+            imports: vec![],
             using_networks_from,
             networks,
             subgraphs,
@@ -302,6 +365,7 @@ mod tests {
             deployments,
             sentry,
             accounts,
+            env_interpolations: vec![],
         };
 
         let config_result = Config::try_from(config_string);
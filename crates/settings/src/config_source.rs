@@ -1,17 +1,35 @@
 use crate::blocks::Blocks;
-use crate::remote::chains::{chainid::ChainIdError, RemoteNetworkError, RemoteNetworks};
+use crate::env::{
+    interpolate_yaml, restore_placeholders, EnvInterpolation, EnvInterpolationError, EnvVars,
+};
+use crate::imports::{
+    resolve_imports, resolve_imports_skipping, ImportBase, ImportError, ImportLocation,
+};
+use crate::merge::{merge_values, MergeError, Provenance};
+use crate::remote::chains::{
+    chainid::ChainIdError, RemoteNetworkError, RemoteNetworkFormats, RemoteNetworks,
+};
+use crate::schema::{AddressSchema, Uint256Schema};
 use crate::{Metric, Plot};
 use alloy::primitives::{Address, U256};
-use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
+use std::path::Path;
 use thiserror::Error;
 use typeshare::typeshare;
 use url::Url;
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
-#[serde(rename_all = "kebab-case")]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(remote = "Self", rename_all = "kebab-case")]
+#[schemars(rename = "ConfigSource")]
 pub struct ConfigSource {
+    /// Paths or urls of other ymls to merge into this one, relative paths
+    /// are resolved from the importing yml. Emptied once resolved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub using_networks_from: HashMap<String, RemoteNetworksConfigSource>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
@@ -40,6 +58,44 @@ pub struct ConfigSource {
     pub raindex_version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub accounts: Option<HashMap<String, String>>,
+    /// Values resolved from environment variables, serialization writes
+    /// their placeholders back instead of the resolved values
+    #[typeshare(skip)]
+    #[serde(skip)]
+    pub env_interpolations: Vec<EnvInterpolation>,
+}
+
+impl Serialize for ConfigSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.env_interpolations.is_empty() {
+            return ConfigSource::serialize(self, serializer);
+        }
+        let mut value = ConfigSource::serialize(self, serde_json::value::Serializer)
+            .map_err(S::Error::custom)?;
+        restore_placeholders(&mut value, &self.env_interpolations);
+        value.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConfigSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ConfigSource::deserialize(deserializer)
+    }
+}
+
+impl ConfigSource {
+    /// Json value of the config with resolved values, ie without writing
+    /// back env placeholders
+    pub(crate) fn to_raw_json(&self) -> Result<serde_json::Value, serde_json::Error> {
+        ConfigSource::serialize(self, serde_json::value::Serializer)
+    }
+
+    /// The config without its env placeholders, so it serializes to the
+    /// values they resolved to, eg to hand it over to be deserialized again
+    pub fn into_resolved(mut self) -> Self {
+        self.env_interpolations.clear();
+        self
+    }
 }
 
 #[typeshare]
@@ -67,10 +123,11 @@ pub type TokenRef = String;
 pub type MetaboardRef = String;
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct NetworkConfigSource {
-    pub rpc: Url,
+    #[typeshare(typescript(type = "string | (string | { url: string, priority?: number })[]"))]
+    pub rpc: RpcConfigSource,
     #[typeshare(typescript(type = "number"))]
     pub chain_id: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -82,18 +139,81 @@ pub struct NetworkConfigSource {
     pub currency: Option<String>,
 }
 
+/// Rpc of a network, either a single url or a list of urls to fall back on
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum RpcConfigSource {
+    Single(Url),
+    Multiple(Vec<RpcEndpointConfigSource>),
+}
+
+/// An rpc url in a list, optionally with a priority where lower values are
+/// tried first
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum RpcEndpointConfigSource {
+    Url(Url),
+    WithPriority {
+        url: Url,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        priority: Option<u32>,
+    },
+}
+
+impl From<Url> for RpcConfigSource {
+    fn from(value: Url) -> Self {
+        RpcConfigSource::Single(value)
+    }
+}
+
+impl RpcConfigSource {
+    /// Urls ordered by priority, entries without a priority come after the
+    /// prioritized ones and keep their order in the list
+    pub fn urls_by_priority(&self) -> Vec<Url> {
+        match self {
+            RpcConfigSource::Single(url) => vec![url.clone()],
+            RpcConfigSource::Multiple(endpoints) => {
+                let mut endpoints: Vec<(Option<u32>, &Url)> = endpoints
+                    .iter()
+                    .map(|endpoint| match endpoint {
+                        RpcEndpointConfigSource::Url(url) => (None, url),
+                        RpcEndpointConfigSource::WithPriority { url, priority } => (*priority, url),
+                    })
+                    .collect();
+                endpoints.sort_by_key(|(priority, _)| priority.unwrap_or(u32::MAX));
+                endpoints.into_iter().map(|(_, url)| url.clone()).collect()
+            }
+        }
+    }
+}
+
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct RemoteNetworksConfigSource {
+    /// Url or path of the list, relative paths are resolved like imports
     pub url: String,
+    /// Name of the list's format, see [RemoteNetworkFormats]
     pub format: String,
+    /// Only import the networks of these chain ids
+    #[typeshare(typescript(type = "number[]"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chain_ids: Option<Vec<u64>>,
+    /// Cache a fetched list on disk for this many seconds, a cached list is
+    /// also used past its ttl when the list cannot be fetched
+    #[typeshare(typescript(type = "number"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl: Option<u64>,
+    /// Directory of the cache, defaults to one in the temp directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<String>,
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct OrderbookConfigSource {
+    #[schemars(with = "AddressSchema")]
     pub address: Address,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<NetworkRef>,
@@ -104,10 +224,11 @@ pub struct OrderbookConfigSource {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct TokenConfigSource {
     pub network: NetworkRef,
+    #[schemars(with = "AddressSchema")]
     pub address: Address,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub decimals: Option<u8>,
@@ -118,9 +239,10 @@ pub struct TokenConfigSource {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DeployerConfigSource {
+    #[schemars(with = "AddressSchema")]
     pub address: Address,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<NetworkRef>,
@@ -129,25 +251,35 @@ pub struct DeployerConfigSource {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct DeploymentConfigSource {
     pub scenario: ScenarioRef,
     pub order: OrderRef,
+    /// Amounts to deposit into the order's output vaults when it is added,
+    /// keyed by token and in the token's units, ie with its decimals applied.
+    /// Deposits are made in the order they are listed.
+    #[typeshare(typescript(type = "Record<string, string>"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposits: Option<IndexMap<TokenRef, String>>,
+    /// How vault ids left unset on the order's inputs and outputs are chosen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_id_mode: Option<VaultIdMode>,
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct IOString {
     pub token: TokenRef,
     #[typeshare(typescript(type = "bigint"))]
+    #[schemars(with = "Option<Uint256Schema>")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vault_id: Option<U256>,
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct OrderConfigSource {
     pub inputs: Vec<IOString>,
@@ -159,7 +291,7 @@ pub struct OrderConfigSource {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct ScenarioConfigSource {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
@@ -177,7 +309,7 @@ pub struct ScenarioConfigSource {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct ChartConfigSource {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -198,41 +330,187 @@ pub enum ConfigSourceError {
     ConflictingNetworks(String),
     #[error(transparent)]
     ChainIdError(#[from] ChainIdError),
+    #[error(transparent)]
+    EnvInterpolationError(#[from] EnvInterpolationError),
+    #[error(transparent)]
+    ImportError(#[from] ImportError),
+    #[error(transparent)]
+    MergeError(#[from] MergeError),
+}
+
+impl ConfigSourceError {
+    /// Whether parsing failed only because it needed network access while
+    /// parsing offline, see [ParseContext::offline]
+    pub fn needs_network(&self) -> bool {
+        matches!(
+            self,
+            ConfigSourceError::ImportError(ImportError::Offline(_))
+                | ConfigSourceError::RemoteNetworkError(RemoteNetworkError::ImportError(
+                    ImportError::Offline(_)
+                ))
+        )
+    }
+
+    /// Whether parsing failed only because a `${VAR}` placeholder without a
+    /// default has no variable set for it
+    pub fn needs_env(&self) -> bool {
+        matches!(
+            self,
+            ConfigSourceError::EnvInterpolationError(EnvInterpolationError::MissingVariable(_))
+        )
+    }
+}
+
+/// Context ymls are parsed in
+#[derive(Debug, Clone, Default)]
+pub struct ParseContext {
+    /// Variables `${VAR}` placeholders are resolved from
+    pub env: EnvVars,
+    /// Where relative `imports` and remote network lists of the parsed ymls
+    /// are resolved from
+    pub import_base: ImportBase,
+    /// Formats `using-networks-from` lists can be in
+    pub remote_network_formats: RemoteNetworkFormats,
+    /// Where the parsed yml was read from if known, the yml importing itself
+    /// back is an import cycle
+    pub location: Option<ImportLocation>,
+    /// Fail instead of fetching imports and remote network lists from urls,
+    /// eg for diagnostics that run on every edit
+    pub offline: bool,
+}
+
+impl ParseContext {
+    /// Read an imported yml or a remote network list
+    pub(crate) async fn read(&self, location: &ImportLocation) -> Result<String, ImportError> {
+        if self.offline && matches!(location, ImportLocation::Url(_)) {
+            return Err(ImportError::Offline(location.to_string()));
+        }
+        location.read().await
+    }
+
+    /// Process environment, with imports relative to the current directory.
+    /// Does not read any files, so parsing in this context behaves the same
+    /// wherever the process runs.
+    pub fn from_process() -> Self {
+        ParseContext {
+            env: EnvVars::from_process(),
+            ..Default::default()
+        }
+    }
+
+    /// Process environment and `.env` file of the current directory, with
+    /// imports relative to the current directory
+    pub fn load() -> Result<Self, ConfigSourceError> {
+        Ok(ParseContext {
+            env: EnvVars::load()?,
+            ..Default::default()
+        })
+    }
+
+    /// Process environment and `.env` file of the current directory, with
+    /// imports relative to the directory of the given yml file
+    pub fn load_for_file(path: &Path) -> Result<Self, ConfigSourceError> {
+        let location =
+            ImportLocation::Path(std::fs::canonicalize(path).unwrap_or(path.to_path_buf()));
+        Ok(ParseContext {
+            import_base: location.base(),
+            location: Some(location),
+            ..Self::load()?
+        })
+    }
 }
 
 impl ConfigSource {
+    /// Parse ymls, resolving `${VAR}` placeholders from the process
+    /// environment and imports relative to the current directory, see
+    /// [ParseContext::load] for also reading a `.env` file
     pub async fn try_from_string(
         val: String,
         top_config: Option<String>,
+    ) -> Result<(ConfigSource, ConfigSource), ConfigSourceError> {
+        Self::try_from_string_with_context(val, top_config, &ParseContext::from_process()).await
+    }
+
+    /// Parse ymls in the given context
+    pub async fn try_from_string_with_context(
+        val: String,
+        top_config: Option<String>,
+        context: &ParseContext,
     ) -> Result<(ConfigSource, ConfigSource), ConfigSourceError> {
         if let Some(top_config) = top_config {
-            let merged = MergedConfigSource::new(val, top_config).await?;
+            let mut merged = MergedConfigSource::new(val, top_config, context).await?;
+            // a yml imported by both is only merged into the first of them,
+            // the two get merged together later
+            let mut imported = vec![];
+            resolve_imports_skipping(&mut merged.main, context, &mut imported).await?;
+            resolve_imports_skipping(&mut merged.top_config, context, &mut imported).await?;
             Ok((merged.main, merged.top_config))
         } else {
-            let mut conf: ConfigSource = serde_yaml::from_str(&val)?;
-            if !conf.using_networks_from.is_empty() {
-                for (_key, item) in conf.using_networks_from.iter() {
-                    let remote_networks =
-                        RemoteNetworks::try_from_remote_network_config_source(item.clone()).await?;
-                    match remote_networks {
-                        RemoteNetworks::ChainId(chains) => {
-                            for chain in &chains {
-                                if conf.networks.iter().all(|(k, _v)| *k != chain.short_name) {
-                                    if let Ok(v) = chain.clone().try_into() {
-                                        conf.networks.insert(chain.short_name.clone(), v);
-                                    }
-                                } else {
-                                    return Err(ConfigSourceError::ConflictingNetworks(
-                                        chain.name.clone(),
-                                    ));
-                                }
-                            }
-                        }
-                    }
+            let mut conf = Self::try_from_single_string(val, context).await?;
+            resolve_imports(&mut conf, context).await?;
+            Ok((conf, ConfigSource::default()))
+        }
+    }
+
+    /// Parse ymls in the given context, with the values of the top config
+    /// overriding those of the main yml field by field instead of being
+    /// collisions, the top config does not have to be a complete config
+    pub async fn try_from_string_with_override(
+        val: String,
+        top_config: Option<String>,
+        context: &ParseContext,
+    ) -> Result<ConfigSource, ConfigSourceError> {
+        let Some(top_config) = top_config else {
+            return Ok(Self::try_from_string_with_context(val, None, context)
+                .await?
+                .0);
+        };
+        let merged = MergedYaml::parse(val, top_config, &context.env)?;
+        let mut main: ConfigSource = serde_yaml::from_value(merged.main)?;
+        main.env_interpolations = merged.main_interpolations;
+        main.add_remote_networks(context).await?;
+        resolve_imports(&mut main, context).await?;
+
+        let top_config = PartialConfigSource::try_from_yaml(
+            merged.top_config,
+            merged.top_config_interpolations,
+            context,
+        )
+        .await?;
+        main.merge_override(top_config, "top-config", &mut Provenance::default())?;
+        Ok(main)
+    }
+
+    /// Parse a single yml without resolving its imports
+    pub(crate) async fn try_from_single_string(
+        val: String,
+        context: &ParseContext,
+    ) -> Result<ConfigSource, ConfigSourceError> {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(&val)?;
+        let env_interpolations = interpolate_yaml(&mut value, &context.env)?;
+        let mut conf: ConfigSource = serde_yaml::from_value(value)?;
+        conf.env_interpolations = env_interpolations;
+        conf.add_remote_networks(context).await?;
+        Ok(conf)
+    }
+
+    /// Add the networks of the `using-networks-from` lists
+    async fn add_remote_networks(
+        &mut self,
+        context: &ParseContext,
+    ) -> Result<(), ConfigSourceError> {
+        for item in self.using_networks_from.values() {
+            let remote_networks =
+                RemoteNetworks::try_from_remote_network_config_source(item.clone(), context)
+                    .await?;
+            for (name, network) in remote_networks.0 {
+                if self.networks.contains_key(&name) {
+                    return Err(ConfigSourceError::ConflictingNetworks(name));
                 }
+                self.networks.insert(name, network);
             }
-            Ok((conf, ConfigSource::default()))
         }
+        Ok(())
     }
 }
 
@@ -247,7 +525,41 @@ impl MergedConfigSource {
     async fn new(
         main_config: String,
         top_config: String,
+        context: &ParseContext,
     ) -> Result<MergedConfigSource, ConfigSourceError> {
+        let merged = MergedYaml::parse(main_config, top_config, &context.env)?;
+        let mut merged_conf = MergedConfigSource {
+            main: serde_yaml::from_value(merged.main)?,
+            top_config: serde_yaml::from_value(merged.top_config)?,
+        };
+        merged_conf.main.env_interpolations = merged.main_interpolations;
+        merged_conf.top_config.env_interpolations = merged.top_config_interpolations;
+
+        // handle remote networks for both ymls
+        merged_conf.main.add_remote_networks(context).await?;
+        merged_conf.top_config.add_remote_networks(context).await?;
+        Ok(merged_conf)
+    }
+}
+
+/// Values of a main yml and a top config parsed together as one yml
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct MergedYaml {
+    main: serde_yaml::Value,
+    top_config: serde_yaml::Value,
+    #[serde(skip)]
+    main_interpolations: Vec<EnvInterpolation>,
+    #[serde(skip)]
+    top_config_interpolations: Vec<EnvInterpolation>,
+}
+
+impl MergedYaml {
+    fn parse(
+        main_config: String,
+        top_config: String,
+        env: &EnvVars,
+    ) -> Result<MergedYaml, ConfigSourceError> {
         let mut main_indented = String::new();
         let mut top_config_indented = String::new();
 
@@ -277,68 +589,110 @@ main:
 ",
             top_config_indented, main_indented
         );
-        let mut merged_conf: MergedConfigSource = serde_yaml::from_str(&merged)?;
+        let mut value: serde_yaml::Value = serde_yaml::from_str(&merged)?;
+        let env_interpolations = interpolate_yaml(&mut value, env)?;
+        let mut merged_yaml: MergedYaml = serde_yaml::from_value(value)?;
+        merged_yaml.main_interpolations = env_interpolations
+            .iter()
+            .filter_map(|v| v.strip_prefix("main"))
+            .collect();
+        merged_yaml.top_config_interpolations = env_interpolations
+            .iter()
+            .filter_map(|v| v.strip_prefix("top-config"))
+            .collect();
+        Ok(merged_yaml)
+    }
+}
 
-        // handle remote networks for both ymls
-        if !merged_conf.main.using_networks_from.is_empty() {
-            for (_key, item) in merged_conf.main.using_networks_from.iter() {
-                let remote_networks =
-                    RemoteNetworks::try_from_remote_network_config_source(item.clone()).await?;
-                match remote_networks {
-                    RemoteNetworks::ChainId(chains) => {
-                        for chain in &chains {
-                            if merged_conf
-                                .main
-                                .networks
-                                .iter()
-                                .all(|(k, _v)| *k != chain.short_name)
-                            {
-                                if let Ok(v) = chain.clone().try_into() {
-                                    merged_conf
-                                        .main
-                                        .networks
-                                        .insert(chain.short_name.clone(), v);
-                                }
-                            } else {
-                                return Err(ConfigSourceError::ConflictingNetworks(
-                                    chain.name.clone(),
-                                ));
-                            }
-                        }
-                    }
-                }
-            }
+/// A yml that may set only some of the values of a config, to override the
+/// values of other configs with, see [ConfigSource::merge_override]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartialConfigSource {
+    /// Values the yml sets, including those of its imports and remote
+    /// network lists
+    pub value: serde_json::Value,
+    pub env_interpolations: Vec<EnvInterpolation>,
+}
+
+/// Keys of a yml that pull in values from elsewhere
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct IncludesConfigSource {
+    imports: Vec<String>,
+    using_networks_from: HashMap<String, RemoteNetworksConfigSource>,
+}
+
+impl PartialConfigSource {
+    /// Parse a yml that does not have to be a complete config, resolving
+    /// its placeholders, imports and remote networks in the given context
+    pub async fn try_from_string(
+        val: String,
+        context: &ParseContext,
+    ) -> Result<PartialConfigSource, ConfigSourceError> {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(&val)?;
+        let env_interpolations = interpolate_yaml(&mut value, &context.env)?;
+        Self::try_from_yaml(value, env_interpolations, context).await
+    }
+
+    async fn try_from_yaml(
+        mut value: serde_yaml::Value,
+        env_interpolations: Vec<EnvInterpolation>,
+        context: &ParseContext,
+    ) -> Result<PartialConfigSource, ConfigSourceError> {
+        if value.is_null() {
+            value = serde_yaml::Value::Mapping(Default::default());
         }
-        if !merged_conf.top_config.using_networks_from.is_empty() {
-            for (_key, item) in merged_conf.top_config.using_networks_from.iter() {
-                let remote_networks =
-                    RemoteNetworks::try_from_remote_network_config_source(item.clone()).await?;
-                match remote_networks {
-                    RemoteNetworks::ChainId(chains) => {
-                        for chain in &chains {
-                            if merged_conf
-                                .top_config
-                                .networks
-                                .iter()
-                                .all(|(k, _v)| *k != chain.short_name)
-                            {
-                                if let Ok(v) = chain.clone().try_into() {
-                                    merged_conf
-                                        .top_config
-                                        .networks
-                                        .insert(chain.short_name.clone(), v);
-                                }
-                            } else {
-                                return Err(ConfigSourceError::ConflictingNetworks(
-                                    chain.name.clone(),
-                                ));
-                            }
-                        }
-                    }
-                }
-            }
+
+        // imports and remote networks go into a config the values of the
+        // yml itself then override
+        let includes: IncludesConfigSource = serde_yaml::from_value(value.clone())?;
+        let mut included = ConfigSource {
+            imports: includes.imports,
+            using_networks_from: includes.using_networks_from,
+            ..Default::default()
+        };
+        included.add_remote_networks(context).await?;
+        resolve_imports(&mut included, context).await?;
+
+        let mut merged = included
+            .to_raw_json()
+            .map_err(|e| MergeError::ValueError(e.to_string()))?;
+        let mut value: serde_json::Value = serde_yaml::from_value(value)?;
+        if let Some(map) = value.as_object_mut() {
+            map.remove("imports");
         }
-        Ok(merged_conf)
+        merge_values(&mut merged, value, "", "", &mut Provenance::default());
+
+        let mut interpolations = included.env_interpolations;
+        interpolations.extend(env_interpolations);
+        Ok(PartialConfigSource {
+            value: merged,
+            env_interpolations: interpolations,
+        })
+    }
+}
+
+impl TryFrom<PartialConfigSource> for ConfigSource {
+    type Error = MergeError;
+
+    fn try_from(partial: PartialConfigSource) -> Result<Self, Self::Error> {
+        let mut config: ConfigSource = serde_json::from_value(partial.value)
+            .map_err(|e| MergeError::ValueError(e.to_string()))?;
+        config.env_interpolations = partial.env_interpolations;
+        Ok(config)
+    }
+}
+
+impl TryFrom<ConfigSource> for PartialConfigSource {
+    type Error = MergeError;
+
+    fn try_from(config: ConfigSource) -> Result<Self, Self::Error> {
+        Ok(PartialConfigSource {
+            value: config
+                .to_raw_json()
+                .map_err(|e| MergeError::ValueError(e.to_string()))?,
+            env_interpolations: config.env_interpolations,
+        })
     }
 }
 
@@ -530,7 +884,7 @@ accounts:
         // Asserting a few values to verify successful parsing
         assert_eq!(
             config.clone().networks.get("mainnet").unwrap().rpc,
-            Url::parse("https://mainnet.node").unwrap()
+            Url::parse("https://mainnet.node").unwrap().into()
         );
         assert_eq!(
             config.networks.get("mainnet").unwrap().label,
@@ -552,7 +906,9 @@ accounts:
         // remote networks fetched from remote source and converted and added to networks
         assert_eq!(
             config.clone().networks.get("eth").unwrap().rpc,
-            Url::parse("https://api.mycryptoapi.com/eth").unwrap()
+            Url::parse("https://api.mycryptoapi.com/eth")
+                .unwrap()
+                .into()
         );
         assert_eq!(
             config.networks.get("eth").unwrap().label,
@@ -560,7 +916,7 @@ accounts:
         );
         assert_eq!(
             config.clone().networks.get("matic").unwrap().rpc,
-            Url::parse("https://polygon-rpc.com/").unwrap()
+            Url::parse("https://polygon-rpc.com/").unwrap().into()
         );
         assert_eq!(
             config.networks.get("matic").unwrap().label,
@@ -686,7 +1042,7 @@ orderbooks: *orderbooks
         assert_eq!(config.clone().raindex_version.unwrap(), "123".to_string());
         assert_eq!(
             config.clone().networks.get("mainnet").unwrap().rpc,
-            Url::parse("https://mainnet.node").unwrap()
+            Url::parse("https://mainnet.node").unwrap().into()
         );
         assert_eq!(
             config.networks.get("mainnet").unwrap().label,
@@ -710,7 +1066,7 @@ orderbooks: *orderbooks
         );
         assert_eq!(
             top_config.clone().networks.get("mainnet").unwrap().rpc,
-            Url::parse("https://mainnet.node").unwrap()
+            Url::parse("https://mainnet.node").unwrap().into()
         );
         assert_eq!(
             top_config.networks.get("mainnet").unwrap().label,
@@ -734,4 +1090,89 @@ orderbooks: *orderbooks
         // in this case both configs should be equal
         assert_eq!(config, top_config);
     }
+
+    #[tokio::test]
+    async fn test_env_interpolation() {
+        let yaml_data = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node/${API_KEY}
+        chain-id: ${CHAIN_ID:-1}
+subgraphs:
+    mainnet: ${SUBGRAPH_URL}
+tokens:
+    token1:
+        network: mainnet
+        address: 0x0000000000000000000000000000000000000001
+        decimals: 1
+"#;
+        let env = EnvVars(HashMap::from([
+            ("API_KEY".to_string(), "secret".to_string()),
+            (
+                "SUBGRAPH_URL".to_string(),
+                "https://mainnet.subgraph".to_string(),
+            ),
+        ]));
+
+        let context = ParseContext {
+            env,
+            ..Default::default()
+        };
+        let config =
+            ConfigSource::try_from_string_with_context(yaml_data.to_string(), None, &context)
+                .await
+                .unwrap()
+                .0;
+        let mainnet = config.networks.get("mainnet").unwrap();
+        assert_eq!(
+            mainnet.rpc,
+            Url::parse("https://mainnet.node/secret").unwrap().into()
+        );
+        assert_eq!(mainnet.chain_id, 1);
+
+        // placeholders are written back on serialization
+        let serialized = serde_yaml::to_string(&config).unwrap();
+        assert!(!serialized.contains("secret"));
+        assert!(serialized.contains("https://mainnet.node/${API_KEY}"));
+        assert!(serialized.contains("${SUBGRAPH_URL}"));
+        // only the resolved paths get their placeholders back
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["networks"]["mainnet"]["chain-id"], "${CHAIN_ID:-1}");
+        assert_eq!(value["tokens"]["token1"]["decimals"], 1);
+
+        // a resolved config serializes to values that deserialize as they are
+        let resolved: ConfigSource =
+            serde_json::from_value(serde_json::to_value(config.clone().into_resolved()).unwrap())
+                .unwrap();
+        assert_eq!(resolved, config.clone().into_resolved());
+        assert_eq!(resolved.networks.get("mainnet").unwrap().chain_id, 1);
+
+        // placeholders of each of merged ymls are kept apart
+        let (main, top_config) = ConfigSource::try_from_string_with_context(
+            "subgraphs:\n    other: https://other.subgraph\n".to_string(),
+            Some(yaml_data.to_string()),
+            &context,
+        )
+        .await
+        .unwrap();
+        assert!(main.env_interpolations.is_empty());
+        assert_eq!(top_config.env_interpolations.len(), 3);
+        assert_eq!(
+            top_config.env_interpolations[0].path,
+            vec!["networks", "mainnet", "rpc"]
+        );
+
+        let err = ConfigSource::try_from_string_with_context(
+            yaml_data.to_string(),
+            None,
+            &ParseContext::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigSourceError::EnvInterpolationError(EnvInterpolationError::MissingVariable(name))
+                if name == "API_KEY"
+        ));
+    }
 }
@@ -1,4 +1,5 @@
 use crate::*;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, sync::Arc};
 use thiserror::Error;
@@ -8,10 +9,39 @@ use typeshare::typeshare;
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub struct Deployment {
+    pub name: String,
     #[typeshare(typescript(type = "Scenario"))]
     pub scenario: Arc<Scenario>,
     #[typeshare(typescript(type = "Order"))]
     pub order: Arc<Order>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deposits: Option<Vec<DeploymentDeposit>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vault_id_mode: Option<VaultIdMode>,
+}
+
+/// How vault ids that are not specified on an order's IO are picked
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum VaultIdMode {
+    /// A new random vault id every time the order is added
+    #[default]
+    Random,
+    /// Derived from the owner, deployment name and token, so the same vaults
+    /// are used every time the deployment is added by the same owner
+    Deterministic,
+}
+
+/// Amount to deposit into an output vault of the deployed order
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct DeploymentDeposit {
+    #[typeshare(typescript(type = "Token"))]
+    pub token: Arc<Token>,
+    /// Amount in the token's units, ie with its decimals applied
+    pub amount: String,
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -22,13 +52,33 @@ pub enum ParseDeploymentConfigSourceError {
     OrderNotFoundError(String),
     #[error("Scenario and Order do not match")]
     NoMatch,
+    #[error("Deposit token not found: {0}")]
+    DepositTokenNotFoundError(String),
+    #[error("Deposit token {0} is not an output of the Order")]
+    DepositTokenNotOutput(String),
+}
+
+impl Deployment {
+    /// Check the order's deployer, if it has one, is the scenario's deployer
+    pub fn check_deployer(&self) -> Result<(), ParseDeploymentConfigSourceError> {
+        match &self.order.deployer {
+            Some(deployer) if *deployer != self.scenario.deployer => {
+                Err(ParseDeploymentConfigSourceError::NoMatch)
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 impl DeploymentConfigSource {
+    /// Resolve the references of the deployment, without checking that its
+    /// scenario and order match, see [Deployment::check_deployer]
     pub fn try_into_deployment(
         self,
+        name: String,
         scenarios: &HashMap<String, Arc<Scenario>>,
         orders: &HashMap<String, Arc<Order>>,
+        tokens: &HashMap<String, Arc<Token>>,
     ) -> Result<Deployment, ParseDeploymentConfigSourceError> {
         let scenario = scenarios
             .get(&self.scenario)
@@ -44,14 +94,38 @@ impl DeploymentConfigSource {
             ))
             .map(Arc::clone)?;
 
-        // check validity
-        if let Some(deployer) = &order.deployer {
-            if deployer != &scenario.deployer {
-                return Err(ParseDeploymentConfigSourceError::NoMatch);
-            }
-        };
+        let deposits = self
+            .deposits
+            .map(|deposits| {
+                deposits
+                    .into_iter()
+                    .map(|(token_name, amount)| {
+                        let token = tokens.get(&token_name).ok_or(
+                            ParseDeploymentConfigSourceError::DepositTokenNotFoundError(
+                                token_name.clone(),
+                            ),
+                        )?;
+                        if !order.outputs.iter().any(|v| v.token == *token) {
+                            return Err(ParseDeploymentConfigSourceError::DepositTokenNotOutput(
+                                token_name,
+                            ));
+                        }
+                        Ok(DeploymentDeposit {
+                            token: token.clone(),
+                            amount,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
 
-        Ok(Deployment { scenario, order })
+        Ok(Deployment {
+            name,
+            scenario,
+            order,
+            deposits,
+            vault_id_mode: self.vault_id_mode,
+        })
     }
 }
 
@@ -59,6 +133,7 @@ impl DeploymentConfigSource {
 mod tests {
     use super::*;
     use crate::test::*;
+    use indexmap::IndexMap;
 
     #[test]
     fn test_try_into_deployment_success() {
@@ -83,9 +158,19 @@ mod tests {
         let deploment_string = DeploymentConfigSource {
             scenario: scenario_name.to_string(),
             order: order_name.to_string(),
+            deposits: None,
+            vault_id_mode: Some(VaultIdMode::Deterministic),
         };
-        let result = deploment_string.try_into_deployment(&scenarios, &orders);
-        assert!(result.is_ok());
+        let result = deploment_string
+            .try_into_deployment(
+                "deployment1".to_string(),
+                &scenarios,
+                &orders,
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert_eq!(result.name, "deployment1");
+        assert_eq!(result.vault_id_mode, Some(VaultIdMode::Deterministic));
     }
 
     #[test]
@@ -112,11 +197,144 @@ mod tests {
         let deploment_string = DeploymentConfigSource {
             scenario: other_scenario_name.to_string(),
             order: order_name.to_string(),
+            deposits: None,
+            vault_id_mode: None,
         };
-        let result = deploment_string.try_into_deployment(&scenarios, &orders);
+        let result = deploment_string.try_into_deployment(
+            "deployment1".to_string(),
+            &scenarios,
+            &orders,
+            &HashMap::new(),
+        );
         assert!(matches!(
             result,
             Err(ParseDeploymentConfigSourceError::ScenarioNotFoundError(_))
         ));
     }
+
+    #[test]
+    fn test_try_into_deployment_deposits() {
+        let scenario = Scenario {
+            name: "scenario1".into(),
+            bindings: HashMap::new(),
+            deployer: mock_deployer(),
+            runs: None,
+            blocks: None,
+        };
+        let output_token = mock_token("output");
+        let input_token = mock_token("input");
+        let order = Order {
+            inputs: vec![OrderIO {
+                token: input_token.clone(),
+                vault_id: None,
+            }],
+            outputs: vec![OrderIO {
+                token: output_token.clone(),
+                vault_id: None,
+            }],
+            network: mock_network(),
+            deployer: None,
+            orderbook: None,
+        };
+        let orders = HashMap::from([("order1".to_string(), Arc::new(order))]);
+        let scenarios = HashMap::from([("scenario1".to_string(), Arc::new(scenario))]);
+        let tokens = HashMap::from([
+            ("output".to_string(), output_token.clone()),
+            ("input".to_string(), input_token),
+        ]);
+        let deployment_source = |token: &str| DeploymentConfigSource {
+            scenario: "scenario1".to_string(),
+            order: "order1".to_string(),
+            deposits: Some(IndexMap::from([(token.to_string(), "1.5".to_string())])),
+            vault_id_mode: None,
+        };
+
+        let result = deployment_source("output")
+            .try_into_deployment("deployment1".to_string(), &scenarios, &orders, &tokens)
+            .unwrap();
+        assert_eq!(
+            result.deposits,
+            Some(vec![DeploymentDeposit {
+                token: output_token,
+                amount: "1.5".to_string(),
+            }])
+        );
+
+        let result = deployment_source("input").try_into_deployment(
+            "deployment1".to_string(),
+            &scenarios,
+            &orders,
+            &tokens,
+        );
+        assert_eq!(
+            result,
+            Err(ParseDeploymentConfigSourceError::DepositTokenNotOutput(
+                "input".to_string()
+            ))
+        );
+
+        let result = deployment_source("unknown").try_into_deployment(
+            "deployment1".to_string(),
+            &scenarios,
+            &orders,
+            &tokens,
+        );
+        assert_eq!(
+            result,
+            Err(ParseDeploymentConfigSourceError::DepositTokenNotFoundError(
+                "unknown".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_try_into_deployment_deposits_order() {
+        let scenario = Scenario {
+            name: "scenario1".into(),
+            bindings: HashMap::new(),
+            deployer: mock_deployer(),
+            runs: None,
+            blocks: None,
+        };
+        let tokens =
+            HashMap::from(["a", "b", "c"].map(|name| (name.to_string(), mock_token(name))));
+        let order = Order {
+            inputs: vec![],
+            outputs: ["a", "b", "c"]
+                .iter()
+                .map(|name| OrderIO {
+                    token: tokens[*name].clone(),
+                    vault_id: None,
+                })
+                .collect(),
+            network: mock_network(),
+            deployer: None,
+            orderbook: None,
+        };
+        let orders = HashMap::from([("order1".to_string(), Arc::new(order))]);
+        let scenarios = HashMap::from([("scenario1".to_string(), Arc::new(scenario))]);
+        let deployment_source: DeploymentConfigSource = serde_yaml::from_str(
+            r#"
+scenario: scenario1
+order: order1
+deposits:
+    c: "3"
+    a: "1"
+    b: "2"
+"#,
+        )
+        .unwrap();
+
+        // deposits keep the order they are listed in
+        let result = deployment_source
+            .try_into_deployment("deployment1".to_string(), &scenarios, &orders, &tokens)
+            .unwrap();
+        let amounts = result
+            .deposits
+            .unwrap()
+            .into_iter()
+            .map(|deposit| deposit.amount)
+            .collect::<Vec<_>>();
+        assert_eq!(amounts, vec!["3", "1", "2"]);
+    }
 }
@@ -0,0 +1,405 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use thiserror::Error;
+use url::Url;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EnvInterpolationError {
+    #[error("Environment variable '{0}' is not set")]
+    MissingVariable(String),
+    #[error("Unterminated placeholder in '{0}'")]
+    UnterminatedPlaceholder(String),
+    #[error("Invalid environment variable name '{0}'")]
+    InvalidVariableName(String),
+    #[error("Failed to read env file: {0}")]
+    EnvFileReadError(String),
+    #[error("Invalid line {0} in env file")]
+    InvalidEnvFileLine(usize),
+}
+
+/// Variables that `${VAR}` placeholders are resolved against
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvVars(pub HashMap<String, String>);
+
+impl EnvVars {
+    /// Variables of the current process
+    pub fn from_process() -> Self {
+        EnvVars(std::env::vars().collect())
+    }
+
+    /// Variables of the current process on top of those of a `.env` file,
+    /// ie process variables take precedence. The file is optional and
+    /// ignored if it does not exist.
+    pub fn from_process_and_env_file(path: &Path) -> Result<Self, EnvInterpolationError> {
+        let mut vars = if path.is_file() {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| EnvInterpolationError::EnvFileReadError(e.to_string()))?;
+            parse_env_file(&contents)?
+        } else {
+            HashMap::new()
+        };
+        vars.extend(std::env::vars());
+        Ok(EnvVars(vars))
+    }
+
+    /// Variables of the current process and of a `.env` file in the current
+    /// directory if there is one
+    pub fn load() -> Result<Self, EnvInterpolationError> {
+        Self::from_process_and_env_file(Path::new(".env"))
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(|v| v.as_str())
+    }
+}
+
+/// Parse the contents of a `.env` file, ie `KEY=value` lines with optional
+/// `export` prefixes, quotes and `#` comments
+pub fn parse_env_file(contents: &str) -> Result<HashMap<String, String>, EnvInterpolationError> {
+    let mut vars = HashMap::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(EnvInterpolationError::InvalidEnvFileLine(i + 1))?;
+        let key = key.trim();
+        if !is_valid_name(key) {
+            return Err(EnvInterpolationError::InvalidEnvFileLine(i + 1));
+        }
+        let value = value.trim();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..]
+                .split_once(quote)
+                .map(|(quoted, _)| quoted)
+                .ok_or(EnvInterpolationError::InvalidEnvFileLine(i + 1))?,
+            _ => value.split(" #").next().unwrap_or(value).trim_end(),
+        };
+        vars.insert(key.to_string(), value.to_string());
+    }
+    Ok(vars)
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Resolve `${VAR}` and `${VAR:-default}` placeholders of a string, `$${`
+/// escapes a literal `${`
+pub fn interpolate(value: &str, env: &EnvVars) -> Result<String, EnvInterpolationError> {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(escaped) = rest.strip_prefix("$${") {
+            result.push_str("${");
+            rest = escaped;
+        } else if let Some(placeholder) = rest.strip_prefix("${") {
+            let end =
+                placeholder
+                    .find('}')
+                    .ok_or(EnvInterpolationError::UnterminatedPlaceholder(
+                        value.to_string(),
+                    ))?;
+            let (name, default) = match placeholder[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&placeholder[..end], None),
+            };
+            if !is_valid_name(name) {
+                return Err(EnvInterpolationError::InvalidVariableName(name.to_string()));
+            }
+            match (env.get(name), default) {
+                (Some(v), Some(default)) if v.is_empty() => result.push_str(default),
+                (Some(v), _) => result.push_str(v),
+                (None, Some(default)) => result.push_str(default),
+                (None, None) => {
+                    return Err(EnvInterpolationError::MissingVariable(name.to_string()))
+                }
+            }
+            rest = &placeholder[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// A yaml value that had placeholders, kept so the placeholders can be
+/// written back when serializing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvInterpolation {
+    /// Keys and sequence indexes leading to the value from the yaml's root
+    pub path: Vec<String>,
+    pub raw: String,
+    pub resolved: String,
+}
+
+impl EnvInterpolation {
+    /// Whether a serialized scalar is this interpolation's resolved value,
+    /// also matching the normalized form of urls
+    fn matches(&self, scalar: &str) -> bool {
+        scalar == self.resolved
+            || Url::parse(&self.resolved).is_ok_and(|url| url.as_str() == scalar)
+    }
+
+    /// The interpolation relative to the value under the given key of the
+    /// root, if it is under that key
+    pub fn strip_prefix(&self, key: &str) -> Option<EnvInterpolation> {
+        match self.path.split_first() {
+            Some((first, rest)) if first == key => Some(EnvInterpolation {
+                path: rest.to_vec(),
+                raw: self.raw.clone(),
+                resolved: self.resolved.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Resolve the placeholders of all string scalars of a yaml value, returns
+/// the interpolations that were made. A scalar that is a single placeholder
+/// resolving to a number or bool is converted to it.
+pub fn interpolate_yaml(
+    value: &mut serde_yaml::Value,
+    env: &EnvVars,
+) -> Result<Vec<EnvInterpolation>, EnvInterpolationError> {
+    let mut interpolations = vec![];
+    interpolate_yaml_inner(value, env, &mut vec![], &mut interpolations)?;
+    Ok(interpolations)
+}
+
+fn interpolate_yaml_inner(
+    value: &mut serde_yaml::Value,
+    env: &EnvVars,
+    path: &mut Vec<String>,
+    interpolations: &mut Vec<EnvInterpolation>,
+) -> Result<(), EnvInterpolationError> {
+    match value {
+        serde_yaml::Value::String(s) if s.contains('$') => {
+            let resolved = interpolate(s, env)?;
+            if resolved != *s {
+                let is_single_placeholder =
+                    s.starts_with("${") && s.ends_with('}') && s.matches("${").count() == 1;
+                let new_value = match serde_yaml::from_str::<serde_yaml::Value>(&resolved) {
+                    Ok(v @ (serde_yaml::Value::Number(_) | serde_yaml::Value::Bool(_)))
+                        if is_single_placeholder =>
+                    {
+                        v
+                    }
+                    _ => serde_yaml::Value::String(resolved.clone()),
+                };
+                interpolations.push(EnvInterpolation {
+                    path: path.clone(),
+                    raw: s.clone(),
+                    resolved,
+                });
+                *value = new_value;
+            }
+        }
+        serde_yaml::Value::Sequence(seq) => {
+            for (i, item) in seq.iter_mut().enumerate() {
+                path.push(i.to_string());
+                interpolate_yaml_inner(item, env, path, interpolations)?;
+                path.pop();
+            }
+        }
+        serde_yaml::Value::Mapping(map) => {
+            for (key, item) in map.iter_mut() {
+                path.push(yaml_key(key));
+                interpolate_yaml_inner(item, env, path, interpolations)?;
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// A mapping key as the string it is serialized to as a json object key
+fn yaml_key(key: &serde_yaml::Value) -> String {
+    match key {
+        serde_yaml::Value::String(s) => s.clone(),
+        serde_yaml::Value::Number(n) => n.to_string(),
+        serde_yaml::Value::Bool(b) => b.to_string(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    }
+}
+
+/// Put the placeholders back in place of their resolved values, only at the
+/// paths they were resolved at and only if the value there is still the
+/// resolved one
+pub fn restore_placeholders(value: &mut serde_json::Value, interpolations: &[EnvInterpolation]) {
+    for interpolation in interpolations {
+        let Some(target) =
+            interpolation
+                .path
+                .iter()
+                .try_fold(&mut *value, |value, key| match value {
+                    serde_json::Value::Object(map) => map.get_mut(key),
+                    serde_json::Value::Array(items) => {
+                        key.parse::<usize>().ok().and_then(|i| items.get_mut(i))
+                    }
+                    _ => None,
+                })
+        else {
+            continue;
+        };
+        let scalar = match target {
+            serde_json::Value::String(s) => s.clone(),
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => continue,
+        };
+        if interpolation.matches(&scalar) {
+            *target = serde_json::Value::String(interpolation.raw.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> EnvVars {
+        EnvVars(HashMap::from([
+            ("API_KEY".to_string(), "secret".to_string()),
+            ("EMPTY".to_string(), "".to_string()),
+            ("CHAIN_ID".to_string(), "137".to_string()),
+        ]))
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(
+            interpolate("https://rpc.com/${API_KEY}", &env()).unwrap(),
+            "https://rpc.com/secret"
+        );
+        assert_eq!(
+            interpolate("${MISSING:-https://default.com}", &env()).unwrap(),
+            "https://default.com"
+        );
+        assert_eq!(
+            interpolate("${EMPTY:-fallback}", &env()).unwrap(),
+            "fallback"
+        );
+        assert_eq!(
+            interpolate("$${API_KEY} $5", &env()).unwrap(),
+            "${API_KEY} $5"
+        );
+        assert_eq!(
+            interpolate("${MISSING}", &env()),
+            Err(EnvInterpolationError::MissingVariable(
+                "MISSING".to_string()
+            ))
+        );
+        assert_eq!(
+            interpolate("${API_KEY", &env()),
+            Err(EnvInterpolationError::UnterminatedPlaceholder(
+                "${API_KEY".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let vars = parse_env_file(
+            r#"
+# comment
+API_KEY=secret
+export RPC="https://rpc.com/#path"
+OTHER='quoted' # trailing
+PLAIN=value # trailing
+"#,
+        )
+        .unwrap();
+        assert_eq!(vars.get("API_KEY").unwrap(), "secret");
+        assert_eq!(vars.get("RPC").unwrap(), "https://rpc.com/#path");
+        assert_eq!(vars.get("OTHER").unwrap(), "quoted");
+        assert_eq!(vars.get("PLAIN").unwrap(), "value");
+
+        assert_eq!(
+            parse_env_file("NOT A LINE"),
+            Err(EnvInterpolationError::InvalidEnvFileLine(1))
+        );
+    }
+
+    #[test]
+    fn test_interpolate_yaml_and_restore() {
+        let mut value: serde_yaml::Value = serde_yaml::from_str(
+            r#"
+rpc: https://rpc.com/${API_KEY}
+chain-id: ${CHAIN_ID}
+network-id: ${MISSING:-1}
+label: plain
+rpcs:
+  - https://other.com/${API_KEY}
+"#,
+        )
+        .unwrap();
+        let interpolations = interpolate_yaml(&mut value, &env()).unwrap();
+        assert_eq!(interpolations.len(), 4);
+        assert_eq!(interpolations[0].path, vec!["rpc".to_string()]);
+        assert_eq!(
+            interpolations[3].path,
+            vec!["rpcs".to_string(), "0".to_string()]
+        );
+        assert_eq!(value["rpc"].as_str().unwrap(), "https://rpc.com/secret");
+        assert_eq!(value["chain-id"].as_u64().unwrap(), 137);
+        assert_eq!(value["network-id"].as_u64().unwrap(), 1);
+
+        // values equal to a resolved value elsewhere are left as they are
+        let mut json = serde_json::json!({
+            "rpc": "https://rpc.com/secret",
+            "chain-id": 137,
+            "network-id": 1,
+            "label": "plain",
+            "rpcs": ["https://other.com/secret"],
+            "decimals": 1,
+            "other-rpc": "https://rpc.com/secret",
+        });
+        restore_placeholders(&mut json, &interpolations);
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "rpc": "https://rpc.com/${API_KEY}",
+                "chain-id": "${CHAIN_ID}",
+                "network-id": "${MISSING:-1}",
+                "label": "plain",
+                "rpcs": ["https://other.com/${API_KEY}"],
+                "decimals": 1,
+                "other-rpc": "https://rpc.com/secret",
+            })
+        );
+
+        // a value that changed since it was resolved is kept
+        let mut json = serde_json::json!({ "chain-id": 1 });
+        restore_placeholders(&mut json, &interpolations);
+        assert_eq!(json, serde_json::json!({ "chain-id": 1 }));
+    }
+
+    #[test]
+    fn test_env_interpolation_strip_prefix() {
+        let interpolation = EnvInterpolation {
+            path: vec!["main".to_string(), "rpc".to_string()],
+            raw: "${RPC}".to_string(),
+            resolved: "https://rpc.com".to_string(),
+        };
+        assert_eq!(
+            interpolation.strip_prefix("main").unwrap().path,
+            vec!["rpc".to_string()]
+        );
+        assert!(interpolation.strip_prefix("top-config").is_none());
+    }
+}
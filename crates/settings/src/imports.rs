@@ -0,0 +1,348 @@
+use crate::config_source::{ConfigSource, ConfigSourceError, ParseContext};
+use std::fmt;
+use std::path::PathBuf;
+use thiserror::Error;
+use url::Url;
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("Import cycle detected: {0}")]
+    Cycle(String),
+    #[error("Failed to read import {0}: {1}")]
+    ReadError(String, String),
+    #[error("Failed to fetch import {0}: {1}")]
+    FetchError(String, reqwest::Error),
+    #[error("Invalid import url {0}: {1}")]
+    InvalidUrl(String, url::ParseError),
+    #[error("Cannot fetch {0} while offline")]
+    Offline(String),
+}
+
+/// Where relative imports are resolved from, ie the directory or url of the
+/// importing yml
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportBase {
+    Dir(PathBuf),
+    Url(Url),
+}
+
+impl Default for ImportBase {
+    fn default() -> Self {
+        ImportBase::Dir(PathBuf::from("."))
+    }
+}
+
+/// Resolved location of an imported yml
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportLocation {
+    Path(PathBuf),
+    Url(Url),
+}
+
+impl fmt::Display for ImportLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportLocation::Path(path) => write!(f, "{}", path.display()),
+            ImportLocation::Url(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+impl ImportBase {
+    /// Resolve an import, absolute urls and `file://` paths are used as is
+    /// and anything else is relative to this base
+    pub fn resolve(&self, import: &str) -> Result<ImportLocation, ImportError> {
+        if let Ok(url) = Url::parse(import) {
+            if url.scheme() == "http" || url.scheme() == "https" {
+                return Ok(ImportLocation::Url(url));
+            }
+        }
+        if let Some(path) = import.strip_prefix("file://") {
+            return Ok(ImportLocation::Path(PathBuf::from(path)));
+        }
+        match self {
+            ImportBase::Url(base) => base
+                .join(import)
+                .map(ImportLocation::Url)
+                .map_err(|e| ImportError::InvalidUrl(import.to_string(), e)),
+            ImportBase::Dir(dir) => {
+                let path = dir.join(import);
+                Ok(ImportLocation::Path(
+                    std::fs::canonicalize(&path).unwrap_or(path),
+                ))
+            }
+        }
+    }
+}
+
+impl ImportLocation {
+    /// Base that imports of the yml at this location are resolved from
+    pub fn base(&self) -> ImportBase {
+        match self {
+            ImportLocation::Path(path) => {
+                ImportBase::Dir(path.parent().map(|v| v.to_path_buf()).unwrap_or_default())
+            }
+            ImportLocation::Url(url) => ImportBase::Url(url.clone()),
+        }
+    }
+
+    pub async fn read(&self) -> Result<String, ImportError> {
+        match self {
+            ImportLocation::Path(path) => std::fs::read_to_string(path)
+                .map_err(|e| ImportError::ReadError(self.to_string(), e.to_string())),
+            ImportLocation::Url(url) => async {
+                reqwest::get(url.clone())
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await
+            }
+            .await
+            .map_err(|e| ImportError::FetchError(self.to_string(), e)),
+        }
+    }
+}
+
+/// Resolve the `imports` of a config recursively and merge the imported
+/// configs into it with the usual collision rules. A yml imported more than
+/// once through different paths is only merged once, while a yml that
+/// imports itself through its own imports is an error.
+pub async fn resolve_imports(
+    config: &mut ConfigSource,
+    context: &ParseContext,
+) -> Result<(), ConfigSourceError> {
+    resolve_imports_skipping(config, context, &mut vec![]).await
+}
+
+/// Same as [resolve_imports] but skips the ymls that are already in
+/// `imported`, and adds the ones it imports to it, so ymls are merged only
+/// once across several configs
+pub async fn resolve_imports_skipping(
+    config: &mut ConfigSource,
+    context: &ParseContext,
+    imported: &mut Vec<ImportLocation>,
+) -> Result<(), ConfigSourceError> {
+    // pending imports with the chain of imports that led to them, starting
+    // at the config's own location if known
+    let root_chain: Vec<ImportLocation> = context.location.iter().cloned().collect();
+    let mut pending: Vec<(ImportBase, String, Vec<ImportLocation>)> = config
+        .imports
+        .drain(..)
+        .rev()
+        .map(|import| (context.import_base.clone(), import, root_chain.clone()))
+        .collect();
+
+    while let Some((base, import, chain)) = pending.pop() {
+        let location = base.resolve(&import)?;
+        if chain.contains(&location) {
+            let mut cycle: Vec<String> = chain.iter().map(|v| v.to_string()).collect();
+            cycle.push(location.to_string());
+            return Err(ImportError::Cycle(cycle.join(" -> ")).into());
+        }
+        if imported.contains(&location) {
+            continue;
+        }
+
+        let text = context.read(&location).await?;
+        let imported_base = location.base();
+        let imported_context = ParseContext {
+            import_base: imported_base.clone(),
+            location: Some(location.clone()),
+            ..context.clone()
+        };
+        let mut imported_config =
+            ConfigSource::try_from_single_string(text, &imported_context).await?;
+
+        let mut chain = chain;
+        chain.push(location.clone());
+        for nested in imported_config.imports.drain(..).rev() {
+            pending.push((imported_base.clone(), nested, chain.clone()));
+        }
+
+        config.merge(imported_config)?;
+        imported.push(location);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::GET, MockServer};
+    use tempfile::TempDir;
+
+    fn context(dir: &std::path::Path) -> ParseContext {
+        ParseContext {
+            import_base: ImportBase::Dir(dir.to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    fn write(dir: &std::path::Path, name: &str, contents: &str) {
+        std::fs::write(dir.join(name), contents).unwrap();
+    }
+
+    const NETWORKS: &str = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+"#;
+
+    #[tokio::test]
+    async fn test_resolve_imports() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "networks.yml", NETWORKS);
+        write(
+            dir.path(),
+            "tokens.yml",
+            r#"
+imports:
+    - ./networks.yml
+tokens:
+    eth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000003
+"#,
+        );
+
+        let mut config: ConfigSource = serde_yaml::from_str(
+            r#"
+imports:
+    - ./tokens.yml
+    - ./networks.yml
+subgraphs:
+    mainnet: https://mainnet.subgraph
+"#,
+        )
+        .unwrap();
+        resolve_imports(&mut config, &context(dir.path()))
+            .await
+            .unwrap();
+
+        assert!(config.imports.is_empty());
+        assert!(config.networks.contains_key("mainnet"));
+        assert!(config.tokens.contains_key("eth"));
+        assert!(config.subgraphs.contains_key("mainnet"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_imports_relative_to_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        write(&dir.path().join("shared"), "networks.yml", NETWORKS);
+        let settings_path = dir.path().join("settings.yml");
+        let settings = "imports:\n    - ./shared/networks.yml\n";
+        write(dir.path(), "settings.yml", settings);
+
+        let context = ParseContext::load_for_file(&settings_path).unwrap();
+        let config =
+            ConfigSource::try_from_string_with_context(settings.to_string(), None, &context)
+                .await
+                .unwrap()
+                .0;
+        assert!(config.networks.contains_key("mainnet"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_imports_cycle() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "a.yml", "imports:\n    - ./b.yml\n");
+        write(dir.path(), "b.yml", "imports:\n    - ./a.yml\n");
+
+        let mut config: ConfigSource = serde_yaml::from_str("imports:\n    - ./a.yml\n").unwrap();
+        let err = resolve_imports(&mut config, &context(dir.path()))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigSourceError::ImportError(ImportError::Cycle(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_imports_self_import() {
+        let dir = TempDir::new().unwrap();
+        let settings = format!("imports:\n    - ./settings.yml\n{}", NETWORKS);
+        write(dir.path(), "settings.yml", &settings);
+
+        let context = ParseContext::load_for_file(&dir.path().join("settings.yml")).unwrap();
+        let err = ConfigSource::try_from_string_with_context(settings, None, &context)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigSourceError::ImportError(ImportError::Cycle(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_imports_shared_by_main_and_top_config() {
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "networks.yml", NETWORKS);
+
+        let (mut main, top_config) = ConfigSource::try_from_string_with_context(
+            "imports:\n    - ./networks.yml\n".to_string(),
+            Some("imports:\n    - ./networks.yml\n".to_string()),
+            &context(dir.path()),
+        )
+        .await
+        .unwrap();
+        assert!(main.networks.contains_key("mainnet"));
+        assert!(top_config.networks.is_empty());
+        main.merge(top_config).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_imports_collision() {
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "networks.yml",
+            "networks:\n    mainnet:\n        rpc: https://other.node\n        chain-id: 1\n",
+        );
+
+        let mut config: ConfigSource = serde_yaml::from_str(
+            r#"
+imports:
+    - ./networks.yml
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+"#,
+        )
+        .unwrap();
+        let err = resolve_imports(&mut config, &context(dir.path()))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ConfigSourceError::MergeError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_url_imports() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(GET).path("/shared/tokens.yml");
+            then.body("imports:\n    - ./networks.yml\ntokens:\n    eth:\n        network: mainnet\n        address: 0xabc0000000000000000000000000000000000003\n");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/shared/networks.yml");
+            then.body(
+                "networks:\n    mainnet:\n        rpc: https://mainnet.node\n        chain-id: 1\n",
+            );
+        });
+
+        let mut config: ConfigSource = serde_yaml::from_str(&format!(
+            "imports:\n    - {}\n",
+            server.url("/shared/tokens.yml")
+        ))
+        .unwrap();
+        resolve_imports(&mut config, &ParseContext::default())
+            .await
+            .unwrap();
+
+        assert!(config.networks.contains_key("mainnet"));
+        assert!(config.tokens.contains_key("eth"));
+    }
+}
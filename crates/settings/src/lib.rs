@@ -4,15 +4,22 @@ pub mod config;
 pub mod config_source;
 pub mod deployer;
 pub mod deployment;
+pub mod env;
+pub mod imports;
 pub mod merge;
 pub mod network;
 pub mod order;
 pub mod orderbook;
 pub mod plot_source;
 pub mod remote;
+pub mod rpc;
 pub mod scenario;
+pub mod schema;
+pub mod span;
 pub mod token;
+pub mod token_metadata;
 pub mod unit_test;
+pub mod validate;
 
 pub(crate) use chart::*;
 pub(crate) use config_source::*;
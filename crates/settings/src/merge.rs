@@ -1,5 +1,7 @@
 #![allow(clippy::map_entry)]
 use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -39,6 +41,103 @@ pub enum MergeError {
 
     #[error("There is already a accounts called {0}")]
     AccountsCollision(String),
+
+    #[error("Failed to merge config values: {0}")]
+    ValueError(String),
+}
+
+/// How sources defining the same key are merged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// Defining the same key twice is a collision error
+    #[default]
+    Strict,
+    /// Later sources override earlier ones field by field
+    Override,
+}
+
+/// Source each value of a merged config came from, keyed by the dotted path
+/// of the value, eg `networks.mainnet.rpc`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Provenance(pub BTreeMap<String, String>);
+
+impl Provenance {
+    pub fn get(&self, path: &str) -> Option<&String> {
+        self.0.get(path)
+    }
+
+    /// Sources of the value at a path, or of the values under it
+    pub fn sources_of(&self, path: &str) -> Vec<&str> {
+        let prefix = format!("{}.", path);
+        let mut sources: Vec<&str> = self
+            .0
+            .iter()
+            .filter(|(k, _)| *k == path || k.starts_with(&prefix))
+            .map(|(_, v)| v.as_str())
+            .collect();
+        sources.sort();
+        sources.dedup();
+        sources
+    }
+
+    /// Record the source of all leaf values under a path, dropping what was
+    /// recorded for the values it replaces
+    fn record(&mut self, path: &str, value: &serde_json::Value, source: &str) {
+        let prefix = format!("{}.", path);
+        self.0.retain(|k, _| k != path && !k.starts_with(&prefix));
+        self.record_leaves(path, value, source);
+    }
+
+    fn record_leaves(&mut self, path: &str, value: &serde_json::Value, source: &str) {
+        match value {
+            serde_json::Value::Object(map) if !map.is_empty() => {
+                for (key, item) in map {
+                    self.record_leaves(&join_path(path, key), item, source);
+                }
+            }
+            _ if path.is_empty() => {}
+            _ => {
+                self.0.insert(path.to_string(), source.to_string());
+            }
+        }
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Deep merge `other` into `base`, objects are merged key by key and any
+/// other value, including lists, is replaced
+pub(crate) fn merge_values(
+    base: &mut serde_json::Value,
+    other: serde_json::Value,
+    path: &str,
+    source: &str,
+    provenance: &mut Provenance,
+) {
+    match (base, other) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(other)) => {
+            for (key, value) in other {
+                let item_path = join_path(path, &key);
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value, &item_path, source, provenance),
+                    None => {
+                        provenance.record(&item_path, &value, source);
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, other) => {
+            provenance.record(path, &other, source);
+            *base = other;
+        }
+    }
 }
 
 impl ConfigSource {
@@ -166,8 +265,61 @@ impl ConfigSource {
             _ => {}
         }
 
+        // Env interpolations
+        for interpolation in other.env_interpolations {
+            if !self.env_interpolations.contains(&interpolation) {
+                self.env_interpolations.push(interpolation);
+            }
+        }
+
         Ok(())
     }
+
+    /// Merge a partial source on top of this one, overriding values field
+    /// by field and recording the source of the values it sets
+    pub fn merge_override(
+        &mut self,
+        other: PartialConfigSource,
+        source: &str,
+        provenance: &mut Provenance,
+    ) -> Result<(), MergeError> {
+        let mut env_interpolations = std::mem::take(&mut self.env_interpolations);
+        for interpolation in &other.env_interpolations {
+            if !env_interpolations.contains(interpolation) {
+                env_interpolations.push(interpolation.clone());
+            }
+        }
+
+        let mut value = self
+            .to_raw_json()
+            .map_err(|e| MergeError::ValueError(e.to_string()))?;
+        merge_values(&mut value, other.value, "", source, provenance);
+
+        *self = serde_json::from_value(value).map_err(|e| MergeError::ValueError(e.to_string()))?;
+        self.env_interpolations = env_interpolations;
+        Ok(())
+    }
+
+    /// Merge named sources in order with the given strategy, returning the
+    /// merged config and the source each of its values came from. Strict
+    /// merging needs each source to be a complete config.
+    pub fn merge_all(
+        sources: Vec<(String, PartialConfigSource)>,
+        strategy: MergeStrategy,
+    ) -> Result<(ConfigSource, Provenance), MergeError> {
+        let mut merged = ConfigSource::default();
+        let mut provenance = Provenance::default();
+        for (name, source) in sources {
+            match strategy {
+                MergeStrategy::Strict => {
+                    provenance.record("", &source.value, &name);
+                    merged.merge(source.try_into()?)?;
+                }
+                MergeStrategy::Override => merged.merge_override(source, &name, &mut provenance)?,
+            }
+        }
+        Ok((merged, provenance))
+    }
 }
 
 impl Config {
@@ -300,6 +452,7 @@ mod tests {
     fn test_successful_merge() {
         let mut config = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             subgraphs: HashMap::new(),
             metaboards: HashMap::new(),
@@ -313,10 +466,12 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None,
+            env_interpolations: vec![],
         };
 
         let other = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             subgraphs: HashMap::new(),
             metaboards: HashMap::new(),
@@ -330,6 +485,7 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None,
+            env_interpolations: vec![],
         };
 
         assert_eq!(config.merge(other), Ok(()));
@@ -339,6 +495,7 @@ mod tests {
     fn test_unsuccessful_merge() {
         let mut config = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             subgraphs: HashMap::new(),
             metaboards: HashMap::new(),
@@ -352,10 +509,12 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None,
+            env_interpolations: vec![],
         };
 
         let mut other = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             subgraphs: HashMap::new(),
             metaboards: HashMap::new(),
@@ -369,6 +528,7 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None,
+            env_interpolations: vec![],
         };
 
         // Add a collision to cause an unsuccessful merge
@@ -392,6 +552,7 @@ mod tests {
     fn test_successful_merge_metaboard() {
         let mut config = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             subgraphs: HashMap::new(),
             metaboards: HashMap::new(),
@@ -405,10 +566,12 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None,
+            env_interpolations: vec![],
         };
 
         let mut other = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             subgraphs: HashMap::new(),
             metaboards: HashMap::new(),
@@ -422,6 +585,7 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None,
+            env_interpolations: vec![],
         };
 
         other.metaboards.insert(
@@ -438,4 +602,90 @@ mod tests {
             Some(&Url::parse("https://myurl").unwrap())
         );
     }
+
+    #[tokio::test]
+    async fn test_merge_override_with_provenance() {
+        let context = ParseContext::default();
+        let base = PartialConfigSource::try_from_string(
+            r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+        label: Mainnet
+    testnet:
+        rpc: https://testnet.node
+        chain-id: 2
+"#
+            .to_string(),
+            &context,
+        )
+        .await
+        .unwrap();
+        let local = PartialConfigSource::try_from_string(
+            r#"
+networks:
+    mainnet:
+        rpc: https://my-private.node
+subgraphs:
+    mainnet: https://mainnet.subgraph
+"#
+            .to_string(),
+            &context,
+        )
+        .await
+        .unwrap();
+
+        let (merged, provenance) = ConfigSource::merge_all(
+            vec![
+                ("settings.yml".to_string(), base.clone()),
+                ("local.yml".to_string(), local.clone()),
+            ],
+            MergeStrategy::Override,
+        )
+        .unwrap();
+
+        let mainnet = merged.networks.get("mainnet").unwrap();
+        assert_eq!(
+            mainnet.rpc,
+            Url::parse("https://my-private.node").unwrap().into()
+        );
+        assert_eq!(mainnet.chain_id, 1);
+        assert_eq!(mainnet.label, Some("Mainnet".to_string()));
+        assert!(merged.networks.contains_key("testnet"));
+
+        assert_eq!(provenance.get("networks.mainnet.rpc").unwrap(), "local.yml");
+        assert_eq!(
+            provenance.get("networks.mainnet.chain-id").unwrap(),
+            "settings.yml"
+        );
+        assert_eq!(
+            provenance.get("networks.mainnet.label").unwrap(),
+            "settings.yml"
+        );
+        assert_eq!(
+            provenance.get("networks.testnet.rpc").unwrap(),
+            "settings.yml"
+        );
+        assert_eq!(provenance.get("subgraphs.mainnet").unwrap(), "local.yml");
+
+        // the local override alone is not a complete config
+        assert!(matches!(
+            ConfigSource::try_from(local),
+            Err(MergeError::ValueError(_))
+        ));
+
+        // strict merging of sources defining the same network collides
+        assert_eq!(
+            ConfigSource::merge_all(
+                vec![
+                    ("settings.yml".to_string(), base.clone()),
+                    ("other.yml".to_string(), base),
+                ],
+                MergeStrategy::Strict,
+            )
+            .unwrap_err(),
+            MergeError::NetworkCollision("mainnet".to_string())
+        );
+    }
 }
@@ -12,6 +12,10 @@ pub struct Network {
     pub name: String,
     #[typeshare(typescript(type = "string"))]
     pub rpc: Url,
+    /// Rpc urls to fall back on in order when `rpc` fails
+    #[typeshare(typescript(type = "string[]"))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_rpcs: Vec<Url>,
     #[typeshare(typescript(type = "number"))]
     pub chain_id: u64,
     pub label: Option<String>,
@@ -24,12 +28,21 @@ impl Network {
         Network {
             name: "".to_string(),
             rpc: Url::parse("http://rpc.com").unwrap(),
+            fallback_rpcs: vec![],
             chain_id: 1,
             label: None,
             network_id: None,
             currency: None,
         }
     }
+
+    /// All rpc urls of the network in the order they should be tried
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(&self.rpc)
+            .chain(self.fallback_rpcs.iter())
+            .map(|url| url.to_string())
+            .collect()
+    }
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -40,13 +53,18 @@ pub enum ParseNetworkConfigSourceError {
     ChainIdParseError(ParseIntError),
     #[error("Failed to parse network_id: {}", 0)]
     NetworkIdParseError(ParseIntError),
+    #[error("Network has no rpc urls")]
+    NoRpc,
 }
 
 impl NetworkConfigSource {
     pub fn try_into_network(self, name: String) -> Result<Network, ParseNetworkConfigSourceError> {
+        let mut rpcs = self.rpc.urls_by_priority().into_iter();
+        let rpc = rpcs.next().ok_or(ParseNetworkConfigSourceError::NoRpc)?;
         Ok(Network {
             name,
-            rpc: self.rpc,
+            rpc,
+            fallback_rpcs: rpcs.collect(),
             chain_id: self.chain_id,
             label: self.label,
             network_id: self.network_id,
@@ -63,7 +81,7 @@ mod tests {
     #[test]
     fn test_try_from_network_string_success() {
         let network_string = NetworkConfigSource {
-            rpc: Url::parse("http://127.0.0.1:8545").unwrap(),
+            rpc: Url::parse("http://127.0.0.1:8545").unwrap().into(),
             chain_id: 1,
             network_id: Some(1),
             label: Some("Local Testnet".into()),
@@ -80,5 +98,80 @@ mod tests {
         assert_eq!(network.label, Some("Local Testnet".into()));
         assert_eq!(network.currency, Some("ETH".into()));
         assert_eq!(network.name, "local");
+        assert!(network.fallback_rpcs.is_empty());
+    }
+
+    #[test]
+    fn test_try_from_network_with_multiple_rpcs() {
+        let network_source = NetworkConfigSource {
+            rpc: RpcConfigSource::Multiple(vec![
+                RpcEndpointConfigSource::Url(Url::parse("http://rpc-c.com").unwrap()),
+                RpcEndpointConfigSource::WithPriority {
+                    url: Url::parse("http://rpc-b.com").unwrap(),
+                    priority: Some(2),
+                },
+                RpcEndpointConfigSource::WithPriority {
+                    url: Url::parse("http://rpc-a.com").unwrap(),
+                    priority: Some(1),
+                },
+            ]),
+            chain_id: 1,
+            network_id: None,
+            label: None,
+            currency: None,
+        };
+
+        let network = network_source.try_into_network("local".into()).unwrap();
+        assert_eq!(network.rpc, Url::parse("http://rpc-a.com").unwrap());
+        assert_eq!(
+            network.rpc_urls(),
+            vec![
+                "http://rpc-a.com/".to_string(),
+                "http://rpc-b.com/".to_string(),
+                "http://rpc-c.com/".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_try_from_network_without_rpcs() {
+        let network_source = NetworkConfigSource {
+            rpc: RpcConfigSource::Multiple(vec![]),
+            chain_id: 1,
+            network_id: None,
+            label: None,
+            currency: None,
+        };
+
+        let result = network_source.try_into_network("local".into());
+        assert_eq!(result, Err(ParseNetworkConfigSourceError::NoRpc));
+    }
+
+    #[test]
+    fn test_parse_rpc_forms() {
+        let single: NetworkConfigSource =
+            serde_yaml::from_str("rpc: https://rpc-a.com\nchain-id: 1").unwrap();
+        assert_eq!(
+            single.rpc,
+            RpcConfigSource::Single(Url::parse("https://rpc-a.com").unwrap())
+        );
+
+        let multiple: NetworkConfigSource = serde_yaml::from_str(
+            r#"
+rpc:
+    - https://rpc-a.com
+    - url: https://rpc-b.com
+      priority: 1
+chain-id: 1
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            multiple.rpc.urls_by_priority(),
+            vec![
+                Url::parse("https://rpc-b.com").unwrap(),
+                Url::parse("https://rpc-a.com").unwrap(),
+            ]
+        );
     }
 }
@@ -47,129 +47,106 @@ pub enum ParseOrderConfigSourceError {
     VaultParseError(#[from] alloy::primitives::ruint::ParseError),
 }
 
+impl Order {
+    /// Check the deployer, orderbook and tokens of the order are all on the
+    /// order's network
+    pub fn check_networks(&self) -> Result<(), ParseOrderConfigSourceError> {
+        let mut networks = self
+            .deployer
+            .iter()
+            .map(|v| &v.network)
+            .chain(self.orderbook.iter().map(|v| &v.network))
+            .chain(
+                self.inputs
+                    .iter()
+                    .chain(self.outputs.iter())
+                    .map(|v| &v.token.network),
+            );
+        if networks.all(|network| *network == self.network) {
+            Ok(())
+        } else {
+            Err(ParseOrderConfigSourceError::NetworkNotMatch)
+        }
+    }
+}
+
 impl OrderConfigSource {
+    /// Resolve the references of the order, without checking they are all
+    /// on the same network, see [Order::check_networks]
     pub fn try_into_order(
         self,
         deployers: &HashMap<String, Arc<Deployer>>,
         orderbooks: &HashMap<String, Arc<Orderbook>>,
         tokens: &HashMap<String, Arc<Token>>,
     ) -> Result<Order, ParseOrderConfigSourceError> {
-        let mut network = None;
-
         let deployer = self
             .deployer
             .map(|deployer_name| {
-                deployers
-                    .get(&deployer_name)
-                    .ok_or(ParseOrderConfigSourceError::DeployerParseError(
+                deployers.get(&deployer_name).cloned().ok_or(
+                    ParseOrderConfigSourceError::DeployerParseError(
                         ParseDeployerConfigSourceError::NetworkNotFoundError(deployer_name.clone()),
-                    ))
-                    .map(|v| {
-                        if let Some(n) = &network {
-                            if v.network == *n {
-                                Ok(v.clone())
-                            } else {
-                                Err(ParseOrderConfigSourceError::NetworkNotMatch)
-                            }
-                        } else {
-                            network = Some(v.network.clone());
-                            Ok(v.clone())
-                        }
-                    })?
+                    ),
+                )
             })
             .transpose()?;
 
         let orderbook = self
             .orderbook
             .map(|orderbook_name| {
-                orderbooks
-                    .get(&orderbook_name)
-                    .ok_or(ParseOrderConfigSourceError::OrderbookParseError(
+                orderbooks.get(&orderbook_name).cloned().ok_or(
+                    ParseOrderConfigSourceError::OrderbookParseError(
                         ParseOrderbookConfigSourceError::NetworkNotFoundError(
                             orderbook_name.clone(),
                         ),
-                    ))
-                    .map(|v| {
-                        if let Some(n) = &network {
-                            if v.network == *n {
-                                Ok(v.clone())
-                            } else {
-                                Err(ParseOrderConfigSourceError::NetworkNotMatch)
-                            }
-                        } else {
-                            network = Some(v.network.clone());
-                            Ok(v.clone())
-                        }
-                    })?
+                    ),
+                )
             })
             .transpose()?;
 
+        let into_io = |io: IOString| {
+            tokens
+                .get(&io.token)
+                .map(|token| OrderIO {
+                    token: token.clone(),
+                    vault_id: io.vault_id,
+                })
+                .ok_or(ParseOrderConfigSourceError::TokenParseError(
+                    ParseTokenConfigSourceError::NetworkNotFoundError(io.token.clone()),
+                ))
+        };
         let inputs = self
             .inputs
             .into_iter()
-            .map(|input| {
-                tokens
-                    .get(&input.token)
-                    .ok_or(ParseOrderConfigSourceError::TokenParseError(
-                        ParseTokenConfigSourceError::NetworkNotFoundError(input.token.clone()),
-                    ))
-                    .map(|v| {
-                        if let Some(n) = &network {
-                            if v.network == *n {
-                                Ok(OrderIO {
-                                    token: v.clone(),
-                                    vault_id: input.vault_id,
-                                })
-                            } else {
-                                Err(ParseOrderConfigSourceError::NetworkNotMatch)
-                            }
-                        } else {
-                            network = Some(v.network.clone());
-                            Ok(OrderIO {
-                                token: v.clone(),
-                                vault_id: input.vault_id,
-                            })
-                        }
-                    })?
-            })
+            .map(into_io)
             .collect::<Result<Vec<_>, _>>()?;
-
         let outputs = self
             .outputs
             .into_iter()
-            .map(|output| {
-                tokens
-                    .get(&output.token)
-                    .ok_or(ParseOrderConfigSourceError::TokenParseError(
-                        ParseTokenConfigSourceError::NetworkNotFoundError(output.token.clone()),
-                    ))
-                    .map(|v| {
-                        if let Some(n) = &network {
-                            if v.network == *n {
-                                Ok(OrderIO {
-                                    token: v.clone(),
-                                    vault_id: output.vault_id,
-                                })
-                            } else {
-                                Err(ParseOrderConfigSourceError::NetworkNotMatch)
-                            }
-                        } else {
-                            network = Some(v.network.clone());
-                            Ok(OrderIO {
-                                token: v.clone(),
-                                vault_id: output.vault_id,
-                            })
-                        }
-                    })?
-            })
+            .map(into_io)
             .collect::<Result<Vec<_>, _>>()?;
 
+        // the order is on the network of the first of its deployer, orderbook
+        // and tokens
+        let network = deployer
+            .iter()
+            .map(|v| &v.network)
+            .chain(orderbook.iter().map(|v| &v.network))
+            .chain(
+                inputs
+                    .iter()
+                    .chain(outputs.iter())
+                    .map(|v| &v.token.network),
+            )
+            .next()
+            .cloned()
+            .ok_or(ParseOrderConfigSourceError::NetworkNotFoundError(
+                String::new(),
+            ))?;
+
         Ok(Order {
             inputs,
             outputs,
-            network: network.ok_or(ParseOrderConfigSourceError::NetworkNotFoundError(
-                String::new(),
-            ))?,
+            network,
             deployer,
             orderbook,
         })
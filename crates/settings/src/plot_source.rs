@@ -1,8 +1,9 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use typeshare::typeshare;
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct Plot {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -28,7 +29,7 @@ pub struct Plot {
     pub inset: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[typeshare]
 #[serde(tag = "type", content = "options")]
 #[serde(rename_all = "lowercase")]
@@ -38,7 +39,7 @@ pub enum Mark {
     RectY(RectYOptions),
 }
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct DotOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub x: Option<String>,
@@ -55,7 +56,7 @@ pub struct DotOptions {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct LineOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub x: Option<String>,
@@ -73,7 +74,7 @@ pub struct LineOptions {
 
 // RectY mark
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct RectYOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub x0: Option<String>,
@@ -89,7 +90,7 @@ pub struct RectYOptions {
 
 // AxisX mark
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct AxisOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -103,7 +104,7 @@ pub struct AxisOptions {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(tag = "type", content = "content")]
 #[serde(rename_all = "lowercase")]
 pub enum Transform {
@@ -113,7 +114,7 @@ pub enum Transform {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct TransformOutputs {
     #[serde(skip_serializing_if = "Option::is_none")]
     x: Option<String>,
@@ -130,14 +131,14 @@ pub struct TransformOutputs {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct HexBinTransform {
     outputs: TransformOutputs,
     options: HexBinOptions,
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct HexBinOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -149,14 +150,14 @@ pub struct HexBinOptions {
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct BinXTransform {
     outputs: TransformOutputs,
     options: BinXOptions,
 }
 
 #[typeshare]
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct BinXOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     x: Option<String>,
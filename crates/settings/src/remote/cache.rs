@@ -0,0 +1,80 @@
+use alloy::primitives::{hex, keccak256};
+use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
+
+/// On disk cache of fetched remote network lists, one file per url
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteNetworksCache {
+    pub dir: PathBuf,
+    pub ttl: Duration,
+}
+
+impl RemoteNetworksCache {
+    /// Cache in the given directory or in one in the temp directory
+    pub fn new(dir: Option<PathBuf>, ttl: Duration) -> Self {
+        RemoteNetworksCache {
+            dir: dir.unwrap_or_else(|| {
+                std::env::temp_dir()
+                    .join("rain-orderbook")
+                    .join("remote-networks")
+            }),
+            ttl,
+        }
+    }
+
+    fn path(&self, url: &Url) -> PathBuf {
+        self.dir
+            .join(format!("{}.cache", hex::encode(keccak256(url.as_str()))))
+    }
+
+    /// Cached contents of a url if they are not older than the ttl
+    pub fn get_fresh(&self, url: &Url) -> Option<String> {
+        let path = self.path(url);
+        let age = std::fs::metadata(&path)
+            .ok()?
+            .modified()
+            .ok()?
+            .elapsed()
+            .ok()?;
+        if age < self.ttl {
+            std::fs::read_to_string(path).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Cached contents of a url regardless of their age
+    pub fn get_stale(&self, url: &Url) -> Option<String> {
+        std::fs::read_to_string(self.path(url)).ok()
+    }
+
+    pub fn put(&self, url: &Url, contents: &str) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(url), contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cache() {
+        let dir = TempDir::new().unwrap();
+        let url = Url::parse("https://chainid.network/chains.json").unwrap();
+        let other_url = Url::parse("https://other.com/chains.json").unwrap();
+
+        let cache =
+            RemoteNetworksCache::new(Some(dir.path().to_path_buf()), Duration::from_secs(3600));
+        assert_eq!(cache.get_fresh(&url), None);
+        cache.put(&url, "[]").unwrap();
+        assert_eq!(cache.get_fresh(&url), Some("[]".to_string()));
+        assert_eq!(cache.get_fresh(&other_url), None);
+
+        let expired = RemoteNetworksCache::new(Some(dir.path().to_path_buf()), Duration::ZERO);
+        assert_eq!(expired.get_fresh(&url), None);
+        assert_eq!(expired.get_stale(&url), Some("[]".to_string()));
+    }
+}
@@ -72,7 +72,7 @@ impl TryFrom<ChainId> for NetworkConfigSource {
             if !rpc.path().contains("API_KEY") && !rpc.scheme().starts_with("ws") {
                 return Ok(NetworkConfigSource {
                     chain_id: value.chain_id,
-                    rpc: rpc.clone(),
+                    rpc: rpc.clone().into(),
                     network_id: Some(value.network_id),
                     currency: Some(value.native_currency.symbol),
                     label: Some(value.name),
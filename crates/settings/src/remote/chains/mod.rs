@@ -1,5 +1,12 @@
+use crate::config_source::{NetworkConfigSource, ParseContext};
+use crate::imports::{ImportError, ImportLocation};
+use crate::remote::cache::RemoteNetworksCache;
 use crate::RemoteNetworksConfigSource;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 pub mod chainid;
@@ -8,27 +15,294 @@ pub mod chainid;
 pub enum RemoteNetworkError {
     #[error(transparent)]
     ReqwestError(#[from] reqwest::Error),
-    #[error("Unknown format: {}", 0)]
+    #[error("Unknown format: {0}")]
     UnknownFormat(String),
+    #[error(transparent)]
+    ImportError(#[from] ImportError),
+    #[error("Failed to parse {0} networks: {1}")]
+    ParseError(String, String),
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
-pub enum RemoteNetworks {
-    ChainId(Vec<chainid::ChainId>),
+/// A format of remote network lists, registered by name in
+/// [RemoteNetworkFormats]
+pub trait RemoteNetworkFormat: Send + Sync {
+    /// Networks of a list by name
+    fn parse(
+        &self,
+        contents: &str,
+    ) -> Result<Vec<(String, NetworkConfigSource)>, RemoteNetworkError>;
+}
+
+/// The chainid.network format, chains without a usable rpc are skipped
+pub struct ChainIdFormat;
+
+impl RemoteNetworkFormat for ChainIdFormat {
+    fn parse(
+        &self,
+        contents: &str,
+    ) -> Result<Vec<(String, NetworkConfigSource)>, RemoteNetworkError> {
+        let chains = serde_json::from_str::<Vec<chainid::ChainId>>(contents)
+            .map_err(|e| RemoteNetworkError::ParseError("chainid".to_string(), e.to_string()))?;
+        Ok(chains
+            .into_iter()
+            .filter_map(|chain| {
+                let name = chain.short_name.clone();
+                NetworkConfigSource::try_from(chain)
+                    .ok()
+                    .map(|network| (name, network))
+            })
+            .collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedNetworkConfigSource {
+    name: String,
+    #[serde(flatten)]
+    network: NetworkConfigSource,
+}
+
+/// A yaml or json list of networks in the same form as the `networks` of a
+/// yml, with a `name` for each
+pub struct NetworkListFormat;
+
+impl RemoteNetworkFormat for NetworkListFormat {
+    fn parse(
+        &self,
+        contents: &str,
+    ) -> Result<Vec<(String, NetworkConfigSource)>, RemoteNetworkError> {
+        let networks = serde_yaml::from_str::<Vec<NamedNetworkConfigSource>>(contents)
+            .map_err(|e| RemoteNetworkError::ParseError("networks".to_string(), e.to_string()))?;
+        Ok(networks.into_iter().map(|v| (v.name, v.network)).collect())
+    }
+}
+
+/// Formats of remote network lists by name, `chainid` and `networks` are
+/// registered by default
+#[derive(Clone)]
+pub struct RemoteNetworkFormats(HashMap<String, Arc<dyn RemoteNetworkFormat>>);
+
+impl Default for RemoteNetworkFormats {
+    fn default() -> Self {
+        let mut formats = RemoteNetworkFormats(HashMap::new());
+        formats.register("chainid", ChainIdFormat);
+        formats.register("networks", NetworkListFormat);
+        formats
+    }
+}
+
+impl fmt::Debug for RemoteNetworkFormats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<&String> = self.0.keys().collect();
+        names.sort();
+        f.debug_tuple("RemoteNetworkFormats").field(&names).finish()
+    }
+}
+
+impl RemoteNetworkFormats {
+    /// Register a format, replacing any format of the same name
+    pub fn register(&mut self, name: &str, format: impl RemoteNetworkFormat + 'static) {
+        self.0.insert(name.to_string(), Arc::new(format));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn RemoteNetworkFormat> {
+        self.0.get(name).map(|v| v.as_ref())
+    }
 }
 
+/// Networks of a remote list by name
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct RemoteNetworks(pub Vec<(String, NetworkConfigSource)>);
+
 impl RemoteNetworks {
     pub async fn try_from_remote_network_config_source(
         value: RemoteNetworksConfigSource,
+        context: &ParseContext,
     ) -> Result<RemoteNetworks, RemoteNetworkError> {
-        match value.format.as_str() {
-            "chainid" => Ok(Self::ChainId(
-                reqwest::get(value.url)
-                    .await?
-                    .json::<Vec<chainid::ChainId>>()
-                    .await?,
-            )),
-            _ => Err(RemoteNetworkError::UnknownFormat(value.format.clone())),
+        let format = context
+            .remote_network_formats
+            .get(&value.format)
+            .ok_or(RemoteNetworkError::UnknownFormat(value.format.clone()))?;
+
+        let location = context.import_base.resolve(&value.url)?;
+        let contents = match (&location, value.cache_ttl) {
+            (ImportLocation::Url(url), Some(ttl)) => {
+                let cache = RemoteNetworksCache::new(
+                    value.cache_dir.map(Into::into),
+                    Duration::from_secs(ttl),
+                );
+                match cache.get_fresh(url) {
+                    Some(contents) => contents,
+                    None => match context.read(&location).await {
+                        Ok(contents) => {
+                            // a cache that cannot be written only costs a refetch
+                            let _ = cache.put(url, &contents);
+                            contents
+                        }
+                        Err(e) => cache.get_stale(url).ok_or(e)?,
+                    },
+                }
+            }
+            _ => context.read(&location).await?,
+        };
+
+        let mut networks = format.parse(&contents)?;
+        if let Some(chain_ids) = &value.chain_ids {
+            networks.retain(|(_, network)| chain_ids.contains(&network.chain_id));
+        }
+        Ok(RemoteNetworks(networks))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::imports::ImportBase;
+    use httpmock::{Method::GET, MockServer};
+    use tempfile::TempDir;
+
+    fn source(url: String, format: &str) -> RemoteNetworksConfigSource {
+        RemoteNetworksConfigSource {
+            url,
+            format: format.to_string(),
+            chain_ids: None,
+            cache_ttl: None,
+            cache_dir: None,
         }
     }
+
+    #[tokio::test]
+    async fn test_network_list_from_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("networks.yml"),
+            r#"
+- name: mainnet
+  rpc: https://mainnet.node
+  chain-id: 1
+- name: polygon
+  rpc:
+    - https://polygon.node
+    - url: https://other-polygon.node
+      priority: 1
+  chain-id: 137
+  currency: MATIC
+"#,
+        )
+        .unwrap();
+        let context = ParseContext {
+            import_base: ImportBase::Dir(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let networks = RemoteNetworks::try_from_remote_network_config_source(
+            source("./networks.yml".to_string(), "networks"),
+            &context,
+        )
+        .await
+        .unwrap();
+        assert_eq!(networks.0.len(), 2);
+        assert_eq!(networks.0[1].0, "polygon");
+        assert_eq!(networks.0[1].1.currency, Some("MATIC".to_string()));
+
+        let networks = RemoteNetworks::try_from_remote_network_config_source(
+            RemoteNetworksConfigSource {
+                chain_ids: Some(vec![137]),
+                ..source("./networks.yml".to_string(), "networks")
+            },
+            &context,
+        )
+        .await
+        .unwrap();
+        assert_eq!(networks.0.len(), 1);
+        assert_eq!(networks.0[0].0, "polygon");
+
+        let err = RemoteNetworks::try_from_remote_network_config_source(
+            source("./networks.yml".to_string(), "unknown"),
+            &context,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, RemoteNetworkError::UnknownFormat(_)));
+    }
+
+    #[tokio::test]
+    async fn test_cached_remote_networks() {
+        let dir = TempDir::new().unwrap();
+        let server = MockServer::start_async().await;
+        let mut mock = server.mock(|when, then| {
+            when.method(GET).path("/networks");
+            then.body("- name: mainnet\n  rpc: https://mainnet.node\n  chain-id: 1\n");
+        });
+        let value = RemoteNetworksConfigSource {
+            cache_ttl: Some(3600),
+            cache_dir: Some(dir.path().display().to_string()),
+            ..source(server.url("/networks"), "networks")
+        };
+
+        let networks = RemoteNetworks::try_from_remote_network_config_source(
+            value.clone(),
+            &ParseContext::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(networks.0.len(), 1);
+        mock.assert_hits(1);
+
+        // served from the cache while fresh
+        let cached = RemoteNetworks::try_from_remote_network_config_source(
+            value.clone(),
+            &ParseContext::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(cached, networks);
+        mock.assert_hits(1);
+
+        // and past its ttl when the list cannot be fetched
+        mock.delete();
+        let offline = RemoteNetworks::try_from_remote_network_config_source(
+            RemoteNetworksConfigSource {
+                cache_ttl: Some(0),
+                ..value
+            },
+            &ParseContext::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(offline, networks);
+    }
+
+    #[test]
+    fn test_custom_format() {
+        struct SingleNetwork;
+        impl RemoteNetworkFormat for SingleNetwork {
+            fn parse(
+                &self,
+                contents: &str,
+            ) -> Result<Vec<(String, NetworkConfigSource)>, RemoteNetworkError> {
+                Ok(vec![(
+                    "single".to_string(),
+                    NetworkConfigSource {
+                        rpc: contents.trim().parse::<url::Url>().unwrap().into(),
+                        chain_id: 1,
+                        label: None,
+                        network_id: None,
+                        currency: None,
+                    },
+                )])
+            }
+        }
+
+        let mut formats = RemoteNetworkFormats::default();
+        formats.register("single", SingleNetwork);
+        let networks = formats
+            .get("single")
+            .unwrap()
+            .parse("https://rpc.com")
+            .unwrap();
+        assert_eq!(networks[0].0, "single");
+        assert!(formats.get("chainid").is_some());
+        assert!(formats.get("unknown").is_none());
+    }
 }
@@ -0,0 +1,155 @@
+use alloy_ethers_typecast::transaction::ReadableClientError;
+use std::{fmt::Display, future::Future};
+use tracing::warn;
+
+/// An error of a request made against an rpc url, tells apart failures of
+/// the rpc itself, which are worth retrying on another rpc, from errors that
+/// any rpc would give such as reverts
+pub trait RpcRequestError: Display {
+    /// Whether the error is a failure of the rpc, eg a transport error or a
+    /// rate limit, rather than an error of the request itself
+    fn is_rpc_failure(&self) -> bool;
+
+    /// The error given when there are no rpc urls to make the request with
+    fn no_rpc_urls() -> Self;
+}
+
+impl RpcRequestError for ReadableClientError {
+    fn is_rpc_failure(&self) -> bool {
+        !matches!(
+            self,
+            ReadableClientError::AbiDecodedErrorType(_)
+                | ReadableClientError::AbiDecodeFailedErrors(_)
+        )
+    }
+
+    fn no_rpc_urls() -> Self {
+        ReadableClientError::CreateReadableClientHttpError("no rpc urls provided".to_string())
+    }
+}
+
+/// Runs a request against each of the given rpc urls in order until one of
+/// them succeeds. Moves on to the next rpc only if the request failed because
+/// of the rpc, other errors are returned right away. Returns the last error if
+/// all of the rpcs fail.
+pub async fn with_rpc_failover<T, E, F, Fut>(rpcs: &[String], request: F) -> Result<T, E>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: RpcRequestError,
+{
+    let mut last_error = None;
+    for rpc in rpcs {
+        match request(rpc.clone()).await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_rpc_failure() => {
+                warn!("Request to rpc {} failed: {}", rpc, e);
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(E::no_rpc_urls))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_ethers_typecast::{rpc::Response, transaction::ReadableClient};
+    use httpmock::{Method::POST, MockServer};
+    use serde_json::{from_str, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, thiserror::Error)]
+    enum TestError {
+        #[error("rpc failure")]
+        Rpc,
+        #[error("reverted")]
+        Reverted,
+        #[error("no rpc urls")]
+        NoRpcUrls,
+    }
+
+    impl RpcRequestError for TestError {
+        fn is_rpc_failure(&self) -> bool {
+            matches!(self, TestError::Rpc)
+        }
+
+        fn no_rpc_urls() -> Self {
+            TestError::NoRpcUrls
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_rpc_failover() {
+        let failing_server = MockServer::start_async().await;
+        failing_server.mock(|when, then| {
+            when.method(POST).path("/");
+            then.status(500);
+        });
+        let rpc_server = MockServer::start_async().await;
+        rpc_server.mock(|when, then| {
+            when.method(POST).path("/").body_contains("blockNumber");
+            then.json_body_obj(
+                &from_str::<Value>(&Response::new_success(1, "0x10").to_json_string().unwrap())
+                    .unwrap(),
+            );
+        });
+
+        let rpcs = vec![failing_server.url("/"), rpc_server.url("/")];
+        let block_number = with_rpc_failover(&rpcs, |rpc| async move {
+            ReadableClient::new_from_url(rpc)?.get_block_number().await
+        })
+        .await
+        .unwrap();
+        assert_eq!(block_number, 16);
+
+        let result: Result<u64, ReadableClientError> =
+            with_rpc_failover(&rpcs[..1], |rpc| async move {
+                ReadableClient::new_from_url(rpc)?.get_block_number().await
+            })
+            .await;
+        assert!(result.is_err());
+
+        let result: Result<u64, ReadableClientError> = with_rpc_failover(&[], |rpc| async move {
+            ReadableClient::new_from_url(rpc)?.get_block_number().await
+        })
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_rpc_failover_request_errors() {
+        let rpcs = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+        // a request error is not retried on the other rpcs
+        let calls = AtomicUsize::new(0);
+        let result: Result<(), TestError> = with_rpc_failover(&rpcs, |_| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(TestError::Reverted)
+        })
+        .await;
+        assert!(matches!(result, Err(TestError::Reverted)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // rpc failures are retried until a request error
+        let calls = AtomicUsize::new(0);
+        let result: Result<(), TestError> = with_rpc_failover(&rpcs, |rpc| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if rpc == "b" {
+                    Err(TestError::Reverted)
+                } else {
+                    Err(TestError::Rpc)
+                }
+            }
+        })
+        .await;
+        assert!(matches!(result, Err(TestError::Reverted)));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let result: Result<(), TestError> =
+            with_rpc_failover(&[], |_| async { Err(TestError::Rpc) }).await;
+        assert!(matches!(result, Err(TestError::NoRpcUrls)));
+    }
+}
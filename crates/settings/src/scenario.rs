@@ -139,7 +139,7 @@ mod tests {
         networks.insert(
             "mainnet".to_string(),
             NetworkConfigSource {
-                rpc: Url::parse("https://mainnet.node").unwrap(),
+                rpc: Url::parse("https://mainnet.node").unwrap().into(),
                 chain_id: 1,
                 label: Some("Ethereum Mainnet".to_string()),
                 network_id: Some(1),
@@ -201,6 +201,7 @@ mod tests {
         // Construct ConfigSource with the above scenarios
         let config_string = ConfigSource {
             raindex_version: None,
+            imports: vec![],
             using_networks_from: HashMap::new(),
             networks,
             subgraphs: HashMap::new(), // Assuming no subgraphs for simplification
@@ -214,6 +215,7 @@ mod tests {
             deployments: HashMap::new(),
             sentry: None,
             accounts: None, // Assuming no accounts for simplification
+            env_interpolations: vec![],
         };
 
         // Perform the conversion
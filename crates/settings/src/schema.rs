@@ -0,0 +1,202 @@
+use crate::config_source::ConfigSource;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::{
+        InstanceType, Metadata, NumberValidation, RootSchema, Schema, SchemaObject, SingleOrVec,
+        StringValidation, SubschemaValidation,
+    },
+    visit::{visit_schema_object, Visitor},
+    JsonSchema,
+};
+
+/// Pattern of strings with a `${VAR}` or `${VAR:-default}` placeholder
+const PLACEHOLDER_PATTERN: &str = r"\$\{[A-Za-z_][A-Za-z0-9_]*(:-[^}]*)?\}";
+
+/// Json schema of settings ymls and .rain frontmatter, for editors and yaml
+/// language servers to validate and autocomplete them
+pub fn config_source_schema() -> RootSchema {
+    SchemaSettings::draft07()
+        .with_visitor(PlaceholderVisitor)
+        .into_generator()
+        .into_root_schema_for::<ConfigSource>()
+}
+
+/// [config_source_schema] as pretty printed json
+pub fn config_source_schema_json() -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&config_source_schema())
+}
+
+/// Schema of a string matching a pattern
+pub(crate) fn string_schema(pattern: &str, description: &str) -> SchemaObject {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(pattern.to_string()),
+            ..Default::default()
+        })),
+        metadata: Some(Box::new(Metadata {
+            description: Some(description.to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+/// Lets numbers, bools and patterned strings also be a string with a `${VAR}`
+/// placeholder, as placeholders are only resolved when parsing
+#[derive(Debug, Clone)]
+struct PlaceholderVisitor;
+
+impl Visitor for PlaceholderVisitor {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        visit_schema_object(self, schema);
+
+        let accepts_placeholders = match &schema.instance_type {
+            Some(SingleOrVec::Single(instance_type)) => match **instance_type {
+                InstanceType::Integer | InstanceType::Number | InstanceType::Boolean => true,
+                // strings of enums are left as is, as a placeholder would match
+                // every variant of a oneOf
+                InstanceType::String => {
+                    schema.enum_values.is_none()
+                        && schema.string.as_ref().is_some_and(|v| v.pattern.is_some())
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if !accepts_placeholders {
+            return;
+        }
+
+        let mut original = std::mem::take(schema);
+        schema.metadata = original.metadata.take();
+        schema.subschemas = Some(Box::new(SubschemaValidation {
+            any_of: Some(vec![
+                original.into(),
+                string_schema(PLACEHOLDER_PATTERN, "Value with ${VAR} placeholders").into(),
+            ]),
+            ..Default::default()
+        }));
+    }
+}
+
+/// Schema of alloy addresses
+pub(crate) struct AddressSchema;
+
+impl JsonSchema for AddressSchema {
+    fn schema_name() -> String {
+        "Address".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        string_schema("^0x[0-9a-fA-F]{40}$", "Hex encoded address").into()
+    }
+}
+
+/// Schema of alloy U256s, ie a non negative integer or a decimal or hex
+/// string
+pub(crate) struct Uint256Schema;
+
+impl JsonSchema for Uint256Schema {
+    fn schema_name() -> String {
+        "Uint256".to_string()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        let integer = SchemaObject {
+            instance_type: Some(InstanceType::Integer.into()),
+            number: Some(Box::new(NumberValidation {
+                minimum: Some(0.0),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![
+                    integer.into(),
+                    string_schema(
+                        "^([0-9]+|0x[0-9a-fA-F]{1,64})$",
+                        "Decimal or hex encoded 256 bit unsigned integer",
+                    )
+                    .into(),
+                ]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_config_source_schema() {
+        let schema: Value = serde_json::to_value(config_source_schema()).unwrap();
+
+        assert_eq!(schema["title"], "ConfigSource");
+        let properties = schema["properties"].as_object().unwrap();
+        for key in [
+            "imports",
+            "using-networks-from",
+            "networks",
+            "subgraphs",
+            "orderbooks",
+            "tokens",
+            "deployers",
+            "orders",
+            "scenarios",
+            "charts",
+            "deployments",
+            "metaboards",
+            "sentry",
+            "raindex-version",
+            "accounts",
+        ] {
+            assert!(properties.contains_key(key), "missing {}", key);
+        }
+        assert!(!properties.contains_key("env-interpolations"));
+        assert!(schema.get("required").is_none());
+
+        let definitions = &schema["definitions"];
+        let network = &definitions["NetworkConfigSource"];
+        assert_eq!(network["required"], json!(["chain-id", "rpc"]));
+        assert!(network["properties"]["network-id"].is_object());
+
+        let token = &definitions["TokenConfigSource"];
+        assert_eq!(token["required"], json!(["address", "network"]));
+        assert_eq!(
+            definitions["Address"]["anyOf"][0]["pattern"],
+            json!("^0x[0-9a-fA-F]{40}$")
+        );
+
+        let range = &definitions["BlockRange"]["anyOf"];
+        let pattern = range[0]["anyOf"][0]["pattern"].as_str().unwrap();
+        assert!(pattern.starts_with(r"^([0-9]*|-[0-9]+[smhdw]|"));
+        assert!(pattern.contains(r")\.\.("));
+        assert_eq!(range[1]["type"], json!("array"));
+
+        let mark = serde_json::to_string(&definitions["Mark"]).unwrap();
+        assert!(mark.contains("\"recty\""));
+    }
+
+    #[test]
+    fn test_config_source_schema_placeholders() {
+        let schema: Value = serde_json::to_value(config_source_schema()).unwrap();
+        let definitions = &schema["definitions"];
+
+        // interpolated fields also take a placeholder string
+        let chain_id = &definitions["NetworkConfigSource"]["properties"]["chain-id"]["anyOf"];
+        assert_eq!(chain_id[0]["type"], json!("integer"));
+        assert_eq!(chain_id[1]["type"], json!("string"));
+        assert_eq!(chain_id[1]["pattern"], json!(PLACEHOLDER_PATTERN));
+        assert_eq!(
+            definitions["Address"]["anyOf"][1]["pattern"],
+            json!(PLACEHOLDER_PATTERN)
+        );
+    }
+}
@@ -0,0 +1,342 @@
+use crate::config::ParseConfigSourceError;
+use crate::config_source::ConfigSourceError;
+use crate::merge::Provenance;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strict_yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use strict_yaml_rust::scanner::Marker;
+use thiserror::Error;
+use typeshare::typeshare;
+
+/// Location of a piece of a text as a byte range, line and column are 0
+/// based and point at the start of the range
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn from_range(text: &str, start: usize, end: usize) -> Self {
+        TextIndex::new(text).span(start, end)
+    }
+
+    /// Move a span of a text that starts at the given byte offset of an
+    /// enclosing text, eg the frontmatter of a .rain document, to be
+    /// relative to the enclosing text
+    pub fn shift(self, text: &str, offset: usize) -> Self {
+        Self::from_range(text, self.start + offset, self.end + offset)
+    }
+}
+
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Byte offsets of the chars and line starts of a text, to locate many
+/// spans in it without scanning the text up to each of them
+struct TextIndex<'a> {
+    text: &'a str,
+    char_offsets: Vec<usize>,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> TextIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let char_offsets = text.char_indices().map(|(i, _)| i).collect();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        TextIndex {
+            text,
+            char_offsets,
+            line_starts,
+        }
+    }
+
+    /// Byte offset of the char at the given char index
+    fn byte_index(&self, char_index: usize) -> usize {
+        self.char_offsets
+            .get(char_index)
+            .copied()
+            .unwrap_or(self.text.len())
+    }
+
+    fn span(&self, start: usize, end: usize) -> Span {
+        let start = floor_char_boundary(self.text, start);
+        let end = floor_char_boundary(self.text, end.max(start));
+        let line = self.line_starts.partition_point(|&i| i <= start) - 1;
+        Span {
+            start,
+            end,
+            line,
+            column: self.text[self.line_starts[line]..start].chars().count(),
+        }
+    }
+
+    /// Span of the rest of the line from a byte offset, without trailing
+    /// whitespace
+    fn span_to_line_end(&self, start: usize) -> Span {
+        let start = floor_char_boundary(self.text, start);
+        let line = self.text[start..].split('\n').next().unwrap_or("");
+        self.span(start, start + line.trim_end().len())
+    }
+}
+
+/// Spans of the keys of a yml by their dotted path, eg
+/// `orders.my-order.inputs.0.token`, sequence items are located at the
+/// start of the item
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct YamlSpans(pub HashMap<String, Span>);
+
+enum Frame {
+    Mapping { path: String, key: Option<String> },
+    Sequence { path: String, index: usize },
+}
+
+struct SpanCollector<'a> {
+    index: TextIndex<'a>,
+    frames: Vec<Frame>,
+    spans: HashMap<String, Span>,
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+impl SpanCollector<'_> {
+    fn scalar_span(&self, mark: &Marker, value: &str) -> Span {
+        let start = self.index.byte_index(mark.index());
+        self.index.span(start, start + value.len())
+    }
+
+    /// Path of the node starting at the current event, or `None` if the
+    /// node is a mapping key or the document root
+    fn node_path(&mut self, mark: &Marker, scalar: Option<&str>) -> Option<String> {
+        let span = match scalar {
+            Some(value) => self.scalar_span(mark, value),
+            None => self
+                .index
+                .span_to_line_end(self.index.byte_index(mark.index())),
+        };
+        match self.frames.last_mut() {
+            Some(Frame::Mapping { path, key }) => match key.take() {
+                Some(key) => Some(join(path, &key)),
+                None => {
+                    let name = scalar.unwrap_or_default().to_string();
+                    self.spans.insert(join(path, &name), span);
+                    *key = Some(name);
+                    None
+                }
+            },
+            Some(Frame::Sequence { path, index }) => {
+                let item_path = join(path, &index.to_string());
+                *index += 1;
+                self.spans.entry(item_path.clone()).or_insert(span);
+                Some(item_path)
+            }
+            None => Some(String::new()),
+        }
+    }
+}
+
+impl MarkedEventReceiver for SpanCollector<'_> {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(value, ..) => {
+                self.node_path(&mark, Some(&value));
+            }
+            Event::MappingStart => {
+                if let Some(path) = self.node_path(&mark, None) {
+                    self.frames.push(Frame::Mapping { path, key: None });
+                }
+            }
+            Event::SequenceStart => {
+                if let Some(path) = self.node_path(&mark, None) {
+                    self.frames.push(Frame::Sequence { path, index: 0 });
+                }
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.frames.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl YamlSpans {
+    /// Collect the key spans of a yml, a yml that is not valid yields the
+    /// spans found up to the invalid part
+    pub fn parse(source: &str) -> Self {
+        let mut collector = SpanCollector {
+            index: TextIndex::new(source),
+            frames: vec![],
+            spans: HashMap::new(),
+        };
+        let _ = Parser::new(source.chars()).load(&mut collector, false);
+        YamlSpans(collector.spans)
+    }
+
+    /// Span of a path, or of its closest located parent
+    pub fn find(&self, path: &str) -> Option<Span> {
+        let mut path = path;
+        loop {
+            if let Some(span) = self.0.get(path) {
+                return Some(*span);
+            }
+            path = &path[..path.rfind('.')?];
+        }
+    }
+}
+
+/// Locate a config parse error in the yml it came from
+pub fn locate_error(source: &str, error: &ParseConfigSourceError) -> Option<Span> {
+    let yaml_error = match error {
+        ParseConfigSourceError::AtPathError { path, .. } => {
+            return YamlSpans::parse(source).find(path)
+        }
+        ParseConfigSourceError::YamlDeserializerError(e) => e,
+        ParseConfigSourceError::ConfigSourceError(ConfigSourceError::YamlDeserializerError(e)) => e,
+        _ => return None,
+    };
+    yaml_error
+        .location()
+        .map(|location| TextIndex::new(source).span_to_line_end(location.index()))
+}
+
+/// A config parse error with its location, if it could be located
+#[derive(Error, Debug)]
+#[error("{error}")]
+pub struct SpannedConfigError {
+    pub error: ParseConfigSourceError,
+    pub span: Option<Span>,
+    /// Name of the document the span is in, for configs merged from
+    /// several documents
+    pub document: Option<String>,
+}
+
+impl SpannedConfigError {
+    pub fn new(source: &str, error: ParseConfigSourceError) -> Self {
+        SpannedConfigError {
+            span: locate_error(source, &error),
+            error,
+            document: None,
+        }
+    }
+
+    /// Locate an error of a config merged from several named documents, in
+    /// the first of them that set a value of the key the error is about
+    pub fn in_documents(
+        documents: &[(&str, &str)],
+        provenance: &Provenance,
+        error: ParseConfigSourceError,
+    ) -> Self {
+        let sources = match &error {
+            ParseConfigSourceError::AtPathError { path, .. } => provenance.sources_of(path),
+            _ => vec![],
+        };
+        let document = documents
+            .iter()
+            .find(|(name, _)| sources.contains(name))
+            .or(documents.first());
+        match document {
+            Some((name, source)) => SpannedConfigError {
+                document: Some(name.to_string()),
+                ..Self::new(source, error)
+            },
+            None => SpannedConfigError {
+                span: None,
+                error,
+                document: None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    const YAML: &str = r#"networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+tokens:
+    eth:
+        network: unknown
+        address: 0xabc0000000000000000000000000000000000003
+orders:
+    order1:
+        inputs:
+            - token: eth
+        outputs:
+            - token: eth
+"#;
+
+    #[test]
+    fn test_yaml_spans() {
+        let spans = YamlSpans::parse(YAML);
+
+        let span = spans.find("tokens.eth").unwrap();
+        assert_eq!(&YAML[span.start..span.end], "eth");
+        assert_eq!((span.line, span.column), (5, 4));
+
+        let span = spans.find("networks.mainnet.chain-id").unwrap();
+        assert_eq!(&YAML[span.start..span.end], "chain-id");
+        assert_eq!((span.line, span.column), (3, 8));
+
+        let span = spans.find("orders.order1.outputs.0").unwrap();
+        assert_eq!(span.line, 13);
+        assert_eq!(&YAML[span.start..span.end], "token: eth");
+
+        let span = spans.find("orders.order1.inputs.0.token").unwrap();
+        assert_eq!(&YAML[span.start..span.end], "token");
+        assert_eq!(span.line, 11);
+
+        // falls back to the closest parent
+        assert_eq!(spans.find("tokens.eth.decimals"), spans.find("tokens.eth"));
+        assert_eq!(spans.find("unknown"), None);
+    }
+
+    #[test]
+    fn test_span_shift() {
+        let document = format!("{}---\n#calculate-io\n_ _: 0 0;", YAML);
+        let span = YamlSpans::parse(YAML).find("orders.order1").unwrap();
+        assert_eq!(span.shift(&document, 0), span);
+
+        let document = format!("\n\n{}", YAML);
+        let shifted = span.shift(&document, 2);
+        assert_eq!(shifted.line, span.line + 2);
+        assert_eq!(&document[shifted.start..shifted.end], "order1");
+    }
+
+    #[tokio::test]
+    async fn test_locate_error() {
+        let error = Config::try_from_string(YAML.to_string()).await.unwrap_err();
+        let spanned = SpannedConfigError::new(YAML, error);
+        let span = spanned.span.unwrap();
+        assert_eq!(&YAML[span.start..span.end], "eth");
+        assert_eq!(span.line, 5);
+        assert!(spanned.to_string().starts_with("tokens.eth: "));
+
+        let invalid = "networks:\n    mainnet: [\n";
+        let error = Config::try_from_string(invalid.to_string())
+            .await
+            .unwrap_err();
+        let span = locate_error(invalid, &error).unwrap();
+        assert!(span.line >= 1);
+    }
+}
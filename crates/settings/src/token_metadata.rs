@@ -0,0 +1,300 @@
+use crate::config_source::TokenConfigSource;
+use crate::span::YamlSpans;
+use alloy::primitives::{hex, Address};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use typeshare::typeshare;
+
+/// Onchain metadata of an erc20 token, fields are `None` if the token does
+/// not implement them
+#[typeshare]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TokenMetadata {
+    pub decimals: Option<u8>,
+    pub symbol: Option<String>,
+    pub name: Option<String>,
+}
+
+impl TokenMetadata {
+    /// Whether all of decimals, symbol and name were read
+    pub fn is_complete(&self) -> bool {
+        self.decimals.is_some() && self.symbol.is_some() && self.name.is_some()
+    }
+}
+
+impl TokenConfigSource {
+    /// Whether any of decimals, symbol or label is not set
+    pub fn is_missing_metadata(&self) -> bool {
+        self.decimals.is_none() || self.symbol.is_none() || self.label.is_none()
+    }
+
+    /// Fill in the fields that are not set from token metadata, the token's
+    /// name is used as its label. Returns whether anything was filled in.
+    pub fn apply_metadata(&mut self, metadata: &TokenMetadata) -> bool {
+        let before = (self.decimals, self.symbol.is_some(), self.label.is_some());
+        self.decimals = self.decimals.or(metadata.decimals);
+        self.symbol = self.symbol.take().or_else(|| metadata.symbol.clone());
+        self.label = self.label.take().or_else(|| metadata.name.clone());
+        before != (self.decimals, self.symbol.is_some(), self.label.is_some())
+    }
+}
+
+/// On disk cache of token metadata, one file per chain id and token address.
+/// Entries older than the ttl are read from chain again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenMetadataCache {
+    pub dir: PathBuf,
+    pub ttl: Duration,
+}
+
+impl TokenMetadataCache {
+    /// Default ttl of cached token metadata, a week
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+    /// Cache in the given directory or in one in the temp directory
+    pub fn new(dir: Option<PathBuf>, ttl: Duration) -> Self {
+        TokenMetadataCache {
+            dir: dir.unwrap_or_else(|| {
+                std::env::temp_dir()
+                    .join("rain-orderbook")
+                    .join("token-metadata")
+            }),
+            ttl,
+        }
+    }
+
+    fn path(&self, chain_id: u64, address: Address) -> PathBuf {
+        self.dir
+            .join(format!("{}-{}.json", chain_id, hex::encode(address)))
+    }
+
+    /// Cached metadata of a token if it is not older than the ttl
+    pub fn get(&self, chain_id: u64, address: Address) -> Option<TokenMetadata> {
+        let path = self.path(chain_id, address);
+        let age = std::fs::metadata(&path)
+            .ok()?
+            .modified()
+            .ok()?
+            .elapsed()
+            .ok()?;
+        if age >= self.ttl {
+            return None;
+        }
+        let contents = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn put(
+        &self,
+        chain_id: u64,
+        address: Address,
+        metadata: &TokenMetadata,
+    ) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.path(chain_id, address),
+            serde_json::to_string(metadata)?,
+        )
+    }
+
+    /// Drop the cached metadata of a token, if any
+    pub fn remove(&self, chain_id: u64, address: Address) -> std::io::Result<()> {
+        match std::fs::remove_file(self.path(chain_id, address)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn yaml_scalar(value: &str) -> String {
+    serde_yaml::to_string(value)
+        .map(|v| v.trim_end().to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// Write the decimals, symbols and labels of tokens into the `tokens` of a
+/// yml, keeping the rest of the text as is. Only keys the yml does not have
+/// yet are added, right after the token's `address`, and tokens that are
+/// not in the yml or not written as block mappings are left alone.
+pub fn write_token_metadata(yaml: &str, tokens: &HashMap<String, TokenConfigSource>) -> String {
+    let spans = YamlSpans::parse(yaml);
+    let mut insertions: Vec<(usize, String)> = vec![];
+    for (name, token) in tokens {
+        let path = format!("tokens.{}", name);
+        let Some(address) = spans.0.get(&format!("{}.address", path)) else {
+            continue;
+        };
+        let line_start = yaml[..address.start].rfind('\n').map_or(0, |i| i + 1);
+        let indent = &yaml[line_start..address.start];
+        if !indent.trim().is_empty() {
+            continue;
+        }
+
+        let mut lines = String::new();
+        for (key, value) in [
+            ("decimals", token.decimals.map(|v| v.to_string())),
+            ("symbol", token.symbol.as_deref().map(yaml_scalar)),
+            ("label", token.label.as_deref().map(yaml_scalar)),
+        ] {
+            if let Some(value) = value {
+                if !spans.0.contains_key(&format!("{}.{}", path, key)) {
+                    lines.push_str(&format!("{}{}: {}\n", indent, key, value));
+                }
+            }
+        }
+        if lines.is_empty() {
+            continue;
+        }
+
+        let line_end = match yaml[address.start..].find('\n') {
+            Some(i) => address.start + i + 1,
+            None => {
+                lines.insert(0, '\n');
+                yaml.len()
+            }
+        };
+        insertions.push((line_end, lines));
+    }
+
+    let mut result = yaml.to_string();
+    insertions.sort_by(|a, b| b.0.cmp(&a.0));
+    for (index, lines) in insertions {
+        result.insert_str(index, &lines);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config_source::ConfigSource;
+    use tempfile::TempDir;
+
+    fn token(address: Address) -> TokenConfigSource {
+        TokenConfigSource {
+            network: "mainnet".to_string(),
+            address,
+            decimals: None,
+            label: None,
+            symbol: None,
+        }
+    }
+
+    #[test]
+    fn test_apply_metadata() {
+        let metadata = TokenMetadata {
+            decimals: Some(6),
+            symbol: Some("USDC".to_string()),
+            name: Some("USD Coin".to_string()),
+        };
+
+        let mut usdc = token(Address::repeat_byte(0x01));
+        assert!(usdc.is_missing_metadata());
+        assert!(usdc.apply_metadata(&metadata));
+        assert_eq!(usdc.decimals, Some(6));
+        assert_eq!(usdc.symbol, Some("USDC".to_string()));
+        assert_eq!(usdc.label, Some("USD Coin".to_string()));
+        assert!(!usdc.is_missing_metadata());
+
+        // set fields are kept
+        let mut labeled = TokenConfigSource {
+            label: Some("My USDC".to_string()),
+            decimals: Some(6),
+            ..token(Address::repeat_byte(0x01))
+        };
+        assert!(labeled.apply_metadata(&metadata));
+        assert_eq!(labeled.label, Some("My USDC".to_string()));
+        assert!(!labeled.apply_metadata(&metadata));
+        assert!(!token(Address::ZERO).apply_metadata(&TokenMetadata::default()));
+    }
+
+    #[test]
+    fn test_write_token_metadata() {
+        let yaml = r#"networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+tokens:
+    # stablecoins
+    usdc:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000001
+        label: My USDC
+    inline: { network: mainnet, address: 0xabc0000000000000000000000000000000000002 }
+    weth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000003"#;
+        let mut config: ConfigSource = serde_yaml::from_str(yaml).unwrap();
+        for token in config.tokens.values_mut() {
+            token.apply_metadata(&TokenMetadata {
+                decimals: Some(18),
+                symbol: Some("WETH".to_string()),
+                name: Some("Wrapped Ether".to_string()),
+            });
+        }
+
+        let written = write_token_metadata(yaml, &config.tokens);
+        assert_eq!(
+            written,
+            r#"networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+tokens:
+    # stablecoins
+    usdc:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000001
+        decimals: 18
+        symbol: WETH
+        label: My USDC
+    inline: { network: mainnet, address: 0xabc0000000000000000000000000000000000002 }
+    weth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000003
+        decimals: 18
+        symbol: WETH
+        label: Wrapped Ether
+"#
+        );
+
+        let reparsed: ConfigSource = serde_yaml::from_str(&written).unwrap();
+        assert_eq!(reparsed.tokens["usdc"], config.tokens["usdc"]);
+        assert_eq!(reparsed.tokens["weth"], config.tokens["weth"]);
+        assert_eq!(reparsed.tokens["inline"].decimals, None);
+
+        // nothing left to write
+        assert_eq!(write_token_metadata(&written, &reparsed.tokens), written);
+    }
+
+    #[test]
+    fn test_token_metadata_cache() {
+        let dir = TempDir::new().unwrap();
+        let address = Address::repeat_byte(0x01);
+        let metadata = TokenMetadata {
+            decimals: Some(6),
+            symbol: Some("USDC".to_string()),
+            name: None,
+        };
+
+        let cache = TokenMetadataCache::new(
+            Some(dir.path().to_path_buf()),
+            TokenMetadataCache::DEFAULT_TTL,
+        );
+        assert_eq!(cache.get(1, address), None);
+        cache.put(1, address, &metadata).unwrap();
+        assert_eq!(cache.get(1, address), Some(metadata.clone()));
+        assert_eq!(cache.get(137, address), None);
+        assert_eq!(cache.get(1, Address::repeat_byte(0x02)), None);
+
+        let expired = TokenMetadataCache::new(Some(dir.path().to_path_buf()), Duration::ZERO);
+        assert_eq!(expired.get(1, address), None);
+
+        cache.remove(1, address).unwrap();
+        assert_eq!(cache.get(1, address), None);
+        cache.remove(1, address).unwrap();
+    }
+}
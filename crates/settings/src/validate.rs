@@ -0,0 +1,473 @@
+use crate::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+use typeshare::typeshare;
+
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a config, located by the yaml key path of the value
+/// it is about, eg `orders.my-order.inputs.0.token`
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct Diagnostic {
+    pub path: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            path: path.into(),
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Diagnostic {
+            path: path.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.path, self.message)
+    }
+}
+
+/// A config along with the diagnostics of validating it
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ValidatedConfig {
+    #[typeshare(typescript(type = "Config"))]
+    pub config: Config,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl From<Config> for ValidatedConfig {
+    fn from(config: Config) -> Self {
+        let diagnostics = config.validate();
+        ValidatedConfig {
+            config,
+            diagnostics,
+        }
+    }
+}
+
+/// Error severity diagnostics of a config that failed validation
+#[derive(Error, Debug, PartialEq)]
+#[error("Invalid config:\n{}", .0.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("\n"))]
+pub struct ConfigValidationError(pub Vec<Diagnostic>);
+
+/// Name of the entry of a config map that has the given value
+fn name_of<'a, T: PartialEq>(map: &'a HashMap<String, Arc<T>>, value: &T) -> Option<&'a str> {
+    let mut names: Vec<&String> = map
+        .iter()
+        .filter(|(_, v)| v.as_ref() == value)
+        .map(|(k, _)| k)
+        .collect();
+    names.sort();
+    names.first().map(|v| v.as_str())
+}
+
+impl Config {
+    /// Check the consistency of the config as a whole and collect every
+    /// problem found, sorted by path. Unlike the conversion from a
+    /// [ConfigSource] this does not stop at the first problem.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        self.validate_networks(&mut diagnostics);
+        self.validate_orders(&mut diagnostics);
+        self.validate_deployments(&mut diagnostics);
+        self.validate_unused(&mut diagnostics);
+        diagnostics.sort_by(|a, b| a.path.cmp(&b.path).then(a.severity.cmp(&b.severity)));
+        diagnostics
+    }
+
+    /// Validate and fail with the error severity diagnostics if there are any
+    pub fn validate_strict(&self) -> Result<Vec<Diagnostic>, ConfigValidationError> {
+        let (errors, warnings): (Vec<_>, Vec<_>) = self
+            .validate()
+            .into_iter()
+            .partition(|d| d.severity == Severity::Error);
+        if errors.is_empty() {
+            Ok(warnings)
+        } else {
+            Err(ConfigValidationError(errors))
+        }
+    }
+
+    fn validate_networks(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let mut by_chain_id: BTreeMap<u64, Vec<&String>> = BTreeMap::new();
+        for (name, network) in &self.networks {
+            by_chain_id.entry(network.chain_id).or_default().push(name);
+        }
+        for (chain_id, mut names) in by_chain_id {
+            names.sort();
+            for name in names.iter().skip(1) {
+                diagnostics.push(Diagnostic::warning(
+                    format!("networks.{}.chain-id", name),
+                    format!(
+                        "chain id {} is also used by network '{}'",
+                        chain_id, names[0]
+                    ),
+                ));
+            }
+        }
+    }
+
+    fn validate_orders(&self, diagnostics: &mut Vec<Diagnostic>) {
+        for (name, order) in &self.orders {
+            let path = format!("orders.{}", name);
+            let mismatch = |network: &Network| {
+                format!(
+                    "network '{}' does not match the order's network '{}'",
+                    network.name, order.network.name
+                )
+            };
+
+            if let Some(deployer) = &order.deployer {
+                if deployer.network != order.network {
+                    diagnostics.push(Diagnostic::error(
+                        format!("{}.deployer", path),
+                        mismatch(&deployer.network),
+                    ));
+                }
+            }
+            if let Some(orderbook) = &order.orderbook {
+                if orderbook.network != order.network {
+                    diagnostics.push(Diagnostic::error(
+                        format!("{}.orderbook", path),
+                        mismatch(&orderbook.network),
+                    ));
+                }
+            }
+            for (field, ios) in [("inputs", &order.inputs), ("outputs", &order.outputs)] {
+                for (i, io) in ios.iter().enumerate() {
+                    if io.token.network != order.network {
+                        diagnostics.push(Diagnostic::error(
+                            format!("{}.{}.{}.token", path, field, i),
+                            mismatch(&io.token.network),
+                        ));
+                    }
+                }
+            }
+
+            let mut tokens = order.inputs.iter().chain(order.outputs.iter());
+            if let Some(first) = tokens.next() {
+                if !order.inputs.is_empty()
+                    && !order.outputs.is_empty()
+                    && tokens.all(|io| io.token == first.token)
+                {
+                    diagnostics.push(Diagnostic::error(
+                        path.clone(),
+                        format!(
+                            "inputs and outputs are all the same token '{}', the order can only trade it for itself",
+                            name_of(&self.tokens, first.token.as_ref()).unwrap_or("unknown")
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_deployments(&self, diagnostics: &mut Vec<Diagnostic>) {
+        for (name, deployment) in &self.deployments {
+            let path = format!("deployments.{}", name);
+            let deployer = &deployment.scenario.deployer;
+            if deployer.network != deployment.order.network {
+                diagnostics.push(Diagnostic::error(
+                    format!("{}.scenario", path),
+                    format!(
+                        "the scenario's deployer is on network '{}' but the order is on network '{}'",
+                        deployer.network.name, deployment.order.network.name
+                    ),
+                ));
+            }
+            if let Some(order_deployer) = &deployment.order.deployer {
+                if order_deployer != deployer {
+                    diagnostics.push(Diagnostic::error(
+                        format!("{}.scenario", path),
+                        "the scenario's deployer is not the order's deployer",
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_unused(&self, diagnostics: &mut Vec<Diagnostic>) {
+        let mut used_tokens = vec![];
+        let mut used_deployers = vec![];
+        for order in self.orders.values() {
+            used_tokens.extend(
+                order
+                    .inputs
+                    .iter()
+                    .chain(order.outputs.iter())
+                    .map(|io| &io.token),
+            );
+            used_deployers.extend(order.deployer.iter());
+        }
+        for scenario in self.scenarios.values() {
+            used_deployers.push(&scenario.deployer);
+        }
+        for deployment in self.deployments.values() {
+            used_tokens.extend(deployment.deposits.iter().flatten().map(|d| &d.token));
+        }
+
+        for (name, token) in &self.tokens {
+            if !used_tokens.iter().any(|t| *t == token) {
+                diagnostics.push(Diagnostic::warning(
+                    format!("tokens.{}", name),
+                    "token is not used by any order or deployment",
+                ));
+            }
+        }
+        for (name, deployer) in &self.deployers {
+            if !used_deployers.iter().any(|d| *d == deployer) {
+                diagnostics.push(Diagnostic::warning(
+                    format!("deployers.{}", name),
+                    "deployer is not used by any scenario or order",
+                ));
+            }
+        }
+        if !self.deployments.is_empty() {
+            for (name, order) in &self.orders {
+                if !self.deployments.values().any(|d| d.order == *order) {
+                    diagnostics.push(Diagnostic::warning(
+                        format!("orders.{}", name),
+                        "order is not used by any deployment",
+                    ));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+    also-mainnet:
+        rpc: https://other-mainnet.node
+        chain-id: 1
+    polygon:
+        rpc: https://polygon.node
+        chain-id: 137
+deployers:
+    mainnet:
+        address: 0xabc0000000000000000000000000000000000001
+    polygon:
+        address: 0xabc0000000000000000000000000000000000002
+    unused:
+        address: 0xabc0000000000000000000000000000000000003
+        network: mainnet
+tokens:
+    weth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000004
+    usdc:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000005
+    dai:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000006
+orders:
+    self-trade:
+        inputs:
+            - token: weth
+        outputs:
+            - token: weth
+    polygon-order:
+        inputs:
+            - token: usdc
+        outputs:
+            - token: weth
+scenarios:
+    polygon:
+        bindings:
+            key: value
+deployments:
+    polygon:
+        scenario: polygon
+        order: polygon-order
+"#;
+
+    #[tokio::test]
+    async fn test_validate() {
+        let config = Config::try_from_string(CONFIG.to_string()).await.unwrap();
+        let diagnostics = config.validate();
+
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::error(
+                    "deployments.polygon.scenario",
+                    "the scenario's deployer is on network 'polygon' but the order is on network 'mainnet'"
+                ),
+                Diagnostic::warning(
+                    "deployers.mainnet",
+                    "deployer is not used by any scenario or order"
+                ),
+                Diagnostic::warning(
+                    "deployers.unused",
+                    "deployer is not used by any scenario or order"
+                ),
+                Diagnostic::warning(
+                    "networks.mainnet.chain-id",
+                    "chain id 1 is also used by network 'also-mainnet'"
+                ),
+                Diagnostic::error(
+                    "orders.self-trade",
+                    "inputs and outputs are all the same token 'weth', the order can only trade it for itself"
+                ),
+                Diagnostic::warning("orders.self-trade", "order is not used by any deployment"),
+                Diagnostic::warning("tokens.dai", "token is not used by any order or deployment"),
+            ]
+        );
+
+        let err = config.validate_strict().unwrap_err();
+        assert_eq!(err.0.len(), 2);
+        assert!(err.0.iter().all(|d| d.severity == Severity::Error));
+    }
+
+    #[tokio::test]
+    async fn test_validate_unchecked_source() {
+        let config_source = ConfigSource::try_from_string(
+            r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+    polygon:
+        rpc: https://polygon.node
+        chain-id: 137
+deployers:
+    mainnet:
+        address: 0xabc0000000000000000000000000000000000001
+    polygon:
+        address: 0xabc0000000000000000000000000000000000002
+tokens:
+    weth:
+        network: mainnet
+        address: 0xabc0000000000000000000000000000000000004
+    wmatic:
+        network: polygon
+        address: 0xabc0000000000000000000000000000000000005
+orders:
+    order:
+        deployer: mainnet
+        inputs:
+            - token: weth
+        outputs:
+            - token: wmatic
+scenarios:
+    polygon:
+        bindings:
+            key: value
+deployments:
+    polygon:
+        scenario: polygon
+        order: order
+"#
+            .to_string(),
+            None,
+        )
+        .await
+        .unwrap()
+        .0;
+
+        // the conversion stops at the first mismatch
+        assert!(Config::try_from(config_source.clone()).is_err());
+
+        let config = Config::try_from_source_unchecked(config_source).unwrap();
+        let errors: Vec<_> = config
+            .validate()
+            .into_iter()
+            .filter(|d| d.severity == Severity::Error)
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                Diagnostic::error(
+                    "deployments.polygon.scenario",
+                    "the scenario's deployer is on network 'polygon' but the order is on network 'mainnet'"
+                ),
+                Diagnostic::error(
+                    "deployments.polygon.scenario",
+                    "the scenario's deployer is not the order's deployer"
+                ),
+                Diagnostic::error(
+                    "orders.order.outputs.0.token",
+                    "network 'polygon' does not match the order's network 'mainnet'"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_order_networks() {
+        let other_network = Arc::new(Network {
+            name: "other".to_string(),
+            chain_id: 2,
+            ..Network::dummy()
+        });
+        let order = Order {
+            inputs: vec![OrderIO {
+                token: crate::test::mock_token("input"),
+                vault_id: None,
+            }],
+            outputs: vec![OrderIO {
+                token: Arc::new(Token {
+                    network: other_network,
+                    ..(*crate::test::mock_token("output")).clone()
+                }),
+                vault_id: None,
+            }],
+            network: crate::test::mock_network(),
+            deployer: Some(crate::test::mock_deployer()),
+            orderbook: Some(crate::test::mock_orderbook()),
+        };
+        let config = Config {
+            orders: HashMap::from([("order".to_string(), Arc::new(order))]),
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.validate(),
+            vec![Diagnostic::error(
+                "orders.order.outputs.0.token",
+                "network 'other' does not match the order's network 'local'"
+            )]
+        );
+    }
+}
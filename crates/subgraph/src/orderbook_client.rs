@@ -84,6 +84,30 @@ impl OrderbookSubgraphClient {
         Ok(data.orders)
     }
 
+    /// Fetch a single order by its order hash
+    pub async fn order_detail_by_hash(
+        &self,
+        order_hash: Bytes,
+    ) -> Result<Order, OrderbookSubgraphClientError> {
+        let orders = self
+            .orders_list(
+                OrdersListFilterArgs {
+                    owners: vec![],
+                    active: None,
+                    order_hash: Some(order_hash),
+                },
+                PaginationArgs {
+                    page: 1,
+                    page_size: 1,
+                },
+            )
+            .await?;
+        orders
+            .into_iter()
+            .next()
+            .ok_or(OrderbookSubgraphClientError::Empty)
+    }
+
     /// Fetch all orders, paginated
     pub async fn orders_list(
         &self,
@@ -303,6 +327,38 @@ impl OrderbookSubgraphClient {
         Ok(all_pages_merged)
     }
 
+    /// Fetch all pages of vaults_list query for the given owners, including
+    /// vaults with zero balance
+    pub async fn owners_vaults_list_all(
+        &self,
+        owners: Vec<Bytes>,
+    ) -> Result<Vec<Vault>, OrderbookSubgraphClientError> {
+        let mut all_pages_merged = vec![];
+        let mut page = 1;
+
+        loop {
+            let page_data = self
+                .vaults_list(
+                    VaultsListFilterArgs {
+                        owners: owners.clone(),
+                        hide_zero_balance: false,
+                    },
+                    PaginationArgs {
+                        page,
+                        page_size: ALL_PAGES_QUERY_PAGE_SIZE,
+                    },
+                )
+                .await?;
+            if page_data.is_empty() {
+                break;
+            } else {
+                all_pages_merged.extend(page_data);
+                page += 1
+            }
+        }
+        Ok(all_pages_merged)
+    }
+
     /// Fetch all vault deposits + withdrawals merged paginated, for a single vault
     pub async fn vault_balance_changes_list(
         &self,
@@ -32,6 +32,17 @@ sol!(
     OrderbookSubParser, "../../out/OrderBookSubParser.sol/OrderBookSubParser.json"
 );
 
+sol!(
+    #![sol(all_derives = true, rpc = true)]
+    GenericPoolOrderBookV4ArbOrderTaker,
+    "../../out/GenericPoolOrderBookV4ArbOrderTaker.sol/GenericPoolOrderBookV4ArbOrderTaker.json"
+);
+
+sol!(
+    #![sol(all_derives = true, rpc = true)]
+    MockPool, "../../out/MockPool.sol/MockPool.json"
+);
+
 sol!(
     #![sol(all_derives = true, rpc = true)]
     "../../lib/rain.interpreter/lib/rain.interpreter.interface/lib/forge-std/src/interfaces//IMulticall3.sol"
@@ -1,10 +1,21 @@
 use crate::error::CommandResult;
 use dotrain::RainDocument;
-use rain_orderbook_app_settings::{config::Config, config_source::ConfigSource};
+use rain_orderbook_app_settings::{
+    config::Config,
+    config_source::{ConfigSource, ParseContext},
+    token_metadata::{write_token_metadata, TokenMetadataCache},
+    validate::ValidatedConfig,
+};
+use rain_orderbook_common::token_metadata::TokenMetadataResolver;
 
 #[tauri::command]
 pub async fn parse_configstring(text: String) -> CommandResult<ConfigSource> {
-    Ok(ConfigSource::try_from_string(text, None).await?.0)
+    // the frontend sends the config back to be converted, so it gets the
+    // resolved values rather than the env placeholders
+    Ok(ConfigSource::try_from_string(text, None)
+        .await?
+        .0
+        .into_resolved())
 }
 
 #[tauri::command]
@@ -15,13 +26,109 @@ pub async fn merge_configstrings(
     let frontmatter = RainDocument::get_front_matter(dotrain.as_str())
         .unwrap_or("")
         .to_string();
-    let (mut dotrain_config, config) =
-        ConfigSource::try_from_string(frontmatter, Some(config_text)).await?;
-    dotrain_config.merge(config)?;
-    Ok(dotrain_config)
+    // the settings override the values of the frontmatter
+    Ok(ConfigSource::try_from_string_with_override(
+        frontmatter,
+        Some(config_text),
+        &ParseContext::from_process(),
+    )
+    .await?
+    .into_resolved())
 }
 
+/// Convert a config source along with the errors and warnings of validating
+/// it, for the frontend to show
 #[tauri::command]
-pub fn convert_configstring_to_config(config_string: ConfigSource) -> CommandResult<Config> {
-    Ok(config_string.try_into()?)
+pub fn convert_configstring_to_config(
+    config_string: ConfigSource,
+) -> CommandResult<ValidatedConfig> {
+    let config: Config = config_string.try_into()?;
+    Ok(config.into())
+}
+
+/// Read the missing decimals, symbols and labels of the tokens of a settings
+/// yml from chain and write them into it
+#[tauri::command]
+pub async fn resolve_settings_tokens(text: String) -> CommandResult<String> {
+    let mut config = ConfigSource::try_from_string(text.clone(), None).await?.0;
+    TokenMetadataResolver::new(Some(TokenMetadataCache::new(
+        None,
+        TokenMetadataCache::DEFAULT_TTL,
+    )))
+    .resolve_token_sources(&mut config.tokens, &config.networks)
+    .await?;
+    Ok(write_token_metadata(&text, &config.tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_configstring_round_trip_resolves_placeholders() {
+        std::env::set_var("CONFIG_ROUND_TRIP_API_KEY", "secret");
+        std::env::set_var("CONFIG_ROUND_TRIP_CHAIN_ID", "137");
+        let settings = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node/${CONFIG_ROUND_TRIP_API_KEY}
+        chain-id: ${CONFIG_ROUND_TRIP_CHAIN_ID}
+"#;
+        let dotrain = r#"
+subgraphs:
+    mainnet: https://mainnet.subgraph
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;
+"#;
+
+        let parsed = parse_configstring(settings.to_string()).await.unwrap();
+        let merged = merge_configstrings(dotrain.to_string(), settings.to_string())
+            .await
+            .unwrap();
+        for config_source in [parsed, merged] {
+            // the config source goes through the frontend as json
+            let sent_back: ConfigSource =
+                serde_json::from_value(serde_json::to_value(&config_source).unwrap()).unwrap();
+            let config = convert_configstring_to_config(sent_back).unwrap().config;
+            let network = config.networks.get("mainnet").unwrap();
+            assert_eq!(network.rpc.as_str(), "https://mainnet.node/secret");
+            assert_eq!(network.chain_id, 137);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_configstrings_overrides_frontmatter() {
+        let dotrain = r#"
+networks:
+    mainnet:
+        rpc: https://mainnet.node
+        chain-id: 1
+---
+#calculate-io
+_ _: 0 0;
+#handle-io
+:;
+"#;
+        let settings = r#"
+networks:
+    mainnet:
+        rpc: https://my-private.node
+"#;
+
+        let merged = merge_configstrings(dotrain.to_string(), settings.to_string())
+            .await
+            .unwrap();
+        let network = merged.networks.get("mainnet").unwrap();
+        assert_eq!(
+            network.rpc,
+            "https://my-private.node"
+                .parse::<url::Url>()
+                .unwrap()
+                .into()
+        );
+        assert_eq!(network.chain_id, 1);
+    }
 }
@@ -32,8 +32,9 @@ pub async fn call_lsp_problems(
     block_number: Option<u64>,
     bindings: HashMap<String, String>,
     deployer: Option<Address>,
+    settings: Option<String>,
 ) -> CommandResult<Vec<Problem>> {
     Ok(DotrainAddOrderLsp::new(text_document, bindings)
-        .problems(rpc_url, block_number, deployer)
+        .problems(rpc_url, block_number, deployer, settings)
         .await)
 }
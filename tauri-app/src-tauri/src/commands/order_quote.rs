@@ -3,7 +3,8 @@ use alloy::primitives::{Address, U256};
 use rain_orderbook_bindings::IOrderBookV4::Quote;
 use rain_orderbook_common::fuzz::{RainEvalResults, RainEvalResultsTable};
 use rain_orderbook_quote::{
-    get_order_quotes, BatchOrderQuotesResponse, NewQuoteDebugger, QuoteDebugger, QuoteTarget,
+    get_order_quotes, signed_context::SignedContextSource, BatchOrderQuotesResponse,
+    NewQuoteDebugger, QuoteDebugger, QuoteTarget,
 };
 use rain_orderbook_subgraph_client::types::common::*;
 
@@ -12,8 +13,14 @@ pub async fn batch_order_quotes(
     orders: Vec<Order>,
     block_number: Option<u64>,
     rpc_url: String,
+    signed_context: Option<Vec<SignedContextSource>>,
 ) -> CommandResult<Vec<BatchOrderQuotesResponse>> {
-    Ok(get_order_quotes(orders, block_number, rpc_url).await?)
+    let signed_context = signed_context
+        .unwrap_or_default()
+        .into_iter()
+        .map(SignedContextSource::resolve)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(get_order_quotes(orders, block_number, rpc_url, signed_context).await?)
 }
 
 #[tauri::command]
@@ -133,7 +140,7 @@ amount price: 16 52;
         let calldata = AddOrderArgs::new_from_deployment(dotrain, deployment)
             .await
             .unwrap()
-            .try_into_call(local_evm.url())
+            .try_into_call(&[local_evm.url()])
             .await
             .unwrap()
             .abi_encode();
@@ -1,7 +1,7 @@
 use crate::error::CommandResult;
 use crate::toast::toast_error;
 use crate::transaction_status::TransactionStatusNoticeRwLock;
-use alloy::primitives::{Bytes, U256};
+use alloy::primitives::Bytes;
 use rain_orderbook_common::{
     csv::TryIntoCsv,
     deposit::DepositArgs,
@@ -131,10 +131,9 @@ pub async fn vault_deposit_approve_calldata(
     app_handle: AppHandle,
     deposit_args: DepositArgs,
     transaction_args: TransactionArgs,
-    current_allowance: U256,
 ) -> CommandResult<Bytes> {
     let calldata = deposit_args
-        .get_approve_calldata(transaction_args, current_allowance)
+        .get_approve_calldata(transaction_args)
         .await
         .map_err(|e| {
             toast_error(app_handle.clone(), e.to_string());
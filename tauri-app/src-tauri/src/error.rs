@@ -109,6 +109,9 @@ pub enum CommandError {
 
     #[error(transparent)]
     RainEvalResultError(#[from] rain_orderbook_common::fuzz::RainEvalResultError),
+
+    #[error(transparent)]
+    TokenMetadataError(#[from] rain_orderbook_common::token_metadata::TokenMetadataError),
 }
 
 impl Serialize for CommandError {
@@ -10,7 +10,10 @@ use commands::app::get_app_commit_sha;
 use commands::authoring_meta::get_authoring_meta_v2_for_scenarios;
 use commands::chain::{get_block_number, get_chainid};
 use commands::charts::make_charts;
-use commands::config::{convert_configstring_to_config, merge_configstrings, parse_configstring};
+use commands::config::{
+    convert_configstring_to_config, merge_configstrings, parse_configstring,
+    resolve_settings_tokens,
+};
 use commands::dotrain::parse_dotrain;
 use commands::dotrain_add_order_lsp::{call_lsp_completion, call_lsp_hover, call_lsp_problems};
 use commands::order::{
@@ -68,6 +71,7 @@ fn run_tauri_app() {
             parse_configstring,
             merge_configstrings,
             convert_configstring_to_config,
+            resolve_settings_tokens,
             make_charts,
             order_add_calldata,
             order_remove_calldata,
@@ -7,14 +7,24 @@ import type { Hex } from 'viem';
 import { mockIPC } from '@tauri-apps/api/mocks';
 import type { RainEvalResultsTable } from '$lib/typeshare/config';
 
+/**
+ * Signed context of a quote, either already signed or as context values with
+ * the private key to sign them with
+ */
+export type SignedContextSource =
+  | { signer: Hex; context: string[]; signature: Hex }
+  | { signerKey: Hex; context: string[] };
+
 export async function batchOrderQuotes(
   orders: Order[],
   blockNumber?: number,
+  signedContext?: SignedContextSource[],
 ): Promise<BatchOrderQuotesResponse[]> {
   return invoke('batch_order_quotes', {
     orders,
     blockNumber,
     rpcUrl: get(rpcUrl),
+    signedContext,
   });
 }
 
@@ -1,5 +1,5 @@
 import { settingsText } from '$lib/stores/settings';
-import type { Config, ConfigSource } from '$lib/typeshare/config';
+import type { ConfigSource, ValidatedConfig } from '$lib/typeshare/config';
 import { invoke } from '@tauri-apps/api';
 import { get } from 'svelte/store';
 
@@ -9,5 +9,10 @@ export const parseConfigSource = async (text: string): Promise<ConfigSource> =>
 export const mergeDotrainConfigWithSettings = async (dotrain: string): Promise<ConfigSource> =>
   invoke('merge_configstrings', { dotrain, configText: get(settingsText) });
 
-export const convertConfigstringToConfig = async (configString: ConfigSource): Promise<Config> =>
+export const convertConfigstringToConfig = async (
+  configString: ConfigSource,
+): Promise<ValidatedConfig> =>
   invoke('convert_configstring_to_config', { configString });
+
+export const resolveSettingsTokens = async (text: string): Promise<string> =>
+  invoke('resolve_settings_tokens', { text });
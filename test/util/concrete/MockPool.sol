@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: CAL
+pragma solidity =0.8.25;
+
+import {IERC20} from "openzeppelin-contracts/contracts/token/ERC20/IERC20.sol";
+import {SafeERC20} from "openzeppelin-contracts/contracts/token/ERC20/utils/SafeERC20.sol";
+
+/// Minimal external liquidity source for arb tests. Swaps at whatever rate the
+/// caller asks for, as long as the pool holds enough of the output token.
+contract MockPool {
+    using SafeERC20 for IERC20;
+
+    function swap(address tokenIn, address tokenOut, uint256 amountIn, uint256 amountOut) external {
+        IERC20(tokenIn).safeTransferFrom(msg.sender, address(this), amountIn);
+        IERC20(tokenOut).safeTransfer(msg.sender, amountOut);
+    }
+}